@@ -5,27 +5,144 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::Utc;
 
+use crate::graph::node::node_context::NodeContext;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MessageType {
     Text(String),
-    Image,
-    Audio,
+    Image(MediaContent),
+    Audio(MediaContent),
+    // Any other document, e.g. a pdf
+    File(MediaContent),
+    Location(Location),
+    // Text offering replies the recipient picks from
+    QuickReplies(QuickReplies),
+}
+
+impl MessageType {
+    /// Text a person reads in the message: the text itself, the caption of media, the name of a location
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            MessageType::Text(text) => Some(text),
+            MessageType::Image(media) | MessageType::Audio(media) | MessageType::File(media) => media.caption.as_deref(),
+            MessageType::Location(location) => location.name.as_deref(),
+            MessageType::QuickReplies(quick_replies) => Some(&quick_replies.text),
+        }
+    }
+}
+
+/// Where the payload of a media message lives
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaSource {
+    Url(String),
+    // Base64 in json
+    Bytes(#[serde(with = "base64_bytes")] Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaContent {
+    pub source: MediaSource,
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+impl MediaContent {
+    pub fn new(source: MediaSource, mime_type: String) -> Self {
+        MediaContent { source, mime_type, caption: None, file_name: None }
+    }
+
+    pub fn with_caption(mut self, caption: String) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+
+    pub fn with_file_name(mut self, file_name: String) -> Self {
+        self.file_name = Some(file_name);
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuickReplies {
+    pub text: String,
+    pub replies: Vec<QuickReply>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuickReply {
+    // Sent back when the reply is picked
+    pub id: String,
+    pub title: String,
+}
+
+mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        BASE64.decode(String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "MessageData")]
 pub struct Message {
     id: String,
     pub content: MessageType,
     pub sender: String,
     pub recipient: String,
     pub timestamp: String,
+    pub role: MessageRole,
+}
+
+// Messages serialized before roles existed have none, it is guessed from the sender then
+#[derive(Deserialize)]
+struct MessageData {
+    id: String,
+    content: MessageType,
+    sender: String,
+    recipient: String,
+    timestamp: String,
+    #[serde(default)]
+    role: Option<MessageRole>,
+}
+
+impl From<MessageData> for Message {
+    fn from(data: MessageData) -> Self {
+        let role = data.role.unwrap_or_else(|| MessageRole::from_sender(&data.sender));
+        Message::new_with_id(data.id, data.sender, data.content, data.recipient, data.timestamp).with_role(role)
+    }
 }
 
 impl Message {
     pub fn new(sender: String, content: String, recipient: String) -> Self {
+        Message::new_with_content(sender, MessageType::Text(content), recipient)
+    }
+
+    /// The role is guessed from the sender, see `MessageRole::from_sender`
+    pub fn new_with_content(sender: String, content: MessageType, recipient: String) -> Self {
         Message {
             id: Uuid::new_v4().to_string(),
-            content: MessageType::Text(content),
+            content,
+            role: MessageRole::from_sender(&sender),
             sender,
             recipient,
             timestamp: Utc::now().to_rfc3339(),
@@ -36,23 +153,72 @@ impl Message {
         Message {
             id,
             content,
+            role: MessageRole::from_sender(&sender),
             sender,
             recipient,
             timestamp,
         }
     }
 
+    pub fn with_role(mut self, role: MessageRole) -> Self {
+        self.role = role;
+        self
+    }
+
     pub fn get_id(&self) -> String {
         self.id.clone()
     }
 }
 
+// Sender of the messages written by the AI of the flow
+pub const AI_SENDER: &str = "ai";
+
+/// Who a message comes from, from the point of view of the flow
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageRole {
+    // The person the flow talks to
+    #[default]
+    User,
+    // The AI of the flow
+    Assistant,
+    // Instructions for the AI, never delivered to the user
+    System,
+    // Result of a tool called by the AI, the sender names the tool
+    Tool,
+    // A person answering on behalf of the flow
+    HumanAgent,
+}
+
+impl MessageRole {
+    /// Role of a message that doesn't state one: `ai` is the assistant, anyone else the user
+    pub fn from_sender(sender: &str) -> Self {
+        if sender == AI_SENDER {
+            MessageRole::Assistant
+        } else {
+            MessageRole::User
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversationStatus {
+    #[default]
+    Active,
+    // A terminal node finished, the conversation does not accept messages anymore
+    Completed,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Conversation {
     pub id: String,
     history: Vec<Message>,
     current_node_id: String,
     timeout: i16,
+    // Variables owned by this conversation, carried from node to node
+    context: NodeContext,
+    status: ConversationStatus,
 }
 
 impl Conversation {
@@ -60,8 +226,10 @@ impl Conversation {
         Conversation {
             id,
             history: Vec::new(),
-            current_node_id: current_node_id,
+            current_node_id,
             timeout: 0,
+            context: NodeContext::new(),
+            status: ConversationStatus::Active,
         }
     }
 
@@ -84,6 +252,26 @@ impl Conversation {
     pub fn get_current_node_id(&self) -> String {
         self.current_node_id.clone()
     }
+
+    pub fn set_context(&mut self, context: NodeContext) {
+        self.context = context;
+    }
+
+    pub fn get_context(&self) -> NodeContext {
+        self.context.clone()
+    }
+
+    pub fn set_status(&mut self, status: ConversationStatus) {
+        self.status = status;
+    }
+
+    pub fn get_status(&self) -> ConversationStatus {
+        self.status
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.status == ConversationStatus::Completed
+    }
 }
 
 #[async_trait]
@@ -92,6 +280,45 @@ pub trait ConversationRepository : Send + Sync {
     async fn get_conversation_by_recipient(&self, recipient: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_conversation_by_sender(&self, sender: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_last_conversation_by_recipient(&self, recipient: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>>;
-    async fn save_conversation(&mut self, conversation: Conversation) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
-    async fn update_conversation(&mut self, conversation_id: String, conversation: Conversation) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
-}
\ No newline at end of file
+    async fn save_conversation(&self, conversation: Conversation) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    async fn update_conversation(&self, conversation_id: String, conversation: Conversation) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_media_messages_round_trip_through_json() {
+        let image = MediaContent::new(MediaSource::Bytes(vec![137, 80, 78, 71]), "image/png".to_string())
+            .with_caption("My receipt".to_string());
+        let message = Message::new_with_content("user".to_string(), MessageType::Image(image), "ai".to_string());
+
+        let json = serde_json::to_value(&message).unwrap();
+
+        assert_eq!(json["content"]["Image"]["source"], json!({"bytes": "iVBORw=="}));
+        assert_eq!(serde_json::from_value::<Message>(json).unwrap(), message);
+        assert_eq!(message.content.text(), Some("My receipt"));
+    }
+
+    #[test]
+    fn test_message_role() {
+        assert_eq!(Message::new("ai".to_string(), "hi".to_string(), "+34600000000".to_string()).role, MessageRole::Assistant);
+        assert_eq!(Message::new("+34600000000".to_string(), "hi".to_string(), "ai".to_string()).role, MessageRole::User);
+
+        let message = Message::new("maria".to_string(), "hi".to_string(), "+34600000000".to_string()).with_role(MessageRole::HumanAgent);
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["role"], json!("human_agent"));
+
+        // Messages serialized before roles existed get the role of their sender
+        let mut json = json;
+        json.as_object_mut().unwrap().remove("role");
+        assert_eq!(serde_json::from_value::<Message>(json).unwrap().role, MessageRole::User);
+
+        let mut json = serde_json::to_value(Message::new("ai".to_string(), "hi".to_string(), "+34600000000".to_string())).unwrap();
+        json.as_object_mut().unwrap().remove("role");
+        assert_eq!(serde_json::from_value::<Message>(json).unwrap().role, MessageRole::Assistant);
+    }
+}
@@ -0,0 +1,113 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Hands out one lock per conversation id, so messages of the same conversation are
+/// processed one after the other while different conversations run in parallel.
+#[derive(Default)]
+pub struct ConversationLocks {
+    locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+/// Keeps the conversation locked until dropped
+pub struct ConversationGuard<'a> {
+    locks: &'a ConversationLocks,
+    conversation_id: String,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl ConversationLocks {
+    pub fn new() -> Self {
+        ConversationLocks::default()
+    }
+
+    pub async fn lock(&self, conversation_id: &str) -> ConversationGuard<'_> {
+        let lock = self
+            .locks
+            .lock()
+            .unwrap()
+            .entry(conversation_id.to_string())
+            .or_default()
+            .clone();
+
+        ConversationGuard {
+            locks: self,
+            conversation_id: conversation_id.to_string(),
+            guard: Some(lock.lock_owned().await),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.locks.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Drop for ConversationGuard<'_> {
+    fn drop(&mut self) {
+        self.guard.take();
+
+        // Forget the lock once nobody holds or waits for it
+        let mut locks = self.locks.locks.lock().unwrap();
+        if locks
+            .get(&self.conversation_id)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.conversation_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+
+    // Polling once tells whether the lock is free without depending on timing
+    #[tokio::test]
+    async fn test_same_conversation_is_serialized() {
+        let locks = ConversationLocks::new();
+
+        let guard = locks.lock("conv_a").await;
+        assert!(locks.lock("conv_a").now_or_never().is_none());
+
+        drop(guard);
+        assert!(locks.lock("conv_a").now_or_never().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_lock_is_acquired_once_released() {
+        let locks = ConversationLocks::new();
+
+        let guard = locks.lock("conv_a").await;
+        let mut waiting = Box::pin(locks.lock("conv_a"));
+        assert!((&mut waiting).now_or_never().is_none());
+
+        drop(guard);
+        assert!(waiting.now_or_never().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_different_conversations_do_not_block() {
+        let locks = ConversationLocks::new();
+
+        let _guard = locks.lock("conv_a").await;
+
+        assert!(locks.lock("conv_b").now_or_never().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_released_locks_are_removed() {
+        let locks = ConversationLocks::new();
+
+        let guard = locks.lock("conv_a").await;
+        assert_eq!(locks.len(), 1);
+
+        drop(guard);
+        assert!(locks.is_empty());
+    }
+}
@@ -1,19 +1,23 @@
-use std::{error::Error, fmt::{Display, Formatter}, vec};
+use std::{error::Error, fmt::{Display, Formatter}, sync::Arc, vec};
 
 use crate::{flow::conversation::Message, graph::{flow_graph::flow_graph::FlowGraph, node::node_context::{NodeContext, Value}}};
 
-use super::conversation::{ConversationRepository};
+use super::{conversation::{Conversation, ConversationRepository, ConversationStatus}, conversation_locks::ConversationLocks};
 
 pub struct FlowManager {
-    flow_graph: FlowGraph,
-    conversation_repository: Box<dyn ConversationRepository>,
+    flow_graph: Arc<FlowGraph>,
+    conversation_repository: Arc<dyn ConversationRepository>,
+    conversation_locks: ConversationLocks,
 }
 
 #[derive(Debug)]
 pub enum FlowManagerError {
     NextNodeNotFound(String),
     NodeNotFound(String),
+    NoStartNode,
     ConversationNotFound(String),
+    ConversationCompleted(String),
+    ConversationCreationFailed(Box<dyn Error>),
     ConversationUpdateFailed(Box<dyn Error>),
     NodeExecutionFailed(Box<dyn Error>),
     GraphTraversalFailed(Box<dyn Error>),
@@ -24,7 +28,10 @@ impl Display for FlowManagerError {
         match self {
             FlowManagerError::NodeNotFound(node_id) => write!(f, "Node not found: {}", node_id),
             FlowManagerError::NextNodeNotFound(node_id) => write!(f, "Next node not found for: {}", node_id),
+            FlowManagerError::NoStartNode => write!(f, "The flow graph declares no start node"),
             FlowManagerError::ConversationNotFound(conv_id) => write!(f, "Conversation not found: {}", conv_id),
+            FlowManagerError::ConversationCompleted(conv_id) => write!(f, "Conversation already completed: {}", conv_id),
+            FlowManagerError::ConversationCreationFailed(err) => write!(f, "Failed to create conversation: {}", err),
             FlowManagerError::ConversationUpdateFailed(err) => write!(f, "Failed to update conversation: {}", err),
             FlowManagerError::NodeExecutionFailed(err) => write!(f, "Node execution failed: {}", err),
             FlowManagerError::GraphTraversalFailed(err) => write!(f, "Graph traversal failed: {}", err),
@@ -35,6 +42,7 @@ impl Display for FlowManagerError {
 impl Error for FlowManagerError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
+            FlowManagerError::ConversationCreationFailed(err) => Some(err.as_ref()),
             FlowManagerError::ConversationUpdateFailed(err) => Some(err.as_ref()),
             FlowManagerError::NodeExecutionFailed(err) => Some(err.as_ref()),
             FlowManagerError::GraphTraversalFailed(err) => Some(err.as_ref()),
@@ -44,54 +52,430 @@ impl Error for FlowManagerError {
 }
 
 impl FlowManager {
-    pub fn new(conversation_repository: Box<dyn ConversationRepository>, flow_graph: FlowGraph) -> Self {
+    pub fn new(conversation_repository: Arc<dyn ConversationRepository>, flow_graph: Arc<FlowGraph>) -> Self {
         FlowManager {
-            flow_graph: flow_graph,
-            conversation_repository: conversation_repository,
+            flow_graph,
+            conversation_repository,
+            conversation_locks: ConversationLocks::new(),
         }
     }
 
-    pub async fn trigger_conversation(&mut self, conversation_id: String, new_message: Message) -> Result<NodeContext, FlowManagerError> {
+    pub fn get_flow_graph(&self) -> Arc<FlowGraph> {
+        self.flow_graph.clone()
+    }
+
+    /// Creates a conversation positioned on the start node of the flow graph and saves it
+    pub async fn create_conversation(&self, conversation_id: String) -> Result<Conversation, FlowManagerError> {
+        let start_node_id = self.flow_graph
+            .get_start_node_id()
+            .ok_or(FlowManagerError::NoStartNode)?;
+
+        let conversation = Conversation::new(conversation_id, start_node_id.to_string());
+
+        self.conversation_repository
+            .save_conversation(conversation.clone()).await
+            .map_err(|e| FlowManagerError::ConversationCreationFailed(e))?;
+
+        Ok(conversation)
+    }
+
+    /// Moves a conversation back to the start node with an empty context, the history is kept
+    pub async fn restart_conversation(&self, conversation_id: String) -> Result<Conversation, FlowManagerError> {
+        let _conversation_guard = self.conversation_locks.lock(&conversation_id).await;
+
+        let start_node_id = self.flow_graph
+            .get_start_node_id()
+            .ok_or(FlowManagerError::NoStartNode)?;
+
         let mut conversation = self.conversation_repository
             .get_conversation(conversation_id.clone()).await
             .map_err(|_| FlowManagerError::ConversationNotFound(conversation_id.clone()))?;
-        
+
+        conversation.set_current_node_id(start_node_id.to_string());
+        conversation.set_context(NodeContext::new());
+        conversation.set_status(ConversationStatus::Active);
+
+        self.conversation_repository
+            .update_conversation(conversation_id, conversation.clone()).await
+            .map_err(|e| FlowManagerError::ConversationUpdateFailed(e))?;
+
+        Ok(conversation)
+    }
+
+    /// Executes the current node of the conversation with the conversation's own context,
+    /// moves it to the next node and persists the resulting context through the repository.
+    /// Messages of the same conversation are processed in arrival order, other conversations
+    /// are not blocked meanwhile. Once a terminal node ran the conversation is completed and
+    /// further messages are rejected.
+    pub async fn trigger_conversation(&self, conversation_id: String, new_message: Message) -> Result<NodeContext, FlowManagerError> {
+        let _conversation_guard = self.conversation_locks.lock(&conversation_id).await;
+
+        let mut conversation = self.conversation_repository
+            .get_conversation(conversation_id.clone()).await
+            .map_err(|_| FlowManagerError::ConversationNotFound(conversation_id.clone()))?;
+
+        if conversation.is_completed() {
+            return Err(FlowManagerError::ConversationCompleted(conversation_id));
+        }
+
         let current_node_id = conversation.get_current_node_id();
 
         let current_node = self.flow_graph
-            .get_node_mut(&current_node_id)
+            .get_node(&current_node_id)
             .map_err(|_| FlowManagerError::NodeNotFound(current_node_id.clone()))?;
 
-        let messages = [conversation.get_messages(), vec![new_message.clone()]].concat();
+        let history = conversation.get_messages();
+        let history_len = history.len();
+        let messages = [history, vec![new_message.clone()]].concat();
 
-        current_node.set_var_context("messages".to_string(), Value::Messages(messages)); 
-        current_node.set_var_context("trigger_message".to_string(), Value::Messages(vec![new_message]));
+        // Only the conversation scope is persisted, the others are rebuilt on every trigger
+        let mut context = conversation.get_context();
+        context.global = self.flow_graph.get_global_variables().clone();
+        context.transient.insert("messages".to_string(), Value::Messages(messages));
+        context.transient.insert("trigger_message".to_string(), Value::Messages(vec![new_message]));
 
-        current_node.execute_actions().await
-            .map_err(|e| FlowManagerError::NodeExecutionFailed(e))?;
+        let final_node_context = current_node.execute_actions(context).await
+            .map_err(FlowManagerError::NodeExecutionFailed)?;
 
-        let final_node_context = current_node.get_node_context().clone();
+        // Terminal nodes end the conversation, their outgoing edges are never taken
+        let new_current_node_id = if current_node.is_terminal() {
+            conversation.set_status(ConversationStatus::Completed);
+            Some(current_node_id.clone())
+        } else {
+            self.flow_graph
+                .find_next_node(&current_node_id, &final_node_context).await
+        };
 
-        let new_current_node_id = self.flow_graph
-            .find_next_node(&current_node_id, &final_node_context).await;
-
-        if let Some(Value::Messages(messages)) = final_node_context.variables.get("messages"){
-            conversation.add_messages(messages.clone());
+        // Only the messages produced during this trigger are new to the history
+        if let Some(Value::Messages(messages)) = final_node_context.transient.get("messages") {
+            conversation.add_messages(messages.iter().skip(history_len).cloned().collect());
         }
 
-        match new_current_node_id {
-            Some(node_id) => {
-                conversation.set_current_node_id(node_id);
-            },
-            None => {
-                return Err(FlowManagerError::NextNodeNotFound(current_node_id));
-            },
+        // Without a next node the conversation stays where it is, but what happened is still saved
+        if let Some(node_id) = &new_current_node_id {
+            conversation.set_current_node_id(node_id.clone());
         }
 
+        conversation.set_context(final_node_context.conversation_scope());
+
         self.conversation_repository
             .update_conversation(conversation_id, conversation).await
             .map_err(|e| FlowManagerError::ConversationUpdateFailed(e))?;
 
-        Ok(final_node_context.clone())
+        match new_current_node_id {
+            Some(_) => Ok(final_node_context),
+            None => Err(FlowManagerError::NextNodeNotFound(current_node_id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use tokio::sync::Barrier;
+
+    use crate::{
+        flow::tests::conversation_repository_implementation::InMemoryConversationRepository,
+        graph::{action::action::Action, edge::edge::Edge, node::node::Node},
+    };
+
+    use super::*;
+
+    // Counts how many times the conversation went through the node
+    #[derive(Clone)]
+    struct VisitCounterAction;
+
+    #[async_trait]
+    impl Action for VisitCounterAction {
+        async fn execute(&self, context: &mut NodeContext) -> Result<NodeContext, Box<dyn std::error::Error>> {
+            let visits = match context.variables.get("visits") {
+                Some(Value::Number(visits)) => *visits,
+                _ => 0.0,
+            };
+            context.variables.insert("visits".to_string(), Value::Number(visits + 1.0));
+            Ok(context.clone())
+        }
+
+        fn clone_box(&self) -> Box<dyn Action> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn create_looping_graph() -> FlowGraph {
+        let mut graph = FlowGraph::new();
+        let node = Node::builder(
+            "node1".to_string(),
+            "conversational".to_string(),
+            "Node 1".to_string(),
+            "Node 1 description".to_string(),
+        )
+        .with_action(VisitCounterAction)
+        .build();
+
+        graph.add_node(node).unwrap();
+        graph
+            .add_edge(Edge::new("loop".to_string(), "node1".to_string(), "node1".to_string()))
+            .unwrap();
+        graph
+    }
+
+    fn user_message(content: &str) -> Message {
+        Message::new("user".to_string(), content.to_string(), "ai".to_string())
+    }
+
+    // Waits until every conversation sharing the barrier reached the node
+    #[derive(Clone)]
+    struct BarrierAction(Arc<Barrier>);
+
+    #[async_trait]
+    impl Action for BarrierAction {
+        async fn execute(&self, context: &mut NodeContext) -> Result<NodeContext, Box<dyn std::error::Error>> {
+            self.0.wait().await;
+            Ok(context.clone())
+        }
+
+        fn clone_box(&self) -> Box<dyn Action> {
+            Box::new(self.clone())
+        }
+    }
+
+    // Yields in the middle of a read-modify-write of the context
+    #[derive(Clone)]
+    struct SlowVisitCounterAction;
+
+    #[async_trait]
+    impl Action for SlowVisitCounterAction {
+        async fn execute(&self, context: &mut NodeContext) -> Result<NodeContext, Box<dyn std::error::Error>> {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            VisitCounterAction.execute(context).await
+        }
+
+        fn clone_box(&self) -> Box<dyn Action> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conversations_do_not_share_context() {
+        let repository = InMemoryConversationRepository::new();
+        repository.save_conversation(Conversation::new("conv_a".to_string(), "node1".to_string())).await.unwrap();
+        repository.save_conversation(Conversation::new("conv_b".to_string(), "node1".to_string())).await.unwrap();
+
+        let graph = Arc::new(create_looping_graph());
+        let flow_manager = FlowManager::new(Arc::new(repository), graph.clone());
+
+        flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi")).await.unwrap();
+        let context_a = flow_manager.trigger_conversation("conv_a".to_string(), user_message("again")).await.unwrap();
+        let context_b = flow_manager.trigger_conversation("conv_b".to_string(), user_message("hi")).await.unwrap();
+
+        assert_eq!(context_a.variables.get("visits"), Some(&Value::Number(2.0)));
+        assert_eq!(context_b.variables.get("visits"), Some(&Value::Number(1.0)));
+        assert!(graph.get_node("node1").unwrap().get_node_context().variables.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_persists_context_and_new_messages() {
+        let repository = InMemoryConversationRepository::new();
+        repository.save_conversation(Conversation::new("conv_a".to_string(), "node1".to_string())).await.unwrap();
+
+        let mut graph = create_looping_graph();
+        graph.set_global_variable("company".to_string(), Value::String("Acme".to_string()));
+        let flow_manager = FlowManager::new(Arc::new(repository.clone()), Arc::new(graph));
+
+        flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi")).await.unwrap();
+        let final_context = flow_manager.trigger_conversation("conv_a".to_string(), user_message("again")).await.unwrap();
+
+        let conversation = repository.get_conversation("conv_a".to_string()).await.unwrap();
+        let context = conversation.get_context();
+
+        assert_eq!(final_context.global.get("company"), Some(&Value::String("Acme".to_string())));
+        assert!(final_context.transient.contains_key("trigger_message"));
+        assert_eq!(conversation.get_messages().len(), 2);
+        assert_eq!(context.variables.get("visits"), Some(&Value::Number(2.0)));
+        assert!(context.transient.is_empty());
+        assert!(context.global.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_serializes_messages_of_the_same_conversation() {
+        let repository = InMemoryConversationRepository::new();
+        repository.save_conversation(Conversation::new("conv_a".to_string(), "node1".to_string())).await.unwrap();
+
+        let mut graph = FlowGraph::new();
+        graph.add_node(
+            Node::builder("node1".to_string(), "conversational".to_string(), "Node 1".to_string(), "Node 1 description".to_string())
+                .with_action(SlowVisitCounterAction)
+                .build(),
+        ).unwrap();
+        graph.add_edge(Edge::new("loop".to_string(), "node1".to_string(), "node1".to_string())).unwrap();
+
+        let flow_manager = FlowManager::new(Arc::new(repository.clone()), Arc::new(graph));
+
+        let (first, second, third) = tokio::join!(
+            flow_manager.trigger_conversation("conv_a".to_string(), user_message("one")),
+            flow_manager.trigger_conversation("conv_a".to_string(), user_message("two")),
+            flow_manager.trigger_conversation("conv_a".to_string(), user_message("three")),
+        );
+        first.unwrap();
+        second.unwrap();
+        third.unwrap();
+
+        let conversation = repository.get_conversation("conv_a".to_string()).await.unwrap();
+        assert_eq!(conversation.get_context().variables.get("visits"), Some(&Value::Number(3.0)));
+        assert_eq!(conversation.get_messages().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_processes_different_conversations_in_parallel() {
+        let repository = InMemoryConversationRepository::new();
+        repository.save_conversation(Conversation::new("conv_a".to_string(), "node1".to_string())).await.unwrap();
+        repository.save_conversation(Conversation::new("conv_b".to_string(), "node1".to_string())).await.unwrap();
+
+        let mut graph = FlowGraph::new();
+        graph.add_node(
+            Node::builder("node1".to_string(), "conversational".to_string(), "Node 1".to_string(), "Node 1 description".to_string())
+                .with_action(BarrierAction(Arc::new(Barrier::new(2))))
+                .build(),
+        ).unwrap();
+        graph.add_edge(Edge::new("loop".to_string(), "node1".to_string(), "node1".to_string())).unwrap();
+
+        let flow_manager = FlowManager::new(Arc::new(repository), Arc::new(graph));
+
+        // Both triggers only finish if they are inside the node at the same time
+        let both = tokio::time::timeout(Duration::from_secs(1), async {
+            tokio::join!(
+                flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi")),
+                flow_manager.trigger_conversation("conv_b".to_string(), user_message("hi")),
+            )
+        })
+        .await
+        .expect("conversations were processed one at a time");
+
+        both.0.unwrap();
+        both.1.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_persists_conversation_when_no_next_node_is_found() {
+        let repository = InMemoryConversationRepository::new();
+        repository.save_conversation(Conversation::new("conv_a".to_string(), "node1".to_string())).await.unwrap();
+
+        let mut graph = FlowGraph::new();
+        graph.add_node(
+            Node::builder("node1".to_string(), "conversational".to_string(), "Node 1".to_string(), "Node 1 description".to_string())
+                .with_action(VisitCounterAction)
+                .build(),
+        ).unwrap();
+
+        let flow_manager = FlowManager::new(Arc::new(repository.clone()), Arc::new(graph));
+
+        let result = flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi")).await;
+
+        assert!(matches!(result, Err(FlowManagerError::NextNodeNotFound(_))));
+        let conversation = repository.get_conversation("conv_a".to_string()).await.unwrap();
+        assert_eq!(conversation.get_current_node_id(), "node1");
+        assert_eq!(conversation.get_messages().len(), 1);
+        assert_eq!(conversation.get_context().variables.get("visits"), Some(&Value::Number(1.0)));
+    }
+
+    #[tokio::test]
+    async fn test_routes_unmatched_input_to_the_fallback_node() {
+        let repository = InMemoryConversationRepository::new();
+        repository.save_conversation(Conversation::new("conv_a".to_string(), "node1".to_string())).await.unwrap();
+
+        let mut graph = FlowGraph::new();
+        for node_id in ["node1", "fallback"] {
+            graph.add_node(
+                Node::new(node_id.to_string(), "conversational".to_string(), node_id.to_string(), "description".to_string()),
+            ).unwrap();
+        }
+        graph.set_fallback_node("fallback".to_string()).unwrap();
+
+        let flow_manager = FlowManager::new(Arc::new(repository.clone()), Arc::new(graph));
+
+        flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi")).await.unwrap();
+
+        let conversation = repository.get_conversation("conv_a".to_string()).await.unwrap();
+        assert_eq!(conversation.get_current_node_id(), "fallback");
+    }
+
+    fn create_graph_with_terminal_node() -> FlowGraph {
+        let mut graph = FlowGraph::new();
+        graph.add_node(
+            Node::builder("welcome".to_string(), "conversational".to_string(), "Welcome".to_string(), "Welcome description".to_string())
+                .with_action(VisitCounterAction)
+                .build(),
+        ).unwrap();
+        graph.add_node(
+            Node::builder("goodbye".to_string(), "conversational".to_string(), "Goodbye".to_string(), "Goodbye description".to_string())
+                .with_action(VisitCounterAction)
+                .with_terminal(true)
+                .build(),
+        ).unwrap();
+        graph.add_edge(Edge::new("welcome_to_goodbye".to_string(), "welcome".to_string(), "goodbye".to_string())).unwrap();
+        graph.set_start_node("welcome".to_string()).unwrap();
+        graph
+    }
+
+    #[tokio::test]
+    async fn test_creates_conversations_on_the_start_node() {
+        let repository = InMemoryConversationRepository::new();
+        let flow_manager = FlowManager::new(Arc::new(repository.clone()), Arc::new(create_graph_with_terminal_node()));
+
+        let conversation = flow_manager.create_conversation("conv_a".to_string()).await.unwrap();
+
+        assert_eq!(conversation.get_current_node_id(), "welcome");
+        assert_eq!(repository.get_conversation("conv_a".to_string()).await.unwrap(), conversation);
+    }
+
+    #[tokio::test]
+    async fn test_create_conversation_fails_without_start_node() {
+        let flow_manager = FlowManager::new(Arc::new(InMemoryConversationRepository::new()), Arc::new(create_looping_graph()));
+
+        let result = flow_manager.create_conversation("conv_a".to_string()).await;
+
+        assert!(matches!(result, Err(FlowManagerError::NoStartNode)));
+    }
+
+    #[tokio::test]
+    async fn test_completes_conversation_after_terminal_node() {
+        let repository = InMemoryConversationRepository::new();
+        let flow_manager = FlowManager::new(Arc::new(repository.clone()), Arc::new(create_graph_with_terminal_node()));
+        flow_manager.create_conversation("conv_a".to_string()).await.unwrap();
+
+        flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi")).await.unwrap();
+        let conversation = repository.get_conversation("conv_a".to_string()).await.unwrap();
+        assert_eq!(conversation.get_status(), ConversationStatus::Active);
+
+        flow_manager.trigger_conversation("conv_a".to_string(), user_message("bye")).await.unwrap();
+        let conversation = repository.get_conversation("conv_a".to_string()).await.unwrap();
+        assert_eq!(conversation.get_status(), ConversationStatus::Completed);
+        assert_eq!(conversation.get_current_node_id(), "goodbye");
+
+        let result = flow_manager.trigger_conversation("conv_a".to_string(), user_message("hello?")).await;
+        assert!(matches!(result, Err(FlowManagerError::ConversationCompleted(_))));
+        let conversation = repository.get_conversation("conv_a".to_string()).await.unwrap();
+        assert_eq!(conversation.get_messages().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_restarts_completed_conversation() {
+        let repository = InMemoryConversationRepository::new();
+        let flow_manager = FlowManager::new(Arc::new(repository.clone()), Arc::new(create_graph_with_terminal_node()));
+        flow_manager.create_conversation("conv_a".to_string()).await.unwrap();
+        flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi")).await.unwrap();
+        flow_manager.trigger_conversation("conv_a".to_string(), user_message("bye")).await.unwrap();
+
+        let conversation = flow_manager.restart_conversation("conv_a".to_string()).await.unwrap();
+
+        assert_eq!(conversation.get_status(), ConversationStatus::Active);
+        assert_eq!(conversation.get_current_node_id(), "welcome");
+        assert!(conversation.get_context().variables.is_empty());
+        assert_eq!(conversation.get_messages().len(), 2);
+
+        let context = flow_manager.trigger_conversation("conv_a".to_string(), user_message("hi again")).await.unwrap();
+        assert_eq!(context.variables.get("visits"), Some(&Value::Number(1.0)));
     }
 }
@@ -1,2 +1,7 @@
 pub mod conversation;
-pub mod flow_manager;
\ No newline at end of file
+pub mod conversation_locks;
+pub mod flow_manager;
+
+pub mod tests {
+    pub mod conversation_repository_implementation;
+}
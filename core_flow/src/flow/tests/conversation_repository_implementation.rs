@@ -0,0 +1,56 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use async_trait::async_trait;
+
+use crate::flow::conversation::{Conversation, ConversationRepository};
+
+// Clones share the same storage, so tests can inspect what the flow manager persisted
+#[derive(Clone, Default)]
+pub struct InMemoryConversationRepository {
+    conversations: Arc<Mutex<HashMap<String, Conversation>>>,
+}
+
+impl InMemoryConversationRepository {
+    pub fn new() -> Self {
+        InMemoryConversationRepository::default()
+    }
+
+    fn find(&self, predicate: impl Fn(&Conversation) -> bool) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
+        self.conversations
+            .lock()
+            .unwrap()
+            .values()
+            .find(|conversation| predicate(conversation))
+            .cloned()
+            .ok_or_else(|| "Conversation not found".into())
+    }
+}
+
+#[async_trait]
+impl ConversationRepository for InMemoryConversationRepository {
+    async fn get_conversation(&self, conversation_id: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
+        self.find(|conversation| conversation.id == conversation_id)
+    }
+
+    async fn get_conversation_by_recipient(&self, recipient: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
+        self.find(|conversation| conversation.get_messages().iter().any(|msg| msg.recipient == recipient))
+    }
+
+    async fn get_conversation_by_sender(&self, sender: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
+        self.find(|conversation| conversation.get_messages().iter().any(|msg| msg.sender == sender))
+    }
+
+    async fn get_last_conversation_by_recipient(&self, recipient: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
+        self.get_conversation_by_recipient(recipient).await
+    }
+
+    async fn save_conversation(&self, conversation: Conversation) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conversations.lock().unwrap().insert(conversation.id.clone(), conversation);
+        Ok(())
+    }
+
+    async fn update_conversation(&self, conversation_id: String, conversation: Conversation) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conversations.lock().unwrap().insert(conversation_id, conversation);
+        Ok(())
+    }
+}
@@ -25,7 +25,7 @@ impl Clone for Box<dyn Action> {
 
 #[cfg(test)]
 mod tests {
-    use crate::graph::{action::{action_registry::ActionRegistry, tests::action_implementation::create_test_action, utils::action_deserializer::deserialize_actions}, node::node_context::Value};
+    use crate::graph::{action::{action_policy::{ActionPolicy, OnError}, action_registry::ActionRegistry, parallel_group::{OnConflict, ParallelGroup}, tests::action_implementation::create_test_action, utils::action_deserializer::deserialize_actions}, condition::condition_registry::ConditionRegistry, node::node_context::Value};
 
     use super::*;
 
@@ -40,7 +40,7 @@ mod tests {
                     "test_config": "test_value"
                 },
                 "input_vars": {},
-                "output_vars": {}
+                "output_vars": []
             }
         ]"#;
 
@@ -50,7 +50,7 @@ mod tests {
             create_test_action
         );
 
-        let actions = deserialize_actions(json, &action_registry).unwrap();
+        let actions = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap();
 
         let action = actions.get(0).unwrap();
 
@@ -64,4 +64,160 @@ mod tests {
         );
         assert_eq!(actions.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_deserialize_actions_with_policy() {
+        let json = r#"[
+            {
+                "action_type": "test_action",
+                "config": { "name": "lookup", "id": "lookup" },
+                "input_vars": {},
+                "output_vars": [],
+                "policy": { "max_retries": 3, "backoff_ms": 50, "timeout_ms": 1000, "on_error": "continue" }
+            }
+        ]"#;
+
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("test_action", create_test_action);
+
+        let actions = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap();
+
+        assert_eq!(
+            actions[0].policy,
+            ActionPolicy::default()
+                .with_max_retries(3)
+                .with_backoff_ms(50)
+                .with_timeout_ms(1000)
+                .with_on_error(OnError::Continue)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_actions_with_invalid_policy() {
+        let json = r#"[
+            {
+                "action_type": "test_action",
+                "config": { "name": "lookup", "id": "lookup" },
+                "input_vars": {},
+                "output_vars": [],
+                "policy": { "on_error": "explode" }
+            }
+        ]"#;
+
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("test_action", create_test_action);
+
+        let error = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap_err();
+
+        assert_eq!(error.path(), "[0].policy");
+    }
+
+    #[test]
+    fn test_deserialize_parallel_actions() {
+        let json = r#"[
+            {
+                "parallel": [
+                    { "action_type": "test_action", "config": { "name": "crm", "id": "crm" }, "input_vars": {}, "output_vars": [] },
+                    { "action_type": "test_action", "config": { "name": "kb", "id": "kb" }, "input_vars": {}, "output_vars": [] }
+                ],
+                "on_conflict": "last_wins"
+            },
+            { "action_type": "test_action", "config": { "name": "reply", "id": "reply" }, "input_vars": {}, "output_vars": [] }
+        ]"#;
+
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("test_action", create_test_action);
+
+        let actions = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap();
+
+        let group = Some(ParallelGroup::new(0, OnConflict::LastWins));
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions[0].parallel_group, group);
+        assert_eq!(actions[1].parallel_group, group);
+        assert_eq!(actions[2].parallel_group, None);
+    }
+
+    #[test]
+    fn test_deserialize_invalid_parallel_actions() {
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("test_action", create_test_action);
+
+        let json = r#"[
+            { "action_type": "test_action", "config": { "name": "reply", "id": "reply" }, "input_vars": {}, "output_vars": [] },
+            { "parallel": [ { "action_type": "test_action", "config": { "name": "crm" }, "input_vars": {}, "output_vars": [] } ] }
+        ]"#;
+        let error = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap_err();
+        assert_eq!(error.path(), "[1].parallel[0].config.id");
+
+        let json = r#"[ { "parallel": [ { "parallel": [] } ] } ]"#;
+        let error = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap_err();
+        assert_eq!(error.path(), "[0].parallel[0].parallel");
+
+        let json = r#"[ { "parallel": [], "on_conflict": "merge" } ]"#;
+        let error = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap_err();
+        assert_eq!(error.path(), "[0].on_conflict");
+    }
+
+    #[test]
+    fn test_deserialize_actions_with_when() {
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("test_action", create_test_action);
+        let mut condition_registry = ConditionRegistry::new();
+        condition_registry.register_builtin_conditions();
+
+        let json = r#"[
+            {
+                "action_type": "test_action",
+                "config": { "name": "send_message", "id": "send_message" },
+                "input_vars": {},
+                "output_vars": [],
+                "when": [ { "condition_type": "expression", "config": { "expression": "ai_action.reply != \"\"" } } ]
+            }
+        ]"#;
+        let actions = deserialize_actions(json, &action_registry, &condition_registry).unwrap();
+        assert_eq!(actions[0].when.len(), 1);
+
+        let json = r#"[
+            {
+                "action_type": "test_action",
+                "config": { "name": "send_message", "id": "send_message" },
+                "input_vars": {},
+                "output_vars": [],
+                "when": [ { "condition_type": "expression", "config": { "expression": "ai_action.reply ==" } } ]
+            }
+        ]"#;
+        let error = deserialize_actions(json, &action_registry, &condition_registry).unwrap_err();
+        assert_eq!(error.path(), "[0].when[0].config.expression");
+
+        let json = r#"[
+            {
+                "action_type": "test_action",
+                "config": { "name": "send_message", "id": "send_message" },
+                "input_vars": {},
+                "output_vars": [],
+                "when": []
+            }
+        ]"#;
+        let error = deserialize_actions(json, &action_registry, &condition_registry).unwrap_err();
+        assert_eq!(error.path(), "[0].when");
+    }
+
+    #[test]
+    fn test_deserialize_actions_with_invalid_input_var_mapping() {
+        let json = r#"[
+            {
+                "action_type": "test_action",
+                "config": { "name": "reply", "id": "reply" },
+                "input_vars": { "customer": { "path": "crm.customer", "literal": "Ada" } },
+                "output_vars": []
+            }
+        ]"#;
+
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("test_action", create_test_action);
+
+        let error = deserialize_actions(json, &action_registry, &ConditionRegistry::new()).unwrap_err();
+
+        assert_eq!(error.path(), "[0].input_vars.customer");
+    }
+}
@@ -0,0 +1,283 @@
+use std::time::Duration;
+
+use serde_json::Value as JsonValue;
+
+use crate::graph::{
+    action::{
+        action::Action,
+        action_policy::{ActionPolicy, OnError},
+        parallel_group::ParallelGroup,
+        utils::vars_parser::{parse_output_vars, InputVarMapping},
+    },
+    condition::condition::Condition,
+    node::node_context::NodeContext,
+};
+
+/// An action as declared inside a node, together with the variables it reads and writes
+#[derive(Debug, Clone)]
+pub struct ActionDefinition {
+    pub id: String,
+    pub name: String,
+    pub action_type: String,
+    pub input_vars: JsonValue,
+    pub output_vars: JsonValue,
+    pub policy: ActionPolicy,
+    // Set when the action runs concurrently with its neighbours of the same group
+    pub parallel_group: Option<ParallelGroup>,
+    // The action only runs when every condition holds, it always runs when there are none
+    pub when: Vec<Box<dyn Condition<NodeContext>>>,
+    action: Box<dyn Action>,
+}
+
+impl ActionDefinition {
+    pub fn new(
+        id: String,
+        name: String,
+        action_type: String,
+        input_vars: JsonValue,
+        output_vars: JsonValue,
+        action: Box<dyn Action>,
+    ) -> Self {
+        ActionDefinition {
+            id,
+            name,
+            action_type,
+            input_vars,
+            output_vars,
+            policy: ActionPolicy::default(),
+            parallel_group: None,
+            when: Vec::new(),
+            action,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: ActionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_parallel_group(mut self, parallel_group: ParallelGroup) -> Self {
+        self.parallel_group = Some(parallel_group);
+        self
+    }
+
+    pub fn with_when(mut self, when: Vec<Box<dyn Condition<NodeContext>>>) -> Self {
+        self.when = when;
+        self
+    }
+
+    /// Whether the `when` conditions of the action hold for the context
+    pub async fn should_run(&self, context: &NodeContext) -> bool {
+        for condition in self.when.iter() {
+            if !condition.evaluate(context).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Wraps an action that was built in code and declares no variables
+    pub fn from_action(id: String, action: Box<dyn Action>) -> Self {
+        ActionDefinition::new(
+            id.clone(),
+            id,
+            "custom".to_string(),
+            JsonValue::Object(Default::default()),
+            JsonValue::Array(Vec::new()),
+            action,
+        )
+    }
+
+    /// Runs the action following its policy. Every attempt starts from the given context,
+    /// so a failed attempt leaves nothing behind.
+    pub async fn execute(
+        &self,
+        context: &mut NodeContext,
+    ) -> Result<NodeContext, Box<dyn std::error::Error>> {
+        let mut retry = 0;
+        loop {
+            // The error isn't Send, so it must be dropped before waiting for the retry
+            match self.execute_attempt(context.clone()).await {
+                Ok(new_context) => return Ok(new_context),
+                Err(error) if retry >= self.policy.max_retries => return Err(error),
+                Err(_) => {}
+            }
+            tokio::time::sleep(self.policy.backoff(retry)).await;
+            retry += 1;
+        }
+    }
+
+    async fn execute_attempt(
+        &self,
+        mut context: NodeContext,
+    ) -> Result<NodeContext, Box<dyn std::error::Error>> {
+        let Some(timeout_ms) = self.policy.timeout_ms else {
+            return self.action.execute(&mut context).await;
+        };
+
+        match tokio::time::timeout(Duration::from_millis(timeout_ms), self.action.execute(&mut context)).await {
+            Ok(result) => result,
+            Err(_) => Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Action {} timed out after {} ms", self.id, timeout_ms),
+            ))),
+        }
+    }
+
+    /// Context variable the failure reason is written to when the policy doesn't fail the node
+    pub fn error_variable(&self) -> String {
+        format!("{}.error", self.id)
+    }
+
+    /// Context paths the action needs, as referenced by its required input vars
+    pub fn referenced_input_vars(&self) -> Vec<String> {
+        match self.input_vars.as_object() {
+            Some(input_vars) => input_vars
+                .iter()
+                .filter_map(|(name, mapping)| InputVarMapping::from_json(name, mapping).ok())
+                .filter_map(|mapping| mapping.required_path().map(str::to_string))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Context variables the action writes, namespaced by its id the same way `OutputVarsBuilder` does
+    pub fn declared_output_vars(&self) -> Vec<String> {
+        let mut output_vars: Vec<String> = parse_output_vars(&self.output_vars)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|output_var| format!("{}.{}", self.id, output_var.name))
+            .collect();
+        if self.policy.on_error != OnError::Fail {
+            output_vars.push(self.error_variable());
+        }
+        output_vars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use crate::graph::{action::tests::action_implementation::TestAction, node::node_context::Value};
+
+    use super::*;
+
+    #[test]
+    fn test_declared_vars() {
+        let definition = ActionDefinition::new(
+            "send".to_string(),
+            "send_message".to_string(),
+            "send_message".to_string(),
+            json!({"messages": "ai_action.messages", "ignored": 1}),
+            json!(["status", "sent_at?"]),
+            Box::new(TestAction::new(&JsonValue::Null)),
+        );
+
+        assert_eq!(definition.referenced_input_vars(), vec!["ai_action.messages".to_string()]);
+        assert_eq!(
+            definition.declared_output_vars(),
+            vec!["send.status".to_string(), "send.sent_at".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_action_built_in_code_declares_nothing() {
+        let definition = ActionDefinition::from_action(
+            "action_0".to_string(),
+            Box::new(TestAction::new(&JsonValue::Null)),
+        );
+
+        assert!(definition.referenced_input_vars().is_empty());
+        assert!(definition.declared_output_vars().is_empty());
+    }
+
+    // Fails until it ran `failures` times
+    #[derive(Clone)]
+    struct FlakyAction {
+        attempts: Arc<AtomicU32>,
+        failures: u32,
+    }
+
+    #[async_trait]
+    impl Action for FlakyAction {
+        async fn execute(&self, context: &mut NodeContext) -> Result<NodeContext, Box<dyn std::error::Error>> {
+            context.variables.insert("partial".to_string(), Value::Boolean(true));
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err("flaky".into());
+            }
+            Ok(context.clone())
+        }
+
+        fn clone_box(&self) -> Box<dyn Action> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[derive(Clone)]
+    struct SlowAction;
+
+    #[async_trait]
+    impl Action for SlowAction {
+        async fn execute(&self, context: &mut NodeContext) -> Result<NodeContext, Box<dyn std::error::Error>> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(context.clone())
+        }
+
+        fn clone_box(&self) -> Box<dyn Action> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn flaky_definition(failures: u32, policy: ActionPolicy) -> (ActionDefinition, Arc<AtomicU32>) {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let action = FlakyAction { attempts: attempts.clone(), failures };
+        (ActionDefinition::from_action("flaky".to_string(), Box::new(action)).with_policy(policy), attempts)
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let (definition, attempts) = flaky_definition(2, ActionPolicy::default().with_max_retries(2).with_backoff_ms(1));
+
+        let result = definition.execute(&mut NodeContext::new()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempts_leave_the_context_untouched() {
+        let (definition, attempts) = flaky_definition(5, ActionPolicy::default().with_max_retries(1));
+        let mut context = NodeContext::new();
+
+        let result = definition.execute(&mut context).await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert!(context.variables.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_times_out() {
+        let definition = ActionDefinition::from_action("slow".to_string(), Box::new(SlowAction))
+            .with_policy(ActionPolicy::default().with_timeout_ms(10));
+
+        let error = definition.execute(&mut NodeContext::new()).await.unwrap_err();
+
+        assert_eq!(error.to_string(), "Action slow timed out after 10 ms");
+    }
+
+    #[test]
+    fn test_declares_its_error_variable_unless_it_fails_the_node() {
+        let definition = ActionDefinition::from_action("lookup".to_string(), Box::new(TestAction::new(&JsonValue::Null)))
+            .with_policy(ActionPolicy::default().with_on_error(OnError::Continue));
+
+        assert_eq!(definition.declared_output_vars(), vec!["lookup.error".to_string()]);
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// What a node does once an action failed all its attempts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnError {
+    // The node fails and the conversation stays where it was
+    #[default]
+    Fail,
+    // The error is recorded and the next action runs
+    Continue,
+    // The error is recorded, the remaining actions are skipped and the node leaves
+    // through its on_error edges
+    Route,
+}
+
+// Json Structure, every field is optional
+// {
+//     "max_retries": 2,
+//     "backoff_ms": 200,
+//     "timeout_ms": 5000,
+//     "on_error": "fail" | "continue" | "route"
+// }
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActionPolicy {
+    pub max_retries: u32,
+    // Wait before the first retry, doubled on every further retry
+    pub backoff_ms: u64,
+    // Limit of a single attempt, no limit when absent
+    pub timeout_ms: Option<u64>,
+    pub on_error: OnError,
+}
+
+impl ActionPolicy {
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_backoff_ms(mut self, backoff_ms: u64) -> Self {
+        self.backoff_ms = backoff_ms;
+        self
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    pub fn with_on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Wait before the given retry, counting from 0
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64.checked_shl(retry).unwrap_or(u64::MAX);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_policy() {
+        let policy: ActionPolicy = serde_json::from_value(json!({"max_retries": 2, "on_error": "route"})).unwrap();
+
+        assert_eq!(policy, ActionPolicy::default().with_max_retries(2).with_on_error(OnError::Route));
+        assert!(serde_json::from_value::<ActionPolicy>(json!({"retries": 2})).is_err());
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = ActionPolicy::default().with_backoff_ms(100);
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(80), Duration::from_millis(u64::MAX));
+    }
+}
@@ -1,10 +1,104 @@
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 
-use crate::graph::action::action::Action;
+use crate::graph::{
+    action::{
+        action::Action,
+        templated_message::{TemplatedMessage, TEMPLATED_MESSAGE_ACTION_TYPE},
+    },
+    catalog::{CatalogEntry, ComponentMetadata},
+    config_error::ConfigError,
+    config_schema::ComponentSchema,
+};
+
+/// Builds actions of one type from their json config, input vars and output vars
+pub trait ActionFactory: Send + Sync {
+    fn create(
+        &self,
+        config: &JsonValue,
+        input_vars: &JsonValue,
+        output_vars: &JsonValue,
+    ) -> Result<Box<dyn Action>, ConfigError>;
+}
+
+// Plain constructors and closures capturing shared resources (http clients, db handles) are factories
+impl<F> ActionFactory for F
+where
+    F: Fn(&JsonValue, &JsonValue, &JsonValue) -> Result<Box<dyn Action>, ConfigError> + Send + Sync,
+{
+    fn create(
+        &self,
+        config: &JsonValue,
+        input_vars: &JsonValue,
+        output_vars: &JsonValue,
+    ) -> Result<Box<dyn Action>, ConfigError> {
+        self(config, input_vars, output_vars)
+    }
+}
+
+/// An action type as registered: the factory building it and the contract of its json
+pub struct ActionRegistration {
+    factory: Box<dyn ActionFactory>,
+    schema: ComponentSchema,
+    metadata: ComponentMetadata,
+}
+
+impl ActionRegistration {
+    pub fn new<F>(action_constructor: F) -> Self
+    where
+        F: Fn(&JsonValue, &JsonValue, &JsonValue) -> Result<Box<dyn Action>, ConfigError> + Send + Sync + 'static,
+    {
+        Self::from_factory(action_constructor)
+    }
+
+    pub fn from_factory(action_factory: impl ActionFactory + 'static) -> Self {
+        ActionRegistration {
+            factory: Box::new(action_factory),
+            schema: ComponentSchema::new(),
+            metadata: ComponentMetadata::default(),
+        }
+    }
+
+    pub fn with_schema(mut self, schema: ComponentSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.metadata.name = name.to_string();
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.metadata.description = description.to_string();
+        self
+    }
+
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.metadata.category = category.to_string();
+        self
+    }
+
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.metadata.version = version.to_string();
+        self
+    }
+
+    pub fn get_factory(&self) -> &dyn ActionFactory {
+        self.factory.as_ref()
+    }
+
+    pub fn get_schema(&self) -> &ComponentSchema {
+        &self.schema
+    }
+
+    pub fn get_metadata(&self) -> &ComponentMetadata {
+        &self.metadata
+    }
+}
 
 pub struct ActionRegistry {
-    actions: HashMap<String, fn(&JsonValue, &JsonValue, &JsonValue) -> Box<dyn Action>>,
+    actions: HashMap<String, ActionRegistration>,
 }
 
 impl ActionRegistry {
@@ -14,24 +108,62 @@ impl ActionRegistry {
         }
     }
 
-    pub fn register_action(
-        &mut self,
-        action_type: &str,
-        action_constructor: fn(&JsonValue, &JsonValue, &JsonValue) -> Box<dyn Action>,
-    ) -> &mut Self {
-        self.actions
-            .insert(action_type.to_string(), action_constructor);
+    pub fn register(&mut self, action_type: &str, registration: ActionRegistration) -> &mut Self {
+        self.actions.insert(action_type.to_string(), registration);
         self
     }
 
-    pub fn get_actions(&self) -> &HashMap<String, fn(&JsonValue, &JsonValue, &JsonValue) -> Box<dyn Action>> {
+    pub fn register_action<F>(&mut self, action_type: &str, action_constructor: F) -> &mut Self
+    where
+        F: Fn(&JsonValue, &JsonValue, &JsonValue) -> Result<Box<dyn Action>, ConfigError> + Send + Sync + 'static,
+    {
+        self.register(action_type, ActionRegistration::new(action_constructor))
+    }
+
+    pub fn register_action_factory(&mut self, action_type: &str, action_factory: impl ActionFactory + 'static) -> &mut Self {
+        self.register(action_type, ActionRegistration::from_factory(action_factory))
+    }
+
+    /// Registers the actions shipped with core_flow
+    pub fn register_builtin_actions(&mut self) -> &mut Self {
+        self.register(
+            TEMPLATED_MESSAGE_ACTION_TYPE,
+            ActionRegistration::new(TemplatedMessage::create_templated_message)
+                .with_schema(TemplatedMessage::schema())
+                .with_name("Templated message")
+                .with_description("Writes a message rendered from a template with context variables")
+                .with_category("messaging"),
+        )
+    }
+
+    pub fn get_actions(&self) -> &HashMap<String, ActionRegistration> {
         &self.actions
     }
+
+    /// Every registered action type, sorted by type
+    pub fn catalog(&self) -> Vec<CatalogEntry> {
+        let mut entries: Vec<CatalogEntry> = self
+            .actions
+            .iter()
+            .map(|(action_type, registration)| {
+                CatalogEntry::new(action_type, registration.get_metadata(), registration.get_schema())
+            })
+            .collect();
+        entries.sort_by(|a, b| a.component_type.cmp(&b.component_type));
+        entries
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::graph::{action::tests::action_implementation::create_test_action};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use serde_json::json;
+
+    use crate::graph::{action::tests::action_implementation::{create_test_action, TestAction}};
 
     use super::*;
 
@@ -48,4 +180,68 @@ mod tests {
 
         assert_eq!(actions.len(), 1);
     }
+
+    #[test]
+    fn test_register_closure_capturing_shared_state() {
+        let created_actions = Arc::new(AtomicUsize::new(0));
+        let counter = created_actions.clone();
+
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("counted_action", move |config, _, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(TestAction::new(config)) as Box<dyn Action>)
+        });
+
+        let factory = action_registry.get_actions()["counted_action"].get_factory();
+        factory.create(&json!({}), &json!({}), &json!([])).unwrap();
+        factory.create(&json!({}), &json!({}), &json!([])).unwrap();
+
+        assert_eq!(created_actions.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_constructor_errors_are_returned() {
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_action("failing_action", |_, _, _| Err(ConfigError::MissingField("model".to_string())));
+
+        let result = action_registry.get_actions()["failing_action"].get_factory().create(&json!({}), &json!({}), &json!([]));
+
+        assert!(matches!(result, Err(ConfigError::MissingField(field)) if field == "model"));
+    }
+
+    #[test]
+    fn test_catalog() {
+        let mut action_registry = ActionRegistry::new();
+        action_registry
+            .register(
+                "test_action",
+                ActionRegistration::new(create_test_action)
+                    .with_name("Test action")
+                    .with_description("Writes test_var")
+                    .with_category("testing")
+                    .with_version("0.2.0"),
+            )
+            .register_action("another_action", create_test_action);
+
+        let catalog = action_registry.catalog();
+
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(catalog[0].component_type, "another_action");
+        assert_eq!(catalog[0].name, "another_action");
+        assert_eq!(catalog[1].name, "Test action");
+        assert_eq!(catalog[1].description, "Writes test_var");
+        assert_eq!(catalog[1].category, "testing");
+        assert_eq!(catalog[1].version, "0.2.0");
+    }
+
+    #[test]
+    fn test_register_builtin_actions() {
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_builtin_actions();
+
+        let catalog = action_registry.catalog();
+
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog[0].component_type, TEMPLATED_MESSAGE_ACTION_TYPE);
+    }
 }
@@ -1,8 +1,12 @@
 pub mod action;
+pub mod action_definition;
+pub mod action_policy;
 pub mod action_registry;
+pub mod parallel_group;
+pub mod templated_message;
 
 pub mod tests {
     pub mod action_implementation;
 }
 
-pub mod utils;
\ No newline at end of file
+pub mod utils;
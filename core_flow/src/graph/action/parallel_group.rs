@@ -0,0 +1,233 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    flow::conversation::Message,
+    graph::node::node_context::{NodeContext, Value, VariableScope},
+};
+
+// Transient conversation messages, actions only ever append to them
+const MESSAGES_VARIABLE: &str = "messages";
+
+/// What happens when two actions of a parallel group write the same variable
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnConflict {
+    // The node fails with a ParallelConflictError
+    #[default]
+    Fail,
+    // The action declared first in the group keeps its value
+    FirstWins,
+    // The action declared last in the group keeps its value
+    LastWins,
+}
+
+/// Marks an action as a member of a group of actions running concurrently.
+/// Consecutive actions of a node sharing the same group run together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParallelGroup {
+    // Position in the node of the first action of the group
+    pub index: usize,
+    pub on_conflict: OnConflict,
+}
+
+/// Variables an action of a group wrote in every scope, compared to the context the group started from
+#[derive(Debug, Clone)]
+pub struct ActionOutput {
+    pub action_id: String,
+    pub variables: HashMap<(VariableScope, String), Value>,
+    // Messages the action appended to the transient messages, they never conflict
+    pub messages: Vec<Message>,
+}
+
+impl ActionOutput {
+    pub fn new(action_id: String, base: &NodeContext, mut result: NodeContext) -> Self {
+        let appended = match (base.transient.get(MESSAGES_VARIABLE), result.transient.get(MESSAGES_VARIABLE)) {
+            (Some(Value::Messages(history)), Some(Value::Messages(messages))) if messages.starts_with(history) => {
+                Some(messages[history.len()..].to_vec())
+            }
+            (None, Some(Value::Messages(messages))) => Some(messages.clone()),
+            _ => None,
+        };
+        // Appending leaves the history untouched, any other change of the messages is a regular write
+        let messages = match appended {
+            Some(messages) => {
+                result.transient.remove(MESSAGES_VARIABLE);
+                messages
+            }
+            None => Vec::new(),
+        };
+
+        let mut variables = HashMap::new();
+        for scope in VariableScope::RESOLUTION_ORDER {
+            for (key, value) in std::mem::take(result.scope_mut(scope)) {
+                if base.scope(scope).get(&key) != Some(&value) {
+                    variables.insert((scope, key), value);
+                }
+            }
+        }
+
+        ActionOutput { action_id, variables, messages }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParallelConflictError {
+    pub variable: String,
+    pub first_action_id: String,
+    pub second_action_id: String,
+}
+
+impl fmt::Display for ParallelConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Parallel actions {} and {} both write {}",
+            self.first_action_id, self.second_action_id, self.variable
+        )
+    }
+}
+
+impl Error for ParallelConflictError {}
+
+impl ParallelGroup {
+    pub fn new(index: usize, on_conflict: OnConflict) -> Self {
+        ParallelGroup { index, on_conflict }
+    }
+
+    /// Applies the outputs, given in declaration order, to the context the group started from.
+    /// The result doesn't depend on which action finished first.
+    /// Variables removed by an action of the group are kept, appended messages are all kept,
+    /// in declaration order.
+    pub fn merge(&self, base: NodeContext, outputs: Vec<ActionOutput>) -> Result<NodeContext, ParallelConflictError> {
+        let mut merged = base;
+        let mut writers: HashMap<(VariableScope, String), String> = HashMap::new();
+        let mut appended_messages = Vec::new();
+
+        for output in outputs {
+            appended_messages.extend(output.messages);
+
+            let mut variables: Vec<((VariableScope, String), Value)> = output.variables.into_iter().collect();
+            variables.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for ((scope, key), value) in variables {
+                if let Some(first_action_id) = writers.get(&(scope, key.clone())) {
+                    match self.on_conflict {
+                        OnConflict::Fail => {
+                            return Err(ParallelConflictError {
+                                variable: key,
+                                first_action_id: first_action_id.clone(),
+                                second_action_id: output.action_id,
+                            });
+                        }
+                        OnConflict::FirstWins => continue,
+                        OnConflict::LastWins => {}
+                    }
+                }
+                writers.insert((scope, key.clone()), output.action_id.clone());
+                merged.scope_mut(scope).insert(key, value);
+            }
+        }
+
+        if !appended_messages.is_empty() {
+            match merged.transient.get_mut(MESSAGES_VARIABLE) {
+                Some(Value::Messages(messages)) => messages.extend(appended_messages),
+                _ => {
+                    merged.transient.insert(MESSAGES_VARIABLE.to_string(), Value::Messages(appended_messages));
+                }
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(action_id: &str, variables: &[(&str, &str)]) -> ActionOutput {
+        ActionOutput {
+            action_id: action_id.to_string(),
+            variables: variables
+                .iter()
+                .map(|(key, value)| ((VariableScope::Conversation, key.to_string()), Value::String(value.to_string())))
+                .collect(),
+            messages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_output_only_keeps_written_variables() {
+        let mut base = NodeContext::new();
+        base.variables.insert("kept".to_string(), Value::Boolean(true));
+        base.variables.insert("changed".to_string(), Value::Boolean(true));
+        let mut result = base.clone();
+        result.variables.insert("changed".to_string(), Value::Boolean(false));
+        result.variables.insert("added".to_string(), Value::Null);
+        result.transient.insert("crm.customer".to_string(), Value::Null);
+
+        let output = ActionOutput::new("crm".to_string(), &base, result);
+
+        assert_eq!(output.variables.len(), 3);
+        assert!(!output.variables.contains_key(&(VariableScope::Conversation, "kept".to_string())));
+        assert!(output.variables.contains_key(&(VariableScope::Transient, "crm.customer".to_string())));
+    }
+
+    #[test]
+    fn test_merge_appends_messages_in_declaration_order() {
+        let message = |text: &str| Message::new("ai".to_string(), text.to_string(), "+34600000000".to_string());
+        let history = vec![message("Hi")];
+        let mut base = NodeContext::new();
+        base.transient.insert("messages".to_string(), Value::Messages(history.clone()));
+
+        let outputs = ["Hello from the AI", "Your order shipped"].map(|text| {
+            let mut result = base.clone();
+            result.transient.insert("messages".to_string(), Value::Messages([history.clone(), vec![message(text)]].concat()));
+            ActionOutput::new(text.to_string(), &base, result)
+        });
+        assert!(outputs.iter().all(|output| output.variables.is_empty() && output.messages.len() == 1));
+
+        let merged = ParallelGroup::new(0, OnConflict::Fail).merge(base, outputs.to_vec()).unwrap();
+
+        let texts: Vec<&str> = merged.transient["messages"]
+            .as_messages()
+            .unwrap()
+            .iter()
+            .filter_map(|message| message.content.text())
+            .collect();
+        assert_eq!(texts, ["Hi", "Hello from the AI", "Your order shipped"]);
+    }
+
+    #[test]
+    fn test_merge_applies_every_output() {
+        let group = ParallelGroup::new(0, OnConflict::Fail);
+
+        let merged = group
+            .merge(NodeContext::new(), vec![output("crm", &[("crm.customer", "ada")]), output("kb", &[("kb.answer", "42")])])
+            .unwrap();
+
+        assert_eq!(merged.variables.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let outputs = vec![output("crm", &[("summary", "crm")]), output("kb", &[("summary", "kb")])];
+
+        let error = ParallelGroup::new(0, OnConflict::Fail)
+            .merge(NodeContext::new(), outputs.clone())
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Parallel actions crm and kb both write summary");
+
+        let merged = ParallelGroup::new(0, OnConflict::FirstWins)
+            .merge(NodeContext::new(), outputs.clone())
+            .unwrap();
+        assert_eq!(merged.variables.get("summary"), Some(&Value::String("crm".to_string())));
+
+        let merged = ParallelGroup::new(0, OnConflict::LastWins)
+            .merge(NodeContext::new(), outputs)
+            .unwrap();
+        assert_eq!(merged.variables.get("summary"), Some(&Value::String("kb".to_string())));
+    }
+}
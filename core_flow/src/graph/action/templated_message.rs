@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+
+use crate::{
+    flow::conversation::{Message, MessageRole, AI_SENDER},
+    graph::{
+        action::{action::Action, utils::vars_parser::OutputVarsBuilder},
+        config_error::ConfigError,
+        config_schema::{ComponentSchema, ConfigSchema, FieldType},
+        node::node_context::{NodeContext, Value},
+        template::engine::{config_template, Template},
+    },
+};
+
+pub const TEMPLATED_MESSAGE_ACTION_TYPE: &str = "templated_message";
+const DEFAULT_SENDER: &str = AI_SENDER;
+
+/// Writes a message rendered from a template, e.g. `Hi {{ crm.customer.name | default:"there" }}`.
+/// Like the AI action, the message is written to its `messages` output var and appended to the
+/// transient `messages` of the run, ready for `send_message`.
+#[derive(Debug, Clone)]
+pub struct TemplatedMessage {
+    template: Template,
+    sender: String,
+    config: JsonValue,
+    output_vars: JsonValue,
+}
+
+impl TemplatedMessage {
+    pub fn schema() -> ComponentSchema {
+        ComponentSchema::new()
+            .with_config(
+                ConfigSchema::new()
+                    .with_field("template", FieldType::String, "Text of the message, with {{ path | filter }} placeholders")
+                    .with_optional_field("sender", FieldType::String, "Sender of the message, ai by default"),
+            )
+            .with_output_var("messages", "The rendered message")
+    }
+
+    // config: { "template": <string>, "sender": <string> }
+    pub fn create_templated_message(
+        config: &JsonValue,
+        _: &JsonValue,
+        output_vars: &JsonValue,
+    ) -> Result<Box<dyn Action>, ConfigError> {
+        let sender = match config.get("sender") {
+            None => DEFAULT_SENDER.to_string(),
+            Some(sender) => sender
+                .as_str()
+                .ok_or_else(|| ConfigError::invalid_field("sender", "expected a string"))?
+                .to_string(),
+        };
+
+        Ok(Box::new(TemplatedMessage {
+            template: config_template(config, "template")?,
+            sender,
+            config: config.clone(),
+            output_vars: output_vars.clone(),
+        }))
+    }
+}
+
+#[async_trait]
+impl Action for TemplatedMessage {
+    async fn execute(&self, context: &mut NodeContext) -> Result<NodeContext, Box<dyn std::error::Error>> {
+        let text = self.template.render(context)?;
+
+        let recipient = match context.transient.get("trigger_message").and_then(Value::as_messages) {
+            Some(trigger_messages) if !trigger_messages.is_empty() => trigger_messages[0].recipient.clone(),
+            _ => {
+                return Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Trigger message not found in context",
+                )));
+            }
+        };
+        // Whoever signs it, the message is written by the flow
+        let message = Message::new(self.sender.clone(), text, recipient).with_role(MessageRole::Assistant);
+
+        let mut messages = match context.transient.remove("messages") {
+            Some(Value::Messages(messages)) => messages,
+            _ => Vec::new(),
+        };
+        messages.push(message.clone());
+
+        let mut output_builder = OutputVarsBuilder::new(&self.config, &self.output_vars, context.clone())?;
+        output_builder.add_var("messages".to_string(), Value::Messages(vec![message]))?;
+
+        let mut output_context = output_builder.build()?;
+        output_context.transient.insert("messages".to_string(), Value::Messages(messages));
+
+        Ok(output_context)
+    }
+
+    fn clone_box(&self) -> Box<dyn Action> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::flow::conversation::MessageType;
+
+    use super::*;
+
+    fn create_context() -> NodeContext {
+        let mut context = NodeContext::new();
+        context.transient.insert(
+            "trigger_message".to_string(),
+            Value::Messages(vec![Message::new("user".to_string(), "hi".to_string(), "+34600000000".to_string())]),
+        );
+        context.variables.insert("crm.customer".to_string(), Value::try_from(json!({"name": "Ada"})).unwrap());
+        context
+    }
+
+    #[tokio::test]
+    async fn test_renders_the_message() {
+        let action = TemplatedMessage::create_templated_message(
+            &json!({"id": "greeting", "name": "greeting", "template": "Hi {{ crm.customer.name }}!"}),
+            &json!({}),
+            &json!(["messages"]),
+        )
+        .unwrap();
+
+        let context = action.execute(&mut create_context()).await.unwrap();
+
+        let Some(Value::Messages(messages)) = context.variables.get("greeting.messages") else {
+            panic!("Expected the rendered message");
+        };
+        assert_eq!(messages[0].content, MessageType::Text("Hi Ada!".to_string()));
+        assert_eq!(messages[0].sender, DEFAULT_SENDER);
+        assert_eq!(messages[0].role, MessageRole::Assistant);
+        assert_eq!(context.transient.get("messages").and_then(Value::as_messages).map(Vec::len), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_fails_on_missing_variables() {
+        let action = TemplatedMessage::create_templated_message(
+            &json!({"id": "greeting", "name": "greeting", "template": "Hi {{ crm.customer.nickname }}!"}),
+            &json!({}),
+            &json!(["messages"]),
+        )
+        .unwrap();
+
+        assert!(action.execute(&mut create_context()).await.is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_templates() {
+        let result = TemplatedMessage::create_templated_message(
+            &json!({"id": "greeting", "name": "greeting", "template": "Hi {{ name"}),
+            &json!({}),
+            &json!(["messages"]),
+        );
+
+        assert!(matches!(result, Err(ConfigError::InvalidField { field, .. }) if field == "template"));
+    }
+}
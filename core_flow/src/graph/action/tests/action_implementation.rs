@@ -1,4 +1,4 @@
-use crate::graph::node::node_context::{NodeContext, Value};
+use crate::graph::{config_error::ConfigError, node::node_context::{NodeContext, Value}};
 use serde_json::Value as JsonValue;
 
 #[derive(Clone)]
@@ -36,6 +36,10 @@ impl crate::graph::action::action::Action for TestAction {
     }
 }
 
-pub fn create_test_action(config: &JsonValue, _: &JsonValue, _: &JsonValue) -> Box<dyn crate::graph::action::action::Action> {
-    Box::new(TestAction::new(config))
+pub fn create_test_action(
+    config: &JsonValue,
+    _: &JsonValue,
+    _: &JsonValue,
+) -> Result<Box<dyn crate::graph::action::action::Action>, ConfigError> {
+    Ok(Box::new(TestAction::new(config)))
 }
@@ -1,18 +1,43 @@
-use std::{collections::HashMap, fmt};
-use serde::de::Error;
+use std::{collections::{HashMap, HashSet}, fmt};
 use serde_json::Value as JsonValue;
-use crate::graph::action::{action::Action, action_registry::ActionRegistry};
+use crate::graph::{
+    action::{
+        action_definition::ActionDefinition,
+        action_policy::ActionPolicy,
+        action_registry::ActionRegistry,
+        parallel_group::{OnConflict, ParallelGroup},
+        utils::vars_parser::{parse_input_var_mappings, parse_output_vars, VarParseError},
+    },
+    condition::{
+        condition::{deserialize_conditions_with_config, Condition},
+        condition_registry::ConditionRegistry,
+    },
+    config_error::ConfigError,
+    flow_load_error::{FlowLoadError, FlowLoadErrorKind},
+    node::node_context::NodeContext,
+};
 
 #[derive(Debug)]
 pub enum DeserializeActionError {
     MissingName,
     MissingId,
-    #[allow(dead_code)]
+    InvalidId(String),
     MissingActionType,
+    UnknownActionType(String),
     MissingConfig,
     MissingInputVars,
+    InvalidInputVars(VarParseError),
     MissingOutputVars,
     IncorrectOutputVarsType(String),
+    InvalidOutputVars(VarParseError),
+    InvalidConfig(ConfigError),
+    InvalidPolicy(serde_json::Error),
+    IncorrectParallelType(String),
+    NestedParallelGroup,
+    InvalidConflictRule(serde_json::Error),
+    // Violations of the schema the action type was registered with
+    SchemaViolation(FlowLoadError),
+    InvalidWhen(FlowLoadError),
     DeserializeError(serde_json::Error),
 }
 
@@ -21,11 +46,22 @@ impl fmt::Display for DeserializeActionError {
         match self {
             DeserializeActionError::MissingName => write!(f, "Action config name is required"),
             DeserializeActionError::MissingId => write!(f, "Action config id is required"),
+            DeserializeActionError::InvalidId(id) => write!(f, "Action config id must be a non-empty string, found {}", id),
             DeserializeActionError::MissingActionType => write!(f, "Action type is required"),
+            DeserializeActionError::UnknownActionType(action_type) => write!(f, "Unknown action type: {}", action_type),
             DeserializeActionError::MissingConfig => write!(f, "Action config is required"),
             DeserializeActionError::MissingInputVars => write!(f, "Input vars is required"),
+            DeserializeActionError::InvalidInputVars(error) => write!(f, "{}", error),
             DeserializeActionError::MissingOutputVars => write!(f, "Output vars is required"),
             DeserializeActionError::IncorrectOutputVarsType(type_name) => write!(f, "Output vars must be an array, found {}", type_name),
+            DeserializeActionError::InvalidOutputVars(error) => write!(f, "{}", error),
+            DeserializeActionError::InvalidConfig(error) => write!(f, "{}", error),
+            DeserializeActionError::InvalidPolicy(error) => write!(f, "Invalid policy: {}", error),
+            DeserializeActionError::IncorrectParallelType(type_name) => write!(f, "Parallel must be an array of actions, found {}", type_name),
+            DeserializeActionError::NestedParallelGroup => write!(f, "Parallel groups can't be nested"),
+            DeserializeActionError::InvalidConflictRule(error) => write!(f, "Invalid conflict rule: {}", error),
+            DeserializeActionError::SchemaViolation(error) => write!(f, "{}", error),
+            DeserializeActionError::InvalidWhen(error) => write!(f, "Invalid when clause: {}", error),
             DeserializeActionError::DeserializeError(error) => write!(f, "Deserialize error: {}", error),
         }
     }
@@ -33,9 +69,41 @@ impl fmt::Display for DeserializeActionError {
 
 impl std::error::Error for DeserializeActionError {}
 
-impl From<DeserializeActionError> for serde_json::Error {
+impl From<DeserializeActionError> for FlowLoadError {
     fn from(error: DeserializeActionError) -> Self {
-        serde_json::Error::custom(error.to_string())
+        match error {
+            DeserializeActionError::InvalidConfig(error) => return FlowLoadError::from(error),
+            DeserializeActionError::SchemaViolation(error) => return error,
+            DeserializeActionError::InvalidWhen(error) => return error.with_path_prefix("when"),
+            _ => {}
+        }
+
+        let (kind, path) = match &error {
+            DeserializeActionError::MissingName => (FlowLoadErrorKind::MissingField, "config.name"),
+            DeserializeActionError::MissingId => (FlowLoadErrorKind::MissingField, "config.id"),
+            DeserializeActionError::InvalidId(_) => (FlowLoadErrorKind::InvalidFieldValue, "config.id"),
+            DeserializeActionError::MissingActionType => (FlowLoadErrorKind::MissingField, "action_type"),
+            DeserializeActionError::UnknownActionType(_) => (FlowLoadErrorKind::UnknownActionType, "action_type"),
+            DeserializeActionError::MissingConfig => (FlowLoadErrorKind::MissingField, "config"),
+            DeserializeActionError::MissingInputVars => (FlowLoadErrorKind::MissingField, "input_vars"),
+            DeserializeActionError::InvalidInputVars(VarParseError::InvalidMapping { name, .. }) => {
+                let path = format!("input_vars.{}", name);
+                return FlowLoadError::new(FlowLoadErrorKind::InvalidFieldValue, error.to_string()).with_path(&path);
+            }
+            DeserializeActionError::InvalidInputVars(_) => (FlowLoadErrorKind::InvalidFieldType, "input_vars"),
+            DeserializeActionError::MissingOutputVars => (FlowLoadErrorKind::MissingField, "output_vars"),
+            DeserializeActionError::IncorrectOutputVarsType(_) => (FlowLoadErrorKind::InvalidFieldType, "output_vars"),
+            DeserializeActionError::InvalidOutputVars(_) => (FlowLoadErrorKind::InvalidFieldValue, "output_vars"),
+            DeserializeActionError::InvalidPolicy(_) => (FlowLoadErrorKind::InvalidFieldValue, "policy"),
+            DeserializeActionError::IncorrectParallelType(_) => (FlowLoadErrorKind::InvalidFieldType, "parallel"),
+            DeserializeActionError::NestedParallelGroup => (FlowLoadErrorKind::InvalidFieldValue, "parallel"),
+            DeserializeActionError::InvalidConflictRule(_) => (FlowLoadErrorKind::InvalidFieldValue, "on_conflict"),
+            DeserializeActionError::DeserializeError(_)
+            | DeserializeActionError::InvalidConfig(_)
+            | DeserializeActionError::SchemaViolation(_)
+            | DeserializeActionError::InvalidWhen(_) => (FlowLoadErrorKind::InvalidJson, ""),
+        };
+        FlowLoadError::new(kind, error.to_string()).with_path(path)
     }
 }
 
@@ -52,8 +120,11 @@ fn deserialize_config(config: Option<JsonValue>) -> Result<JsonValue, Deserializ
                 return Err(DeserializeActionError::MissingName);
             }
 
-            if config.get("id").is_none() {
-                return Err(DeserializeActionError::MissingId);
+            // Output vars are namespaced by the id, see `OutputVarsBuilder`
+            match config.get("id") {
+                None => return Err(DeserializeActionError::MissingId),
+                Some(JsonValue::String(id)) if !id.is_empty() => {}
+                Some(id) => return Err(DeserializeActionError::InvalidId(id.to_string())),
             }
 
             Ok(config)
@@ -65,6 +136,7 @@ fn deserialize_config(config: Option<JsonValue>) -> Result<JsonValue, Deserializ
 pub fn deserialize_input_vars(input_vars: Option<JsonValue>) -> Result<JsonValue, DeserializeActionError> {
     match input_vars {
         Some(input_vars) => {
+            parse_input_var_mappings(&input_vars).map_err(DeserializeActionError::InvalidInputVars)?;
             Ok(input_vars)
         }
         None => Err(DeserializeActionError::MissingInputVars),
@@ -77,35 +149,180 @@ fn deserialize_output_vars(output_vars: Option<JsonValue>) -> Result<JsonValue,
             if !output_vars.is_array() {
                 return Err(DeserializeActionError::IncorrectOutputVarsType(output_vars.to_string()));
             }
+            parse_output_vars(&output_vars).map_err(DeserializeActionError::InvalidOutputVars)?;
             Ok(output_vars)
         }
         None => Err(DeserializeActionError::MissingOutputVars),
     }
 }
 
+fn deserialize_policy(policy: Option<JsonValue>) -> Result<ActionPolicy, DeserializeActionError> {
+    match policy {
+        Some(policy) => serde_json::from_value(policy).map_err(DeserializeActionError::InvalidPolicy),
+        None => Ok(ActionPolicy::default()),
+    }
+}
+
+// Conditions that must all hold for the action to run, same structure as the conditions of an edge
+fn deserialize_when(
+    when: Option<&JsonValue>,
+    condition_registry: &ConditionRegistry,
+) -> Result<Vec<Box<dyn Condition<NodeContext>>>, DeserializeActionError> {
+    let Some(when) = when else {
+        return Ok(Vec::new());
+    };
+
+    let when = deserialize_conditions_with_config(when.to_string().as_str(), condition_registry)
+        .map_err(DeserializeActionError::InvalidWhen)?;
+    if when.is_empty() {
+        return Err(DeserializeActionError::InvalidWhen(FlowLoadError::new(
+            FlowLoadErrorKind::InvalidFieldValue,
+            "When requires at least one condition".to_string(),
+        )));
+    }
+    Ok(when)
+}
+
+fn deserialize_on_conflict(on_conflict: Option<JsonValue>) -> Result<OnConflict, DeserializeActionError> {
+    match on_conflict {
+        Some(on_conflict) => serde_json::from_value(on_conflict).map_err(DeserializeActionError::InvalidConflictRule),
+        None => Ok(OnConflict::default()),
+    }
+}
+
 pub fn deserialize_actions(
     json_data: &str,
     action_registry: &ActionRegistry,
-) -> Result<Vec<Box<dyn Action>>, serde_json::Error> {
+    condition_registry: &ConditionRegistry,
+) -> Result<Vec<ActionDefinition>, FlowLoadError> {
     let actions_data: Vec<HashMap<String, JsonValue>> = serde_json::from_str(json_data)?;
-    let mut actions: Vec<Box<dyn Action>> = Vec::new();
-
-    for action_data in actions_data {
-        if let Some(action_type) = action_data.get("action_type").and_then(|v| v.as_str()) {
-            if let Some(action_constructor) = action_registry.get_actions().get(action_type) {
-                let config = deserialize_config(action_data.get("config").cloned())?;
-                let input_vars = deserialize_input_vars(action_data.get("input_vars").cloned())?;
-                let output_vars = deserialize_output_vars(action_data.get("output_vars").cloned())?;
-
-                actions.push(action_constructor(&config, &input_vars, &output_vars));
-            } else {
-                return Err(serde_json::Error::custom(format!(
-                    "Unknown action type: {}",
-                    action_type
-                )));
+    let mut actions: Vec<ActionDefinition> = Vec::new();
+    let mut action_ids: HashSet<String> = HashSet::new();
+
+    for (index, action_data) in actions_data.into_iter().enumerate() {
+        if let Some(parallel) = action_data.get("parallel") {
+            let group = deserialize_parallel_group(parallel, &action_data, actions.len(), action_registry, condition_registry)
+                .map_err(|error| error.with_path_prefix(&format!("[{}]", index)))?;
+            for (group_index, action) in group.iter().enumerate() {
+                check_unique_id(&mut action_ids, action, group_index)
+                    .map_err(|error| error.with_path_prefix(&format!("[{}].parallel[{}]", index, group_index)))?;
             }
+            actions.extend(group);
+            continue;
+        }
+
+        let action = deserialize_indexed_action(index, action_data, action_registry, condition_registry)?;
+        check_unique_id(&mut action_ids, &action, index).map_err(|error| error.with_path_prefix(&format!("[{}]", index)))?;
+        actions.push(action);
+    }
+
+    Ok(actions)
+}
+
+// Actions sharing an id would overwrite each other's output vars
+fn check_unique_id(action_ids: &mut HashSet<String>, action: &ActionDefinition, index: usize) -> Result<(), FlowLoadError> {
+    let id = &action.id;
+    if action_ids.insert(id.to_string()) {
+        return Ok(());
+    }
+    Err(FlowLoadError::new(FlowLoadErrorKind::DuplicateAction, format!("Duplicate action id: {}", id))
+        .with_path("config.id")
+        .with_action(index, Some(id.to_string())))
+}
+
+fn deserialize_indexed_action(
+    index: usize,
+    action_data: HashMap<String, JsonValue>,
+    action_registry: &ActionRegistry,
+    condition_registry: &ConditionRegistry,
+) -> Result<ActionDefinition, FlowLoadError> {
+    let action_id = action_data
+        .get("config")
+        .and_then(|config| config.get("id"))
+        .and_then(|id| id.as_str())
+        .map(str::to_string);
+
+    deserialize_action(action_data, action_registry, condition_registry).map_err(|error| {
+        FlowLoadError::from(error)
+            .with_path_prefix(&format!("[{}]", index))
+            .with_action(index, action_id)
+    })
+}
+
+// Json Structure
+// {
+//     "parallel": [ ...actions ],
+//     "on_conflict": "fail" | "first_wins" | "last_wins"
+// }
+fn deserialize_parallel_group(
+    parallel: &JsonValue,
+    group_data: &HashMap<String, JsonValue>,
+    first_index: usize,
+    action_registry: &ActionRegistry,
+    condition_registry: &ConditionRegistry,
+) -> Result<Vec<ActionDefinition>, FlowLoadError> {
+    let Some(parallel) = parallel.as_array() else {
+        return Err(DeserializeActionError::IncorrectParallelType(parallel.to_string()).into());
+    };
+    let on_conflict = deserialize_on_conflict(group_data.get("on_conflict").cloned())?;
+    let parallel_group = ParallelGroup::new(first_index, on_conflict);
+
+    let mut actions = Vec::new();
+    for (index, action_data) in parallel.iter().enumerate() {
+        let action_data: HashMap<String, JsonValue> = serde_json::from_value(action_data.clone())
+            .map_err(|error| FlowLoadError::from(error).with_path(&format!("parallel[{}]", index)))?;
+        if action_data.contains_key("parallel") {
+            return Err(FlowLoadError::from(DeserializeActionError::NestedParallelGroup)
+                .with_path_prefix(&format!("parallel[{}]", index)));
         }
+
+        let action = deserialize_indexed_action(index, action_data, action_registry, condition_registry)
+            .map_err(|error| error.with_path_prefix("parallel"))?;
+        actions.push(action.with_parallel_group(parallel_group));
     }
 
     Ok(actions)
-}
\ No newline at end of file
+}
+
+fn deserialize_action(
+    action_data: HashMap<String, JsonValue>,
+    action_registry: &ActionRegistry,
+    condition_registry: &ConditionRegistry,
+) -> Result<ActionDefinition, DeserializeActionError> {
+    let action_type = action_data
+        .get("action_type")
+        .and_then(|v| v.as_str())
+        .ok_or(DeserializeActionError::MissingActionType)?;
+
+    let action_registration = action_registry
+        .get_actions()
+        .get(action_type)
+        .ok_or_else(|| DeserializeActionError::UnknownActionType(action_type.to_string()))?;
+
+    let config = deserialize_config(action_data.get("config").cloned())?;
+    let input_vars = deserialize_input_vars(action_data.get("input_vars").cloned())?;
+    let output_vars = deserialize_output_vars(action_data.get("output_vars").cloned())?;
+    let policy = deserialize_policy(action_data.get("policy").cloned())?;
+    let when = deserialize_when(action_data.get("when"), condition_registry)?;
+
+    action_registration
+        .get_schema()
+        .validate(&config, &input_vars, &output_vars)
+        .map_err(DeserializeActionError::SchemaViolation)?;
+
+    let action = action_registration
+        .get_factory()
+        .create(&config, &input_vars, &output_vars)
+        .map_err(DeserializeActionError::InvalidConfig)?;
+
+    Ok(ActionDefinition::new(
+        config["id"].as_str().unwrap_or_default().to_string(),
+        config["name"].as_str().unwrap_or_default().to_string(),
+        action_type.to_string(),
+        input_vars,
+        output_vars,
+        action,
+    )
+    .with_policy(policy)
+    .with_when(when))
+}
@@ -8,6 +8,14 @@ use crate::graph::node::node_context::{NodeContext, Value};
 pub enum VarParseError {
     VariableNotFound,
     MissingVars(String),
+    // A required input var whose path doesn't resolve in the node context
+    MissingInputVar { name: String, path: String },
+    InvalidMapping { name: String, reason: String },
+    InvalidInputVars(String),
+    MissingActionId,
+    InvalidOutputVars(String),
+    UndeclaredOutputVar(String),
+    DuplicatedOutputVar(String),
 }
 
 impl fmt::Display for VarParseError {
@@ -15,72 +23,206 @@ impl fmt::Display for VarParseError {
         match self {
             VarParseError::VariableNotFound => write!(f, "Variable not found"),
             VarParseError::MissingVars(var) => write!(f, "Missing vars: {}", var),
+            VarParseError::MissingInputVar { name, path } => write!(f, "Input var {} is missing, {} is not in the context", name, path),
+            VarParseError::InvalidMapping { name, reason } => write!(f, "Invalid mapping of input var {}: {}", name, reason),
+            VarParseError::InvalidInputVars(input_vars) => write!(f, "Input vars must be an object, found {}", input_vars),
+            VarParseError::MissingActionId => write!(f, "Action config id is required to namespace output vars"),
+            VarParseError::InvalidOutputVars(reason) => write!(f, "Invalid output vars: {}", reason),
+            VarParseError::UndeclaredOutputVar(var) => write!(f, "Output var {} is not declared", var),
+            VarParseError::DuplicatedOutputVar(var) => write!(f, "Output var {} was written twice", var),
         }
     }
 }
 
 impl Error for VarParseError {}
 
+/// Where an input var takes its value from
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputVarMapping {
+    // A path into the node context, the default is used when it doesn't resolve
+    Path { path: String, required: bool, default: Option<Value> },
+    Literal(Value),
+}
+
+impl InputVarMapping {
+    // Json Structure
+    // "customer": "crm.customer"             required, fails when missing
+    // "customer": "crm.customer?"            optional, left out when missing
+    // "order_id": "crm.customer.orders[0].id"
+    // "language": { "path": "user.language", "default": "en" }
+    // "notes": { "path": "crm.notes", "required": false }
+    // "channel": { "literal": "whatsapp" }
+    pub fn from_json(name: &str, mapping: &JsonValue) -> Result<Self, VarParseError> {
+        let invalid = |reason: &str| VarParseError::InvalidMapping { name: name.to_string(), reason: reason.to_string() };
+
+        let mapping = match mapping {
+            JsonValue::String(path) => {
+                return Ok(match path.strip_suffix('?') {
+                    Some(path) => InputVarMapping::Path { path: path.to_string(), required: false, default: None },
+                    None => InputVarMapping::Path { path: path.clone(), required: true, default: None },
+                });
+            }
+            JsonValue::Object(mapping) => mapping,
+            _ => return Err(invalid("expected a path or an object")),
+        };
+
+        if let Some(key) = mapping.keys().find(|key| !["path", "default", "required", "literal"].contains(&key.as_str())) {
+            return Err(invalid(&format!("unknown key {}", key)));
+        }
+
+        match (mapping.get("path"), mapping.get("literal")) {
+            (Some(_), Some(_)) => Err(invalid("path and literal can't be combined")),
+            (None, Some(literal)) if mapping.len() == 1 => Value::try_from(literal.clone())
+                .map(InputVarMapping::Literal)
+                .map_err(|error| invalid(&error.to_string())),
+            (None, Some(_)) => Err(invalid("a literal takes no default or required")),
+            (None, None) => Err(invalid("expected a path or a literal")),
+            (Some(path), None) => {
+                let path = path.as_str().ok_or_else(|| invalid("path must be a string"))?;
+                let default = match mapping.get("default") {
+                    Some(default) => Some(Value::try_from(default.clone()).map_err(|error| invalid(&error.to_string()))?),
+                    None => None,
+                };
+                let required = match mapping.get("required") {
+                    Some(required) => required.as_bool().ok_or_else(|| invalid("required must be a boolean"))?,
+                    // A default means the var is never missing
+                    None => default.is_none(),
+                };
+                Ok(InputVarMapping::Path { path: path.to_string(), required, default })
+            }
+        }
+    }
+
+    /// Path the var must resolve in the context, none for literals, optional vars or vars with a default
+    pub fn required_path(&self) -> Option<&str> {
+        match self {
+            InputVarMapping::Path { path, required: true, default: None } => Some(path),
+            _ => None,
+        }
+    }
+
+    fn resolve(&self, name: &str, node_context: &NodeContext) -> Result<Option<Value>, VarParseError> {
+        match self {
+            InputVarMapping::Literal(value) => Ok(Some(value.clone())),
+            InputVarMapping::Path { path, required, default } => match node_context.resolve_path(path) {
+                Some(value) => Ok(Some(value.clone())),
+                None if default.is_some() => Ok(default.clone()),
+                None if *required => Err(VarParseError::MissingInputVar { name: name.to_string(), path: path.clone() }),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+/// Mappings of the input vars of an action or condition, by input var name
+pub fn parse_input_var_mappings(input_vars: &JsonValue) -> Result<Vec<(String, InputVarMapping)>, VarParseError> {
+    let object_input_vars = input_vars
+        .as_object()
+        .ok_or_else(|| VarParseError::InvalidInputVars(input_vars.to_string()))?;
+
+    object_input_vars
+        .iter()
+        .map(|(name, mapping)| Ok((name.clone(), InputVarMapping::from_json(name, mapping)?)))
+        .collect()
+}
+
+/// Values of the input vars in the node context. Optional vars that can't be resolved are left out.
 pub fn parse_input_vars(
     input_vars: &JsonValue,
     node_context: &NodeContext,
-) -> Result<HashMap<String, Value>, serde_json::Error> {
-    let object_input_vars = input_vars.as_object().unwrap();
-    let mut input_vars: HashMap<String, Value> = HashMap::new();
-
-    for (key, value) in object_input_vars {
-        let value = value.as_str().unwrap();
-        let variable = node_context.variables.get(value);
-        if let Some(variable) = variable {
-            input_vars.insert(key.to_string(), variable.clone());
+) -> Result<HashMap<String, Value>, VarParseError> {
+    let mut values: HashMap<String, Value> = HashMap::new();
+
+    for (name, mapping) in parse_input_var_mappings(input_vars)? {
+        if let Some(value) = mapping.resolve(&name, node_context)? {
+            values.insert(name, value);
         }
     }
 
-    Ok(input_vars)
+    Ok(values)
+}
+
+/// An output var as declared in the `output_vars` of an action, `"summary?"` marks it optional
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputVar {
+    pub name: String,
+    pub required: bool,
 }
 
+/// Output vars declared by an action, names must be unique
+pub fn parse_output_vars(output_vars: &JsonValue) -> Result<Vec<OutputVar>, VarParseError> {
+    let declarations = output_vars
+        .as_array()
+        .ok_or_else(|| VarParseError::InvalidOutputVars(format!("expected an array, found {}", output_vars)))?;
+
+    let mut parsed: Vec<OutputVar> = Vec::new();
+    for declaration in declarations {
+        let declaration = declaration
+            .as_str()
+            .ok_or_else(|| VarParseError::InvalidOutputVars(format!("expected a name, found {}", declaration)))?;
+        let output_var = match declaration.strip_suffix('?') {
+            Some(name) => OutputVar { name: name.to_string(), required: false },
+            None => OutputVar { name: declaration.to_string(), required: true },
+        };
+        if parsed.iter().any(|declared| declared.name == output_var.name) {
+            return Err(VarParseError::InvalidOutputVars(format!("{} is declared twice", output_var.name)));
+        }
+        parsed.push(output_var);
+    }
+
+    Ok(parsed)
+}
+
+/// Writes the outputs of an action into the node context, namespaced by the action id.
+/// Every required output must be written exactly once before `build`.
 #[derive(Debug, Clone)]
 pub struct OutputVarsBuilder {
-    node_name: String,
+    action_id: String,
     node_context: NodeContext,
-    out_put_vars: Vec<String>,
+    out_put_vars: Vec<OutputVar>,
+    written_vars: Vec<String>,
 }
 
 impl OutputVarsBuilder {
-    pub fn new(config: &JsonValue, output_vars: &JsonValue, node_context: NodeContext) -> Self {
-        let node_name = config.get("name").unwrap().as_str().unwrap().to_string();
-
-        let out_put_vars: Vec<String> = output_vars
-            .as_array()
-            .unwrap()
-            .iter()
-            .map(|v| v.as_str().unwrap().to_string())
-            .collect();
-
-        OutputVarsBuilder {
-            node_name,
+    pub fn new(config: &JsonValue, output_vars: &JsonValue, node_context: NodeContext) -> Result<Self, VarParseError> {
+        let action_id = config
+            .get("id")
+            .and_then(|id| id.as_str())
+            .ok_or(VarParseError::MissingActionId)?
+            .to_string();
+
+        Ok(OutputVarsBuilder {
+            action_id,
             node_context,
-            out_put_vars,
-        }
+            out_put_vars: parse_output_vars(output_vars)?,
+            written_vars: Vec::new(),
+        })
     }
 
-    pub fn add_var(&mut self, var: String, value: Value) -> &mut Self {
-        if self.out_put_vars.contains(&var) {
-            let index = self.out_put_vars.iter().position(|v| v == &var).unwrap();
-            self.out_put_vars.remove(index);
-            let new_var = format!("{}.{}", self.node_name, var);
-
-            self.node_context.variables.insert(new_var, value);
-        } else {
-            panic!("Variable '{}' not found", var);
+    pub fn add_var(&mut self, var: String, value: Value) -> Result<&mut Self, VarParseError> {
+        if !self.out_put_vars.iter().any(|output_var| output_var.name == var) {
+            return Err(VarParseError::UndeclaredOutputVar(var));
         }
+        if self.written_vars.contains(&var) {
+            return Err(VarParseError::DuplicatedOutputVar(var));
+        }
+
+        let new_var = format!("{}.{}", self.action_id, var);
+        self.node_context.variables.insert(new_var, value);
+        self.written_vars.push(var);
 
-        self
+        Ok(self)
     }
 
     pub fn build(&self) -> Result<NodeContext, VarParseError> {
-        if self.out_put_vars.len() > 0 {
-            return Err(VarParseError::MissingVars(self.out_put_vars.join(", ")));
+        let missing_vars: Vec<&str> = self
+            .out_put_vars
+            .iter()
+            .filter(|output_var| output_var.required && !self.written_vars.contains(&output_var.name))
+            .map(|output_var| output_var.name.as_str())
+            .collect();
+        if !missing_vars.is_empty() {
+            return Err(VarParseError::MissingVars(missing_vars.join(", ")));
         }
 
         Ok(self.node_context.clone())
@@ -133,60 +275,132 @@ mod tests {
         let node_context = NodeContext::new();
         let output_vars = json!(["var1", "var2", "var3"]);
         let mut output_vars_builder =
-            OutputVarsBuilder::new(&json!({"name": "node_name"}), &output_vars, node_context);
+            OutputVarsBuilder::new(&json!({"id": "action_id", "name": "node_name"}), &output_vars, node_context).unwrap();
         
-        output_vars_builder.add_var("var1".to_string(), Value::String("foo".to_string()));
-        output_vars_builder.add_var("var2".to_string(), Value::String("bar".to_string()));
-        output_vars_builder.add_var("var3".to_string(), Value::String("baz".to_string()));
+        output_vars_builder.add_var("var1".to_string(), Value::String("foo".to_string())).unwrap();
+        output_vars_builder.add_var("var2".to_string(), Value::String("bar".to_string())).unwrap();
+        output_vars_builder.add_var("var3".to_string(), Value::String("baz".to_string())).unwrap();
 
         let node_context = output_vars_builder.build().unwrap();
 
         assert_eq!(node_context.variables.len(), 3);
 
-        let var1 = node_context.variables.get("node_name.var1").unwrap();
+        let var1 = node_context.variables.get("action_id.var1").unwrap();
         assert_eq!(var1, &Value::String("foo".to_string()));
 
-        let var2 = node_context.variables.get("node_name.var2").unwrap();
+        let var2 = node_context.variables.get("action_id.var2").unwrap();
         assert_eq!(var2, &Value::String("bar".to_string()));
 
-        let var3 = node_context.variables.get("node_name.var3").unwrap();
+        let var3 = node_context.variables.get("action_id.var3").unwrap();
         assert_eq!(var3, &Value::String("baz".to_string()));
     }
 
     #[test]
-    #[should_panic]
     fn test_output_vars_builder_with_not_found_var() {
         let node_context = NodeContext::new();
         let output_vars = json!(["var1", "var2", "var3"]);
         let mut output_vars_builder =
-            OutputVarsBuilder::new(&json!({"name": "node_name"}), &output_vars, node_context);
+            OutputVarsBuilder::new(&json!({"id": "action_id"}), &output_vars, node_context).unwrap();
         
-        output_vars_builder.add_var("var123".to_string(), Value::String("foo".to_string()));
-        output_vars_builder.add_var("var1".to_string(), Value::String("bar".to_string()));
-        output_vars_builder.add_var("var2".to_string(), Value::String("bar".to_string()));
-        output_vars_builder.add_var("var3".to_string(), Value::String("baz".to_string()));
+        let result = output_vars_builder.add_var("var123".to_string(), Value::String("foo".to_string()));
 
-        output_vars_builder.build().unwrap();
+        assert!(matches!(result, Err(VarParseError::UndeclaredOutputVar(var)) if var == "var123"));
     }
 
     #[test]
-    #[ignore = "Implement missing vars"]
     fn test_output_vars_builder_with_missing_vars() {
         let node_context = NodeContext::new();
         let output_vars = json!(["var1", "var2", "var3"]);
         let mut output_vars_builder =
-            OutputVarsBuilder::new(&json!({"name": "node_name"}), &output_vars, node_context);
+            OutputVarsBuilder::new(&json!({"id": "action_id"}), &output_vars, node_context).unwrap();
         
-        output_vars_builder.add_var("var1".to_string(), Value::String("foo".to_string()));
-        output_vars_builder.add_var("var1".to_string(), Value::String("bar".to_string()));
-        output_vars_builder.add_var("var3".to_string(), Value::String("baz".to_string()));
+        output_vars_builder.add_var("var1".to_string(), Value::String("foo".to_string())).unwrap();
+        let duplicated = output_vars_builder.add_var("var1".to_string(), Value::String("bar".to_string()));
+        assert!(matches!(duplicated, Err(VarParseError::DuplicatedOutputVar(_))));
+        output_vars_builder.add_var("var3".to_string(), Value::String("baz".to_string())).unwrap();
 
         let node_context = output_vars_builder.build();
-        assert!(node_context.is_err(), "Expected an error due to duplicated variables");
+        assert!(node_context.is_err(), "Expected an error due to missing variables");
         if let Err(VarParseError::MissingVars(missing_vars)) = node_context {
             assert!(missing_vars.contains("var2"), "Expected 'var2' to be missing");
         } else {
             panic!("Expected VarParseError::MissingVars");
         }
     }
+
+    #[test]
+    fn test_output_vars_builder_with_optional_vars() {
+        let output_vars = json!(["reply", "sources?"]);
+        let mut output_vars_builder =
+            OutputVarsBuilder::new(&json!({"id": "kb"}), &output_vars, NodeContext::new()).unwrap();
+
+        output_vars_builder.add_var("reply".to_string(), Value::String("42".to_string())).unwrap();
+
+        let node_context = output_vars_builder.build().unwrap();
+        assert_eq!(node_context.variables.len(), 1);
+    }
+
+    #[test]
+    fn test_output_vars_builder_rejects_invalid_declarations() {
+        assert!(matches!(
+            OutputVarsBuilder::new(&json!({"name": "kb"}), &json!([]), NodeContext::new()),
+            Err(VarParseError::MissingActionId)
+        ));
+        assert!(matches!(
+            OutputVarsBuilder::new(&json!({"id": "kb"}), &json!(["reply", "reply?"]), NodeContext::new()),
+            Err(VarParseError::InvalidOutputVars(_))
+        ));
+    }
+
+    fn create_crm_context() -> NodeContext {
+        let mut node_context = NodeContext::new();
+        node_context.variables.insert(
+            "crm.customer".to_string(),
+            Value::try_from(json!({"name": "Ada", "orders": [{"id": "ord-1"}]})).unwrap(),
+        );
+        node_context
+    }
+
+    #[test]
+    fn test_parse_input_vars_with_paths_and_defaults() {
+        let input_vars = json!({
+            "name": "crm.customer.name",
+            "order_id": "crm.customer.orders[0].id",
+            "notes": "crm.customer.notes?",
+            "language": { "path": "user.language", "default": "en" },
+            "channel": { "literal": "whatsapp" },
+        });
+
+        let vars = parse_input_vars(&input_vars, &create_crm_context()).unwrap();
+
+        assert_eq!(vars.get("name"), Some(&Value::String("Ada".to_string())));
+        assert_eq!(vars.get("order_id"), Some(&Value::String("ord-1".to_string())));
+        assert_eq!(vars.get("notes"), None);
+        assert_eq!(vars.get("language"), Some(&Value::String("en".to_string())));
+        assert_eq!(vars.get("channel"), Some(&Value::String("whatsapp".to_string())));
+    }
+
+    #[test]
+    fn test_parse_input_vars_fails_on_missing_required_vars() {
+        let input_vars = json!({ "order_id": "crm.customer.orders[3].id" });
+
+        let error = parse_input_vars(&input_vars, &create_crm_context()).unwrap_err();
+
+        assert_eq!(error.to_string(), "Input var order_id is missing, crm.customer.orders[3].id is not in the context");
+    }
+
+    #[test]
+    fn test_parse_input_vars_rejects_invalid_mappings() {
+        let context = create_crm_context();
+
+        for input_vars in [
+            json!({ "name": 3 }),
+            json!({ "name": { "path": "crm.customer", "literal": "Ada" } }),
+            json!({ "name": { "path": "crm.customer", "optional": true } }),
+            json!({ "name": { "path": "crm.customer", "required": "yes" } }),
+            json!(["crm.customer"]),
+        ] {
+            assert!(parse_input_vars(&input_vars, &context).is_err(), "{} should be invalid", input_vars);
+        }
+    }
 }
@@ -0,0 +1,70 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::graph::config_schema::ComponentSchema;
+
+pub const DEFAULT_CATEGORY: &str = "general";
+pub const DEFAULT_VERSION: &str = "1.0.0";
+
+/// Human readable description of a registered action or condition type
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentMetadata {
+    // Display name, the type itself is shown when empty
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub version: String,
+}
+
+impl Default for ComponentMetadata {
+    fn default() -> Self {
+        ComponentMetadata {
+            name: String::new(),
+            description: String::new(),
+            category: DEFAULT_CATEGORY.to_string(),
+            version: DEFAULT_VERSION.to_string(),
+        }
+    }
+}
+
+/// One action or condition type usable by flows on this server
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogEntry {
+    // Value of `action_type` / `condition_type` in the flow json
+    pub component_type: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub version: String,
+    // JSON Schema of the config, input_vars and output_vars
+    pub json_schema: JsonValue,
+}
+
+impl CatalogEntry {
+    pub fn new(component_type: &str, metadata: &ComponentMetadata, schema: &ComponentSchema) -> Self {
+        let name = if metadata.name.is_empty() { component_type } else { metadata.name.as_str() };
+
+        CatalogEntry {
+            component_type: component_type.to_string(),
+            name: name.to_string(),
+            description: metadata.description.clone(),
+            category: metadata.category.clone(),
+            version: metadata.version.clone(),
+            json_schema: schema.to_json_schema(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_falls_back_to_the_type_as_name() {
+        let entry = CatalogEntry::new("send_message", &ComponentMetadata::default(), &ComponentSchema::new());
+
+        assert_eq!(entry.name, "send_message");
+        assert_eq!(entry.category, DEFAULT_CATEGORY);
+        assert_eq!(entry.version, DEFAULT_VERSION);
+    }
+}
@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+
+use crate::graph::{condition::condition::Condition, node::node_context::NodeContext};
+
+// Condition types handled by the deserializer itself, their children are given in "conditions"
+pub const ANY_OF_CONDITION_TYPE: &str = "any_of";
+pub const ALL_OF_CONDITION_TYPE: &str = "all_of";
+pub const NOT_CONDITION_TYPE: &str = "not";
+
+/// Holds when at least one of its conditions holds, an empty `AnyOf` never holds
+#[derive(Clone)]
+pub struct AnyOf {
+    conditions: Vec<Box<dyn Condition<NodeContext>>>,
+}
+
+impl AnyOf {
+    pub fn new(conditions: Vec<Box<dyn Condition<NodeContext>>>) -> Self {
+        AnyOf { conditions }
+    }
+}
+
+#[async_trait]
+impl Condition<NodeContext> for AnyOf {
+    async fn evaluate(&self, context: &NodeContext) -> bool {
+        for condition in &self.conditions {
+            if condition.evaluate(context).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn clone_box(&self) -> Box<dyn Condition<NodeContext>> {
+        Box::new(self.clone())
+    }
+}
+
+/// Holds when every one of its conditions holds, an empty `AllOf` always holds
+#[derive(Clone)]
+pub struct AllOf {
+    conditions: Vec<Box<dyn Condition<NodeContext>>>,
+}
+
+impl AllOf {
+    pub fn new(conditions: Vec<Box<dyn Condition<NodeContext>>>) -> Self {
+        AllOf { conditions }
+    }
+}
+
+#[async_trait]
+impl Condition<NodeContext> for AllOf {
+    async fn evaluate(&self, context: &NodeContext) -> bool {
+        for condition in &self.conditions {
+            if !condition.evaluate(context).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn clone_box(&self) -> Box<dyn Condition<NodeContext>> {
+        Box::new(self.clone())
+    }
+}
+
+/// Negates the wrapped condition
+#[derive(Clone)]
+pub struct Not {
+    condition: Box<dyn Condition<NodeContext>>,
+}
+
+impl Not {
+    pub fn new(condition: Box<dyn Condition<NodeContext>>) -> Self {
+        Not { condition }
+    }
+}
+
+#[async_trait]
+impl Condition<NodeContext> for Not {
+    async fn evaluate(&self, context: &NodeContext) -> bool {
+        !self.condition.evaluate(context).await
+    }
+
+    fn clone_box(&self) -> Box<dyn Condition<NodeContext>> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::condition::tests::condition_implementation::{NegativeCondition, PositiveCondition};
+
+    use super::*;
+
+    fn positive() -> Box<dyn Condition<NodeContext>> {
+        Box::new(PositiveCondition)
+    }
+
+    fn negative() -> Box<dyn Condition<NodeContext>> {
+        Box::new(NegativeCondition)
+    }
+
+    #[tokio::test]
+    async fn test_any_of() {
+        let context = NodeContext::new();
+
+        assert!(AnyOf::new(vec![negative(), positive()]).evaluate(&context).await);
+        assert!(!AnyOf::new(vec![negative(), negative()]).evaluate(&context).await);
+        assert!(!AnyOf::new(vec![]).evaluate(&context).await);
+    }
+
+    #[tokio::test]
+    async fn test_all_of() {
+        let context = NodeContext::new();
+
+        assert!(AllOf::new(vec![positive(), positive()]).evaluate(&context).await);
+        assert!(!AllOf::new(vec![positive(), negative()]).evaluate(&context).await);
+        assert!(AllOf::new(vec![]).evaluate(&context).await);
+    }
+
+    #[tokio::test]
+    async fn test_not() {
+        let context = NodeContext::new();
+
+        assert!(Not::new(negative()).evaluate(&context).await);
+        assert!(!Not::new(positive()).evaluate(&context).await);
+    }
+
+    #[tokio::test]
+    async fn test_nested_combinators() {
+        // (negative OR positive) AND NOT negative
+        let condition = AllOf::new(vec![
+            Box::new(AnyOf::new(vec![negative(), positive()])),
+            Box::new(Not::new(negative())),
+        ]);
+
+        assert!(condition.clone_box().evaluate(&NodeContext::new()).await);
+    }
+}
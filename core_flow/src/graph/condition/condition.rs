@@ -1,11 +1,18 @@
-use std::{collections::HashMap, fmt};
+use std::fmt;
 
 use serde_json::Value as JsonValue;
 
 use async_trait::async_trait;
 
-use crate::graph::{action::utils::action_deserializer::deserialize_input_vars, condition::condition_registry::ConditionRegistry, node::node_context::NodeContext};
-use serde::de::Error as SerdeError;
+use crate::graph::{
+    condition::{
+        composite_condition::{AllOf, AnyOf, Not, ALL_OF_CONDITION_TYPE, ANY_OF_CONDITION_TYPE, NOT_CONDITION_TYPE},
+        condition_registry::ConditionRegistry,
+        expression::expression_condition::{ExpressionCondition, EXPRESSION_CONDITION_TYPE},
+    },
+    flow_load_error::{FlowLoadError, FlowLoadErrorKind},
+    node::node_context::NodeContext,
+};
 
 // Trait for condition evaluation
 #[async_trait]
@@ -26,50 +33,149 @@ impl Clone for Box<dyn Condition<NodeContext>> {
     }
 }
 
+// Json Structure, composite conditions nest other conditions, expressions are parsed here and
+// every other condition type is looked up in the registry
+// [
+//     {
+//         "condition_type": "expression",
+//         "config": { "expression": "ai_action.intent == \"refund\" && order.total > 100" }
+//     },
+//     {
+//         "condition_type": "any_of" | "all_of" | "not",
+//         "conditions": [
+//             {
+//                 "condition_type": "positive_condition",
+//                 "config": {},
+//                 "input_vars": {}
+//             }
+//         ]
+//     }
+// ]
 pub fn deserialize_conditions_with_config(
     json_data: &str,
     condition_registry: &ConditionRegistry,
-) -> Result<Vec<Box<dyn Condition<NodeContext>>>, serde_json::Error> {
-    let conditions_data: Vec<HashMap<String, JsonValue>> = serde_json::from_str(json_data)?;
-    let mut conditions: Vec<Box<dyn Condition<NodeContext>>> = Vec::new();
-
-    for condition_data in conditions_data {
-        if let Some(condition_type) = condition_data.get("condition_type").and_then(|v| v.as_str()) {
-            if let Some(condition_constructor) = condition_registry.get_conditions().get(condition_type) {
-                let config = condition_data.get("config");
-                let input_vars = deserialize_input_vars(condition_data.get("input_vars").cloned())?; 
-                if config.is_some() {
-                    conditions.push(condition_constructor(config.unwrap(), &input_vars));
-                } else {
-                    conditions.push(condition_constructor(&JsonValue::Null, &input_vars));
-                }
-            } else {
-                return Err(SerdeError::custom(format!(
-                    "Unknown condition type: {}",
-                    condition_type
-                )));
-            }
+) -> Result<Vec<Box<dyn Condition<NodeContext>>>, FlowLoadError> {
+    let conditions_data: JsonValue = serde_json::from_str(json_data)?;
+    deserialize_condition_list(&conditions_data, condition_registry)
+}
+
+fn deserialize_condition_list(
+    conditions_data: &JsonValue,
+    condition_registry: &ConditionRegistry,
+) -> Result<Vec<Box<dyn Condition<NodeContext>>>, FlowLoadError> {
+    let conditions_data = conditions_data
+        .as_array()
+        .ok_or_else(|| FlowLoadError::new(FlowLoadErrorKind::InvalidFieldType, "Conditions must be an array".to_string()))?;
+
+    conditions_data
+        .iter()
+        .enumerate()
+        .map(|(index, condition_data)| {
+            deserialize_condition(condition_data, condition_registry)
+                .map_err(|error| error.with_path_prefix(&format!("[{}]", index)).with_condition(index))
+        })
+        .collect()
+}
+
+fn deserialize_condition(
+    condition_data: &JsonValue,
+    condition_registry: &ConditionRegistry,
+) -> Result<Box<dyn Condition<NodeContext>>, FlowLoadError> {
+    let condition_type = condition_data
+        .get("condition_type")
+        .ok_or_else(|| {
+            FlowLoadError::new(FlowLoadErrorKind::MissingField, "Condition type is required".to_string())
+                .with_path("condition_type")
+        })?
+        .as_str()
+        .ok_or_else(|| {
+            FlowLoadError::new(FlowLoadErrorKind::InvalidFieldType, "Condition type must be a string".to_string())
+                .with_path("condition_type")
+        })?;
+
+    if [ANY_OF_CONDITION_TYPE, ALL_OF_CONDITION_TYPE, NOT_CONDITION_TYPE].contains(&condition_type) {
+        let nested_conditions = match condition_data.get("conditions") {
+            Some(nested) => deserialize_condition_list(nested, condition_registry)
+                .map_err(|error| error.with_path_prefix("conditions"))?,
+            None => Vec::new(),
+        };
+        if nested_conditions.is_empty() {
+            return Err(FlowLoadError::new(
+                FlowLoadErrorKind::InvalidFieldValue,
+                format!("Condition type {} requires at least one nested condition", condition_type),
+            )
+            .with_path("conditions"));
         }
+
+        return Ok(match condition_type {
+            ANY_OF_CONDITION_TYPE => Box::new(AnyOf::new(nested_conditions)),
+            ALL_OF_CONDITION_TYPE => Box::new(AllOf::new(nested_conditions)),
+            // Several conditions under "not" are negated together: NOT (a AND b)
+            _ if nested_conditions.len() == 1 => Box::new(Not::new(nested_conditions.into_iter().next().unwrap())),
+            _ => Box::new(Not::new(Box::new(AllOf::new(nested_conditions)))),
+        });
+    }
+
+    let config = condition_data.get("config").unwrap_or(&JsonValue::Null);
+
+    if condition_type == EXPRESSION_CONDITION_TYPE {
+        let condition = ExpressionCondition::from_config(config)
+            .map_err(|error| {
+                FlowLoadError::new(FlowLoadErrorKind::InvalidExpression, format!("Invalid expression: {}", error))
+                    .with_path("config.expression")
+            })?;
+        return Ok(Box::new(condition));
+    }
+
+    // Conditions reading no variables may leave input_vars out
+    let input_vars = match condition_data.get("input_vars") {
+        None => JsonValue::Object(Default::default()),
+        Some(input_vars) if input_vars.is_object() => input_vars.clone(),
+        Some(input_vars) => {
+            return Err(FlowLoadError::new(
+                FlowLoadErrorKind::InvalidFieldType,
+                format!("Input vars must be an object, found {}", input_vars),
+            )
+            .with_path("input_vars"));
+        }
+    };
+
+    match condition_registry.get_conditions().get(condition_type) {
+        Some(condition_registration) => {
+            condition_registration
+                .get_schema()
+                .validate(config, &input_vars, &JsonValue::Null)?;
+            condition_registration
+                .get_factory()
+                .create(config, &input_vars)
+                .map_err(FlowLoadError::from)
+        }
+        None => Err(FlowLoadError::new(
+            FlowLoadErrorKind::UnknownConditionType,
+            format!("Unknown condition type: {}", condition_type),
+        )
+        .with_path("condition_type")),
     }
-    Ok(conditions)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::graph::condition::tests::condition_implementation::{ConfigurableCondition, NegativeCondition, PositiveCondition};
+    use crate::graph::config_error::ConfigError;
+    use crate::graph::node::node_context::Value;
 
     use super::*;
 
-    fn create_positive_condition(_: &JsonValue, _: &JsonValue) -> Box<dyn Condition<NodeContext>> {
-        Box::new(PositiveCondition {})
+    fn create_positive_condition(_: &JsonValue, _: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(PositiveCondition {}))
     }
 
-    fn create_negative_condition(_: &JsonValue, _: &JsonValue) -> Box<dyn Condition<NodeContext>> {
-        Box::new(NegativeCondition {})
+    fn create_negative_condition(_: &JsonValue, _: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(NegativeCondition {}))
     }
 
-    fn create_configurable_condition(config: &JsonValue, _: &JsonValue) -> Box<dyn Condition<NodeContext>> {
-        Box::new(ConfigurableCondition::new(config))
+    fn create_configurable_condition(config: &JsonValue, _: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(ConfigurableCondition::new(config)))
     }
 
     
@@ -87,11 +193,11 @@ mod tests {
         let mut condition_registry = ConditionRegistry::new();
         condition_registry.register_condition(
             "positive_condition",
-            create_positive_condition as fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>,
+            create_positive_condition,
         );
         condition_registry.register_condition(
             "negative_condition",
-            create_negative_condition as fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>,
+            create_negative_condition,
         );
 
         let conditions = deserialize_conditions_with_config(json, &condition_registry).unwrap();
@@ -113,7 +219,7 @@ mod tests {
         let mut condition_registry = ConditionRegistry::new();
         condition_registry.register_condition(
             "configurable_condition",
-            create_configurable_condition as fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>,
+            create_configurable_condition,
         );
 
         let conditions = deserialize_conditions_with_config(json, &condition_registry).unwrap();
@@ -124,4 +230,158 @@ mod tests {
 
         assert!(configurable_condition.evaluate(&NodeContext::new()).await);
     }
-}
\ No newline at end of file
+
+    fn create_condition_registry() -> ConditionRegistry {
+        let mut condition_registry = ConditionRegistry::new();
+        condition_registry.register_condition("positive_condition", create_positive_condition);
+        condition_registry.register_condition("negative_condition", create_negative_condition);
+        condition_registry
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_composite_conditions() {
+        // (negative OR positive) AND NOT (positive AND negative)
+        let json = r#"[
+            {
+                "condition_type": "all_of",
+                "conditions": [
+                    {
+                        "condition_type": "any_of",
+                        "conditions": [
+                            { "condition_type": "negative_condition", "input_vars": {} },
+                            { "condition_type": "positive_condition", "input_vars": {} }
+                        ]
+                    },
+                    {
+                        "condition_type": "not",
+                        "conditions": [
+                            { "condition_type": "positive_condition", "input_vars": {} },
+                            { "condition_type": "negative_condition", "input_vars": {} }
+                        ]
+                    }
+                ]
+            }
+        ]"#;
+
+        let conditions = deserialize_conditions_with_config(json, &create_condition_registry()).unwrap();
+
+        assert_eq!(conditions.len(), 1);
+        assert!(conditions[0].evaluate(&NodeContext::new()).await);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_not_condition() {
+        let json = r#"[
+            {
+                "condition_type": "not",
+                "conditions": [
+                    { "condition_type": "positive_condition", "input_vars": {} }
+                ]
+            }
+        ]"#;
+
+        let conditions = deserialize_conditions_with_config(json, &create_condition_registry()).unwrap();
+
+        assert!(!conditions[0].evaluate(&NodeContext::new()).await);
+    }
+
+    #[test]
+    fn test_deserialize_composite_condition_without_children() {
+        let json = r#"[{ "condition_type": "any_of", "conditions": [] }]"#;
+
+        let result = deserialize_conditions_with_config(json, &create_condition_registry());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_composite_condition_with_unknown_child() {
+        let json = r#"[
+            {
+                "condition_type": "any_of",
+                "conditions": [{ "condition_type": "unknown_condition", "input_vars": {} }]
+            }
+        ]"#;
+
+        let result = deserialize_conditions_with_config(json, &create_condition_registry());
+
+        assert_eq!(result.unwrap_err().path(), "[0].conditions[0].condition_type");
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_expression_condition() {
+        let json = r#"[
+            {
+                "condition_type": "expression",
+                "config": { "expression": "ai_action.intent == 'refund' && order.total > 100" }
+            }
+        ]"#;
+
+        let conditions = deserialize_conditions_with_config(json, &ConditionRegistry::new()).unwrap();
+
+        let mut context = NodeContext::new();
+        context.variables.insert("ai_action.intent".to_string(), Value::String("refund".to_string()));
+        context.variables.insert("order".to_string(), Value::try_from(serde_json::json!({"total": 120})).unwrap());
+        assert!(conditions[0].evaluate(&context).await);
+    }
+
+    #[test]
+    fn test_deserialize_expression_condition_with_syntax_error() {
+        let json = r#"[
+            {
+                "condition_type": "expression",
+                "config": { "expression": "intent == " }
+            }
+        ]"#;
+
+        let error = deserialize_conditions_with_config(json, &ConditionRegistry::new()).unwrap_err();
+
+        assert!(error.to_string().contains("unexpected end of expression at position 10"));
+    }
+
+    #[test]
+    fn test_deserialize_conditions_reports_failing_index() {
+        let json = r#"[
+            { "condition_type": "positive_condition" },
+            { "condition_type": "unknown_condition" }
+        ]"#;
+
+        let error = deserialize_conditions_with_config(json, &create_condition_registry()).unwrap_err();
+
+        assert_eq!(error.condition_index(), Some(1));
+        assert_eq!(error.kind(), FlowLoadErrorKind::UnknownConditionType);
+        assert_eq!(error.path(), "[1].condition_type");
+        assert_eq!(error.reason(), "Unknown condition type: unknown_condition");
+    }
+
+    #[test]
+    fn test_deserialize_conditions_without_condition_type() {
+        let json = r#"[{ "config": {} }]"#;
+
+        let error = deserialize_conditions_with_config(json, &create_condition_registry()).unwrap_err();
+
+        assert_eq!(error.condition_index(), Some(0));
+        assert_eq!(error.reason(), "Condition type is required");
+    }
+
+    #[test]
+    fn test_deserialize_conditions_with_invalid_input_vars() {
+        let json = r#"[{ "condition_type": "positive_condition", "input_vars": ["value"] }]"#;
+
+        let error = deserialize_conditions_with_config(json, &create_condition_registry()).unwrap_err();
+
+        assert!(error.reason().starts_with("Input vars must be an object"));
+    }
+
+    #[test]
+    fn test_deserialize_conditions_validates_the_registered_schema() {
+        let mut condition_registry = ConditionRegistry::new();
+        condition_registry.register_builtin_conditions();
+        let json = r#"[{ "condition_type": "contains", "config": { "value": 10 }, "input_vars": { "value": "order.note" } }]"#;
+
+        let error = deserialize_conditions_with_config(json, &condition_registry).unwrap_err();
+
+        assert_eq!(error.kind(), FlowLoadErrorKind::InvalidFieldType);
+        assert_eq!(error.path(), "[0].config.value");
+    }
+}
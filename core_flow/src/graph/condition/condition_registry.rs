@@ -1,11 +1,107 @@
 // core_flow/src/graph/edge/condition_registry.rs
 
 use std::collections::HashMap;
-use crate::graph::{condition::condition::Condition, node::node_context::NodeContext};
+use crate::graph::{
+    catalog::{CatalogEntry, ComponentMetadata, DEFAULT_VERSION},
+    condition::{
+        composite_condition::{ALL_OF_CONDITION_TYPE, ANY_OF_CONDITION_TYPE, NOT_CONDITION_TYPE},
+        condition::Condition,
+        expression::expression_condition::{EXPRESSION_CONDITION_TYPE, EXPRESSION_CONFIG_KEY},
+        variable_condition::{
+            LastMessageCondition, VariableCondition, CONTAINS_CONDITION_TYPE, EQUALS_CONDITION_TYPE,
+            EXISTS_CONDITION_TYPE, IN_RANGE_CONDITION_TYPE, IS_NULL_CONDITION_TYPE, LAST_MESSAGE_CONDITION_TYPE,
+            LIST_CONTAINS_CONDITION_TYPE, MATCHES_REGEX_CONDITION_TYPE, NOT_EQUALS_CONDITION_TYPE,
+            STARTS_WITH_CONDITION_TYPE,
+        },
+    },
+    config_error::ConfigError,
+    config_schema::{ComponentSchema, ConfigSchema, FieldType},
+    node::node_context::NodeContext,
+};
 use serde_json::Value as JsonValue;
 
+const VARIABLES_CATEGORY: &str = "variables";
+const MESSAGES_CATEGORY: &str = "messages";
+const LOGIC_CATEGORY: &str = "logic";
+
+/// Builds conditions of one type from their json config and input vars
+pub trait ConditionFactory: Send + Sync {
+    fn create(&self, config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError>;
+}
+
+// Plain constructors and closures capturing shared resources are factories
+impl<F> ConditionFactory for F
+where
+    F: Fn(&JsonValue, &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> + Send + Sync,
+{
+    fn create(&self, config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        self(config, input_vars)
+    }
+}
+
+/// A condition type as registered: the factory building it and the contract of its json
+pub struct ConditionRegistration {
+    factory: Box<dyn ConditionFactory>,
+    schema: ComponentSchema,
+    metadata: ComponentMetadata,
+}
+
+impl ConditionRegistration {
+    pub fn new<F>(condition_constructor: F) -> Self
+    where
+        F: Fn(&JsonValue, &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> + Send + Sync + 'static,
+    {
+        Self::from_factory(condition_constructor)
+    }
+
+    pub fn from_factory(condition_factory: impl ConditionFactory + 'static) -> Self {
+        ConditionRegistration {
+            factory: Box::new(condition_factory),
+            schema: ComponentSchema::new(),
+            metadata: ComponentMetadata::default(),
+        }
+    }
+
+    pub fn with_schema(mut self, schema: ComponentSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.metadata.name = name.to_string();
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.metadata.description = description.to_string();
+        self
+    }
+
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.metadata.category = category.to_string();
+        self
+    }
+
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.metadata.version = version.to_string();
+        self
+    }
+
+    pub fn get_factory(&self) -> &dyn ConditionFactory {
+        self.factory.as_ref()
+    }
+
+    pub fn get_schema(&self) -> &ComponentSchema {
+        &self.schema
+    }
+
+    pub fn get_metadata(&self) -> &ComponentMetadata {
+        &self.metadata
+    }
+}
+
 pub struct ConditionRegistry {
-    conditions: HashMap<String, fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>>,
+    conditions: HashMap<String, ConditionRegistration>,
 }
 
 impl ConditionRegistry {
@@ -15,33 +111,197 @@ impl ConditionRegistry {
         }
     }
 
-    pub fn register_condition(
+    pub fn register(&mut self, condition_type: &str, registration: ConditionRegistration) -> &mut Self {
+        self.conditions.insert(condition_type.to_string(), registration);
+        self
+    }
+
+    pub fn register_condition<F>(&mut self, condition_type: &str, condition_constructor: F) -> &mut Self
+    where
+        F: Fn(&JsonValue, &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> + Send + Sync + 'static,
+    {
+        self.register(condition_type, ConditionRegistration::new(condition_constructor))
+    }
+
+    pub fn register_condition_factory(
         &mut self,
         condition_type: &str,
-        condition_constructor: fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>,
+        condition_factory: impl ConditionFactory + 'static,
     ) -> &mut Self {
-        self.conditions
-            .insert(condition_type.to_string(), condition_constructor);
-        self
+        self.register(condition_type, ConditionRegistration::from_factory(condition_factory))
+    }
+
+    /// Registers the conditions shipped with core_flow, see `variable_condition` for their config
+    pub fn register_builtin_conditions(&mut self) -> &mut Self {
+        let value = |description: &str| ConfigSchema::new().with_field("value", FieldType::Any, description);
+        let text = |description: &str| ConfigSchema::new().with_field("value", FieldType::String, description);
+
+        self.register(
+            EQUALS_CONDITION_TYPE,
+            variable_condition(VariableCondition::create_equals_condition, value("Value the variable must equal"))
+                .with_name("Equals")
+                .with_description("Holds when the variable equals the configured value"),
+        )
+        .register(
+            NOT_EQUALS_CONDITION_TYPE,
+            variable_condition(VariableCondition::create_not_equals_condition, value("Value the variable must differ from"))
+                .with_name("Not equals")
+                .with_description("Holds when the variable differs from the configured value"),
+        )
+        .register(
+            IN_RANGE_CONDITION_TYPE,
+            variable_condition(
+                VariableCondition::create_in_range_condition,
+                ConfigSchema::new()
+                    .with_optional_field("min", FieldType::Any, "Inclusive lower bound, a number or an RFC 3339 datetime")
+                    .with_optional_field("max", FieldType::Any, "Inclusive upper bound, a number or an RFC 3339 datetime"),
+            )
+            .with_name("In range")
+            .with_description("Holds when the numeric or datetime variable lies within the bounds"),
+        )
+        .register(
+            CONTAINS_CONDITION_TYPE,
+            variable_condition(VariableCondition::create_contains_condition, text("Text the variable must contain"))
+                .with_name("Contains")
+                .with_description("Holds when the text variable contains the configured text"),
+        )
+        .register(
+            STARTS_WITH_CONDITION_TYPE,
+            variable_condition(VariableCondition::create_starts_with_condition, text("Text the variable must start with"))
+                .with_name("Starts with")
+                .with_description("Holds when the text variable starts with the configured text"),
+        )
+        .register(
+            MATCHES_REGEX_CONDITION_TYPE,
+            variable_condition(
+                VariableCondition::create_matches_regex_condition,
+                ConfigSchema::new().with_field("pattern", FieldType::String, "Regular expression the variable must match"),
+            )
+            .with_name("Matches regex")
+            .with_description("Holds when the text variable matches the pattern"),
+        )
+        .register(
+            EXISTS_CONDITION_TYPE,
+            variable_condition(VariableCondition::create_exists_condition, ConfigSchema::new())
+                .with_name("Exists")
+                .with_description("Holds when the variable is set, even to null"),
+        )
+        .register(
+            IS_NULL_CONDITION_TYPE,
+            variable_condition(VariableCondition::create_is_null_condition, ConfigSchema::new())
+                .with_name("Is null")
+                .with_description("Holds when the variable is missing or null"),
+        )
+        .register(
+            LIST_CONTAINS_CONDITION_TYPE,
+            variable_condition(VariableCondition::create_list_contains_condition, value("Item the list variable must contain"))
+                .with_name("List contains")
+                .with_description("Holds when the list variable contains the configured item"),
+        )
+        .register(
+            LAST_MESSAGE_CONDITION_TYPE,
+            ConditionRegistration::new(LastMessageCondition::create_last_message_condition)
+                .with_schema(LastMessageCondition::schema())
+                .with_name("Last message")
+                .with_description("Compares the text of the message that triggered the flow")
+                .with_category(MESSAGES_CATEGORY),
+        )
+    }
+
+    /// Every condition type flows can use: the registered ones and those built into the condition
+    /// json (expressions and combinators), sorted by type
+    pub fn catalog(&self) -> Vec<CatalogEntry> {
+        let mut entries: Vec<CatalogEntry> = self
+            .conditions
+            .iter()
+            .map(|(condition_type, registration)| {
+                CatalogEntry::new(condition_type, registration.get_metadata(), registration.get_schema())
+            })
+            .chain(
+                inline_conditions()
+                    .iter()
+                    .map(|(condition_type, metadata, schema)| CatalogEntry::new(condition_type, metadata, schema)),
+            )
+            .collect();
+        entries.sort_by(|a, b| a.component_type.cmp(&b.component_type));
+        entries
     }
 
-    pub fn get_conditions(&self) -> &HashMap<String, fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>> {
+    /// Schema of any condition type listed in the catalog
+    pub fn schema(&self, condition_type: &str) -> Option<ComponentSchema> {
+        if let Some(registration) = self.conditions.get(condition_type) {
+            return Some(registration.get_schema().clone());
+        }
+        inline_conditions()
+            .into_iter()
+            .find(|(inline_type, _, _)| *inline_type == condition_type)
+            .map(|(_, _, schema)| schema)
+    }
+
+    pub fn get_conditions(&self) -> &HashMap<String, ConditionRegistration> {
         &self.conditions
     }
 }
 
+// Condition types built into the condition json rather than registered
+fn inline_conditions() -> Vec<(&'static str, ComponentMetadata, ComponentSchema)> {
+    let logic = |name: &str, description: &str| ComponentMetadata {
+        name: name.to_string(),
+        description: description.to_string(),
+        category: LOGIC_CATEGORY.to_string(),
+        version: DEFAULT_VERSION.to_string(),
+    };
+    let expression_schema = ComponentSchema::new().with_config(
+        ConfigSchema::new().with_field(EXPRESSION_CONFIG_KEY, FieldType::String, "Expression over node context variables"),
+    );
+    let nested = "Takes its nested conditions from the \"conditions\" array";
+
+    vec![
+        (
+            EXPRESSION_CONDITION_TYPE,
+            logic("Expression", "Holds when the expression evaluates to a truthy value"),
+            expression_schema,
+        ),
+        (
+            ANY_OF_CONDITION_TYPE,
+            logic("Any of", &format!("Holds when any nested condition holds. {}", nested)),
+            ComponentSchema::new(),
+        ),
+        (
+            ALL_OF_CONDITION_TYPE,
+            logic("All of", &format!("Holds when every nested condition holds. {}", nested)),
+            ComponentSchema::new(),
+        ),
+        (
+            NOT_CONDITION_TYPE,
+            logic("Not", &format!("Holds when the nested conditions do not all hold. {}", nested)),
+            ComponentSchema::new(),
+        ),
+    ]
+}
+
+// Registration of a condition comparing the "value" input var
+fn variable_condition<F>(condition_constructor: F, config: ConfigSchema) -> ConditionRegistration
+where
+    F: Fn(&JsonValue, &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> + Send + Sync + 'static,
+{
+    ConditionRegistration::new(condition_constructor)
+        .with_schema(VariableCondition::schema(config))
+        .with_category(VARIABLES_CATEGORY)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::graph::condition::tests::condition_implementation::{NegativeCondition, PositiveCondition};
 
     use super::*;
 
-    fn create_positive_condition(_config: &JsonValue, _input_vars: &JsonValue) -> Box<dyn Condition<NodeContext>> {
-        Box::new(PositiveCondition {})
+    fn create_positive_condition(_config: &JsonValue, _input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(PositiveCondition {}))
     }
 
-    fn create_negative_condition(_config: &JsonValue, _input_vars: &JsonValue) -> Box<dyn Condition<NodeContext>> {
-        Box::new(NegativeCondition {})
+    fn create_negative_condition(_config: &JsonValue, _input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(NegativeCondition {}))
     }
 
     #[test]
@@ -50,16 +310,78 @@ mod tests {
 
         condition_registry.register_condition(
             "positive_condition",
-            create_positive_condition as fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>,
+            create_positive_condition,
         );
 
         condition_registry.register_condition(
             "negative_condition",
-            create_negative_condition as fn(&JsonValue, &JsonValue) -> Box<dyn Condition<NodeContext>>,
+            create_negative_condition,
         );
 
         let conditions = condition_registry.get_conditions();
 
         assert_eq!(conditions.len(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_register_builtin_conditions() {
+        let mut condition_registry = ConditionRegistry::new();
+
+        condition_registry.register_builtin_conditions();
+
+        let conditions = condition_registry.get_conditions();
+        assert_eq!(conditions.len(), 10);
+        assert!(conditions.contains_key("equals"));
+        assert!(conditions.contains_key("last_message"));
+    }
+
+    #[test]
+    fn test_builtin_constructors_reject_invalid_config() {
+        let mut condition_registry = ConditionRegistry::new();
+        condition_registry.register_builtin_conditions();
+
+        let result = condition_registry.get_conditions()["matches_regex"].get_factory().create(&serde_json::json!({"pattern": "("}), &serde_json::json!({}));
+
+        assert!(matches!(result, Err(ConfigError::InvalidField { field, .. }) if field == "pattern"));
+    }
+
+    #[test]
+    fn test_catalog() {
+        let mut condition_registry = ConditionRegistry::new();
+        condition_registry.register_builtin_conditions();
+        condition_registry.register(
+            "positive_condition",
+            ConditionRegistration::new(create_positive_condition)
+                .with_name("Always")
+                .with_description("Always holds")
+                .with_category("testing")
+                .with_version("2.1.0"),
+        );
+
+        let catalog = condition_registry.catalog();
+
+        assert_eq!(catalog.len(), 15);
+        assert!(catalog.windows(2).all(|pair| pair[0].component_type < pair[1].component_type));
+
+        let positive = catalog.iter().find(|entry| entry.component_type == "positive_condition").unwrap();
+        assert_eq!(positive.name, "Always");
+        assert_eq!(positive.category, "testing");
+        assert_eq!(positive.version, "2.1.0");
+
+        let expression = catalog.iter().find(|entry| entry.component_type == "expression").unwrap();
+        assert_eq!(expression.category, "logic");
+        assert_eq!(expression.json_schema["properties"]["config"]["required"], serde_json::json!(["expression"]));
+    }
+
+    #[test]
+    fn test_schema_of_every_catalog_entry() {
+        let mut condition_registry = ConditionRegistry::new();
+        condition_registry.register_builtin_conditions();
+
+        for entry in condition_registry.catalog() {
+            let schema = condition_registry.schema(&entry.component_type).unwrap();
+            assert_eq!(schema.to_json_schema(), entry.json_schema, "{}", entry.component_type);
+        }
+        assert!(condition_registry.schema("unknown").is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+
+use crate::graph::node::node_context::{NodeContext, Value};
+
+use super::{
+    lexer::parse_number,
+    parser::{CompareOperator, Expression},
+};
+
+// Coercion rules:
+// - truthiness (`&&`, `||`, `!` and the final result): false, null, missing variables, 0, ""
+//   and empty lists, maps, bytes or message lists are false, everything else is true
+// - `==` and `!=`: numbers of any kind compare numerically, a string that parses as a number
+//   equals that number, datetimes compare by instant (RFC 3339 strings included), missing
+//   variables equal null, otherwise values of different types are not equal
+// - `<`, `<=`, `>`, `>=`: numbers (or numeric strings) compare numerically, datetimes
+//   chronologically, two strings compare lexicographically, any other combination is false
+// - `-` negates numbers and numeric strings, anything else becomes null
+pub fn evaluate(expression: &Expression, context: &NodeContext) -> Value {
+    match expression {
+        Expression::Literal(value) => value.clone(),
+        Expression::Variable(path) => context.resolve_path(path).cloned().unwrap_or(Value::Null),
+        Expression::Not(inner) => Value::Boolean(!is_truthy(&evaluate(inner, context))),
+        Expression::Negate(inner) => match as_number(&evaluate(inner, context)) {
+            Some(Value::Integer(integer)) => integer.checked_neg().map_or(Value::Number(-(integer as f64)), Value::Integer),
+            Some(Value::Decimal(decimal)) => decimal.checked_neg().map_or(Value::Null, Value::Decimal),
+            Some(number) => number.as_f64().map_or(Value::Null, |number| Value::Number(-number)),
+            None => Value::Null,
+        },
+        Expression::And(operands) => {
+            Value::Boolean(operands.iter().all(|operand| is_truthy(&evaluate(operand, context))))
+        }
+        Expression::Or(operands) => {
+            Value::Boolean(operands.iter().any(|operand| is_truthy(&evaluate(operand, context))))
+        }
+        Expression::Compare(left, operator, right) => {
+            Value::Boolean(compare(&evaluate(left, context), *operator, &evaluate(right, context)))
+        }
+    }
+}
+
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Boolean(boolean) => *boolean,
+        Value::Null => false,
+        Value::Number(number) => *number != 0.0 && !number.is_nan(),
+        Value::Integer(integer) => *integer != 0,
+        Value::Decimal(decimal) => !decimal.is_zero(),
+        Value::DateTime(_) | Value::Attachment(_) => true,
+        Value::String(text) => !text.is_empty(),
+        Value::Bytes(bytes) => !bytes.is_empty(),
+        Value::List(items) => !items.is_empty(),
+        Value::Map(map) => !map.is_empty(),
+        Value::Messages(messages) => !messages.is_empty(),
+    }
+}
+
+// Numbers as they are, numeric strings as a number
+fn as_number(value: &Value) -> Option<Value> {
+    match value {
+        Value::String(text) => parse_number(text.trim()),
+        value if value.is_numeric() => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn compare(left: &Value, operator: CompareOperator, right: &Value) -> bool {
+    match operator {
+        CompareOperator::Equal => equals(left, right),
+        CompareOperator::NotEqual => !equals(left, right),
+        _ => {
+            let Some(ordering) = order(left, right) else {
+                return false;
+            };
+            match operator {
+                CompareOperator::Less => ordering == Ordering::Less,
+                CompareOperator::LessOrEqual => ordering != Ordering::Greater,
+                CompareOperator::Greater => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            }
+        }
+    }
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    if left.is_numeric() || right.is_numeric() {
+        return match (as_number(left), as_number(right)) {
+            (Some(left), Some(right)) => left.equals(&right),
+            _ => false,
+        };
+    }
+    left.equals(right)
+}
+
+fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (as_number(left), as_number(right)) {
+        (Some(left_number), Some(right_number)) if !matches!((left, right), (Value::String(_), Value::String(_))) => {
+            left_number.compare(&right_number)
+        }
+        _ => left.compare(right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::graph::condition::expression::parser::parse;
+
+    use super::*;
+
+    fn create_context() -> NodeContext {
+        let mut context = NodeContext::new();
+        context.variables.insert("ai_action.intent".to_string(), Value::String("refund".to_string()));
+        context.variables.insert("order".to_string(), Value::try_from(json!({"total": 150, "id": "42", "items": []})).unwrap());
+        context.variables.insert("escalated".to_string(), Value::Boolean(false));
+        context.variables.insert("amount".to_string(), Value::Decimal("19.99".parse().unwrap()));
+        context.variables.insert(
+            "appointment".to_string(),
+            Value::try_from(json!({"@datetime": "2025-03-01T10:00:00Z"})).unwrap(),
+        );
+        context
+    }
+
+    fn holds(source: &str) -> bool {
+        is_truthy(&evaluate(&parse(source).unwrap(), &create_context()))
+    }
+
+    #[test]
+    fn test_evaluate_example() {
+        assert!(holds(r#"ai_action.intent == "refund" && order.total > 100"#));
+        assert!(!holds(r#"ai_action.intent == "refund" && order.total > 200"#));
+        assert!(holds(r#"(ai_action.intent == "billing" || ai_action.intent == "refund") && !escalated"#));
+    }
+
+    #[test]
+    fn test_numeric_coercion() {
+        assert!(holds("order.id == 42"));
+        assert!(holds("order.id < 100"));
+        assert!(holds("-order.total == -150"));
+        assert!(!holds("ai_action.intent > 1"));
+        assert!(holds("amount < 20 && amount > 19.9 && amount != 19.9"));
+        assert!(holds("-amount < 0"));
+    }
+
+    #[test]
+    fn test_exact_integers_past_float_precision() {
+        let mut context = create_context();
+        context.variables.insert("order.id".to_string(), Value::Integer(9_007_199_254_740_993));
+        context.variables.insert("order.code".to_string(), Value::String("9007199254740993".to_string()));
+        let holds = |source: &str| is_truthy(&evaluate(&parse(source).unwrap(), &context));
+
+        assert!(holds("order.id == 9007199254740993"));
+        assert!(!holds("order.id == 9007199254740992"));
+        assert!(holds("order.id > 9007199254740992"));
+        assert!(holds("order.code == 9007199254740993"));
+        assert!(!holds("order.code == 9007199254740992"));
+    }
+
+    #[test]
+    fn test_datetime_comparisons() {
+        assert!(holds(r#"appointment > "2025-03-01T09:00:00Z""#));
+        assert!(holds(r#"appointment == "2025-03-01T11:00:00+01:00""#));
+        assert!(!holds("appointment > 100"));
+    }
+
+    #[test]
+    fn test_missing_variables_are_null() {
+        assert!(holds("missing == null"));
+        assert!(!holds("missing"));
+        assert!(!holds("missing > 0"));
+    }
+
+    #[test]
+    fn test_truthiness() {
+        assert!(!holds("order.items"));
+        assert!(holds("order"));
+        assert!(!holds("\"\""));
+        assert!(holds("\"a\" < \"b\""));
+        assert!(!holds("true == 1"));
+    }
+
+    #[test]
+    fn test_long_chains() {
+        assert!(holds(&vec!["order"; 50_000].join(" && ")));
+        assert!(!holds(&vec!["escalated"; 50_000].join(" || ")));
+    }
+}
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+
+use crate::graph::{condition::condition::Condition, node::node_context::NodeContext};
+
+use super::{
+    evaluator::{evaluate, is_truthy},
+    parser::{parse, Expression, ExpressionError},
+};
+
+pub const EXPRESSION_CONDITION_TYPE: &str = "expression";
+// Config key holding the expression source
+pub const EXPRESSION_CONFIG_KEY: &str = "expression";
+
+/// Condition holding when its boolean expression over `NodeContext` variables is truthy,
+/// e.g. `ai_action.intent == "refund" && order.total > 100`. The expression is parsed once.
+#[derive(Debug, Clone)]
+pub struct ExpressionCondition {
+    expression: Expression,
+}
+
+impl ExpressionCondition {
+    pub fn new(source: &str) -> Result<Self, ExpressionError> {
+        Ok(ExpressionCondition { expression: parse(source)? })
+    }
+
+    // config: { "expression": <string> }
+    pub fn from_config(config: &JsonValue) -> Result<Self, ExpressionError> {
+        let source = config
+            .get(EXPRESSION_CONFIG_KEY)
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| ExpressionError::new(0, "config.expression must be a string".to_string()))?;
+
+        Self::new(source)
+    }
+}
+
+#[async_trait]
+impl Condition<NodeContext> for ExpressionCondition {
+    async fn evaluate(&self, context: &NodeContext) -> bool {
+        is_truthy(&evaluate(&self.expression, context))
+    }
+
+    fn clone_box(&self) -> Box<dyn Condition<NodeContext>> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::graph::node::node_context::Value;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expression_condition() {
+        let condition = ExpressionCondition::from_config(&json!({"expression": "score >= 3"})).unwrap();
+
+        let mut context = NodeContext::new();
+        context.variables.insert("score".to_string(), Value::Number(3.0));
+
+        assert!(condition.evaluate(&context).await);
+        assert!(!condition.evaluate(&NodeContext::new()).await);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(ExpressionCondition::from_config(&json!({})).is_err());
+        assert_eq!(ExpressionCondition::from_config(&json!({"expression": "a &&"})).unwrap_err().position, 4);
+    }
+}
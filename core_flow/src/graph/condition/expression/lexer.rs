@@ -0,0 +1,208 @@
+use crate::graph::node::node_context::Value;
+
+use super::parser::ExpressionError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    // Integer, or decimal when it has a fraction or doesn't fit, so literals stay exact
+    Number(Value),
+    String(String),
+    // Variable path, e.g. `crm.customer.orders[0].id`
+    Path(String),
+    True,
+    False,
+    Null,
+    And,
+    Or,
+    Not,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    OpenParen,
+    CloseParen,
+}
+
+/// Token and the byte offset it starts at in the expression
+pub type PositionedToken = (Token, usize);
+
+pub fn tokenize(source: &str) -> Result<Vec<PositionedToken>, ExpressionError> {
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let (position, c) = chars[index];
+        let next = chars.get(index + 1).map(|(_, c)| *c);
+
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        let two_char_token = match (c, next) {
+            ('&', Some('&')) => Some(Token::And),
+            ('|', Some('|')) => Some(Token::Or),
+            ('=', Some('=')) => Some(Token::Equal),
+            ('!', Some('=')) => Some(Token::NotEqual),
+            ('<', Some('=')) => Some(Token::LessOrEqual),
+            ('>', Some('=')) => Some(Token::GreaterOrEqual),
+            _ => None,
+        };
+        if let Some(token) = two_char_token {
+            tokens.push((token, position));
+            index += 2;
+            continue;
+        }
+
+        let single_char_token = match c {
+            '!' => Some(Token::Not),
+            '-' => Some(Token::Minus),
+            '<' => Some(Token::Less),
+            '>' => Some(Token::Greater),
+            '(' => Some(Token::OpenParen),
+            ')' => Some(Token::CloseParen),
+            _ => None,
+        };
+        if let Some(token) = single_char_token {
+            tokens.push((token, position));
+            index += 1;
+            continue;
+        }
+
+        if c == '"' || c == '\'' {
+            let (text, consumed) = read_string(&chars[index..], c)?;
+            tokens.push((Token::String(text), position));
+            index += consumed;
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let end = chars[index..]
+                .iter()
+                .position(|(_, c)| !(c.is_ascii_digit() || *c == '.'))
+                .map_or(chars.len(), |offset| index + offset);
+            let text: String = chars[index..end].iter().map(|(_, c)| c).collect();
+            let number = parse_number(&text)
+                .ok_or_else(|| ExpressionError::new(position, format!("invalid number `{}`", text)))?;
+            tokens.push((Token::Number(number), position));
+            index = end;
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let end = chars[index..]
+                .iter()
+                .position(|(_, c)| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '[' | ']')))
+                .map_or(chars.len(), |offset| index + offset);
+            let text: String = chars[index..end].iter().map(|(_, c)| c).collect();
+            let token = match text.as_str() {
+                "true" => Token::True,
+                "false" => Token::False,
+                "null" => Token::Null,
+                _ => Token::Path(text),
+            };
+            tokens.push((token, position));
+            index = end;
+            continue;
+        }
+
+        return Err(ExpressionError::new(position, format!("unexpected character `{}`", c)));
+    }
+
+    Ok(tokens)
+}
+
+/// Exact value of a number: an integer, a decimal, or a float when neither can hold it
+pub fn parse_number(text: &str) -> Option<Value> {
+    if let Ok(integer) = text.parse::<i64>() {
+        return Some(Value::Integer(integer));
+    }
+    if let Ok(decimal) = text.parse() {
+        return Some(Value::Decimal(decimal));
+    }
+    text.parse().ok().map(Value::Number)
+}
+
+// Reads a quoted string starting at its opening quote, returns the text and the chars consumed
+fn read_string(chars: &[(usize, char)], quote: char) -> Result<(String, usize), ExpressionError> {
+    let start = chars[0].0;
+    let mut text = String::new();
+    let mut index = 1;
+
+    while index < chars.len() {
+        match chars[index].1 {
+            c if c == quote => return Ok((text, index + 1)),
+            '\\' => {
+                let escaped = chars
+                    .get(index + 1)
+                    .map(|(_, c)| *c)
+                    .ok_or_else(|| ExpressionError::new(start, "unterminated string".to_string()))?;
+                text.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    other => other,
+                });
+                index += 2;
+            }
+            c => {
+                text.push(c);
+                index += 1;
+            }
+        }
+    }
+
+    Err(ExpressionError::new(start, "unterminated string".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize(r#"ai_action.intent == "refund" && order.items[0].total >= 10.5"#).unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::Path("ai_action.intent".to_string()), 0),
+                (Token::Equal, 17),
+                (Token::String("refund".to_string()), 20),
+                (Token::And, 29),
+                (Token::Path("order.items[0].total".to_string()), 32),
+                (Token::GreaterOrEqual, 53),
+                (Token::Number(Value::Decimal("10.5".parse().unwrap())), 56),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_strings_with_escapes() {
+        let tokens = tokenize(r#"'it\'s' "a\"b""#).unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![(Token::String("it's".to_string()), 0), (Token::String("a\"b".to_string()), 8)]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_errors_report_position() {
+        assert_eq!(tokenize("a == #").unwrap_err().position, 5);
+        assert_eq!(tokenize("a == \"open").unwrap_err().position, 5);
+        assert_eq!(tokenize("1.2.3").unwrap_err().position, 0);
+    }
+
+    #[test]
+    fn test_numbers_stay_exact() {
+        assert_eq!(parse_number("9007199254740993"), Some(Value::Integer(9_007_199_254_740_993)));
+        assert_eq!(parse_number("99999999999999999999"), Some(Value::Decimal("99999999999999999999".parse().unwrap())));
+        assert_eq!(parse_number("0.1"), Some(Value::Decimal("0.1".parse().unwrap())));
+        assert_eq!(parse_number("1e3"), Some(Value::Number(1000.0)));
+        assert_eq!(parse_number("1.2.3"), None);
+    }
+}
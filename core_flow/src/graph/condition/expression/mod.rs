@@ -0,0 +1,4 @@
+pub mod evaluator;
+pub mod expression_condition;
+pub mod lexer;
+pub mod parser;
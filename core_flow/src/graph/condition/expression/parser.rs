@@ -0,0 +1,276 @@
+use std::{error::Error, fmt};
+
+use crate::graph::{node::node_context::Value, template::filter::to_text};
+
+use super::lexer::{tokenize, PositionedToken, Token};
+
+// Deepest nesting accepted, keeps evaluation of hostile expressions bounded
+const MAX_DEPTH: usize = 64;
+
+/// Syntax error in an expression, `position` is the byte offset it was found at
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl ExpressionError {
+    pub fn new(position: usize, message: String) -> Self {
+        ExpressionError { position, message }
+    }
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ExpressionError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOperator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Literal(Value),
+    Variable(String),
+    Not(Box<Expression>),
+    Negate(Box<Expression>),
+    // Chains such as `a && b && c` keep their operands flat, so a long chain is not a deep tree
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Compare(Box<Expression>, CompareOperator, Box<Expression>),
+}
+
+// Grammar, lowest precedence first:
+// or      := and ("||" and)*
+// and     := unary ("&&" unary)*
+// unary   := "!" unary | compare
+// compare := operand (("==" | "!=" | "<" | "<=" | ">" | ">=") operand)?
+// operand := "-" operand | number | string | true | false | null | path | "(" or ")"
+pub fn parse(source: &str) -> Result<Expression, ExpressionError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, index: 0, end: source.len(), depth: 0 };
+
+    let expression = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(expression),
+        Some((token, position)) => Err(ExpressionError::new(
+            *position,
+            format!("unexpected {}", describe(token)),
+        )),
+    }
+}
+
+struct Parser {
+    tokens: Vec<PositionedToken>,
+    index: usize,
+    // Position reported for errors at the end of the expression
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&PositionedToken> {
+        self.tokens.get(self.index)
+    }
+
+    fn next_if(&mut self, expected: &Token) -> bool {
+        if self.peek().is_some_and(|(token, _)| token == expected) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            let position = self.peek().map_or(self.end, |(_, position)| *position);
+            return Err(ExpressionError::new(position, "expression is nested too deeply".to_string()));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, ExpressionError> {
+        self.enter()?;
+        let mut operands = vec![self.parse_and()?];
+        while self.next_if(&Token::Or) {
+            operands.push(self.parse_and()?);
+        }
+        self.depth -= 1;
+        Ok(if operands.len() == 1 { operands.remove(0) } else { Expression::Or(operands) })
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, ExpressionError> {
+        let mut operands = vec![self.parse_unary()?];
+        while self.next_if(&Token::And) {
+            operands.push(self.parse_unary()?);
+        }
+        Ok(if operands.len() == 1 { operands.remove(0) } else { Expression::And(operands) })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, ExpressionError> {
+        if self.next_if(&Token::Not) {
+            self.enter()?;
+            let expression = Expression::Not(Box::new(self.parse_unary()?));
+            self.depth -= 1;
+            return Ok(expression);
+        }
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> Result<Expression, ExpressionError> {
+        let left = self.parse_operand()?;
+
+        let operator = match self.peek().map(|(token, _)| token) {
+            Some(Token::Equal) => CompareOperator::Equal,
+            Some(Token::NotEqual) => CompareOperator::NotEqual,
+            Some(Token::Less) => CompareOperator::Less,
+            Some(Token::LessOrEqual) => CompareOperator::LessOrEqual,
+            Some(Token::Greater) => CompareOperator::Greater,
+            Some(Token::GreaterOrEqual) => CompareOperator::GreaterOrEqual,
+            _ => return Ok(left),
+        };
+        self.index += 1;
+
+        let right = self.parse_operand()?;
+        Ok(Expression::Compare(Box::new(left), operator, Box::new(right)))
+    }
+
+    fn parse_operand(&mut self) -> Result<Expression, ExpressionError> {
+        let Some((token, position)) = self.tokens.get(self.index).cloned() else {
+            return Err(ExpressionError::new(self.end, "unexpected end of expression".to_string()));
+        };
+        self.index += 1;
+
+        match token {
+            Token::Number(number) => Ok(Expression::Literal(number)),
+            Token::String(text) => Ok(Expression::Literal(Value::String(text))),
+            Token::True => Ok(Expression::Literal(Value::Boolean(true))),
+            Token::False => Ok(Expression::Literal(Value::Boolean(false))),
+            Token::Null => Ok(Expression::Literal(Value::Null)),
+            Token::Path(path) => Ok(Expression::Variable(path)),
+            Token::Minus => {
+                self.enter()?;
+                let expression = Expression::Negate(Box::new(self.parse_operand()?));
+                self.depth -= 1;
+                Ok(expression)
+            }
+            Token::OpenParen => {
+                let expression = self.parse_or()?;
+                if !self.next_if(&Token::CloseParen) {
+                    let position = self.peek().map_or(self.end, |(_, position)| *position);
+                    return Err(ExpressionError::new(position, "expected `)`".to_string()));
+                }
+                Ok(expression)
+            }
+            token => Err(ExpressionError::new(position, format!("unexpected {}", describe(&token)))),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(number) => format!("number `{}`", to_text(number)),
+        Token::String(text) => format!("string \"{}\"", text),
+        Token::Path(path) => format!("variable `{}`", path),
+        Token::True => "`true`".to_string(),
+        Token::False => "`false`".to_string(),
+        Token::Null => "`null`".to_string(),
+        Token::And => "`&&`".to_string(),
+        Token::Or => "`||`".to_string(),
+        Token::Not => "`!`".to_string(),
+        Token::Minus => "`-`".to_string(),
+        Token::Equal => "`==`".to_string(),
+        Token::NotEqual => "`!=`".to_string(),
+        Token::Less => "`<`".to_string(),
+        Token::LessOrEqual => "`<=`".to_string(),
+        Token::Greater => "`>`".to_string(),
+        Token::GreaterOrEqual => "`>=`".to_string(),
+        Token::OpenParen => "`(`".to_string(),
+        Token::CloseParen => "`)`".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(path: &str) -> Box<Expression> {
+        Box::new(Expression::Variable(path.to_string()))
+    }
+
+    fn number(number: i64) -> Box<Expression> {
+        Box::new(Expression::Literal(Value::Integer(number)))
+    }
+
+    #[test]
+    fn test_precedence() {
+        let expression = parse("a || b && !c").unwrap();
+
+        assert_eq!(
+            expression,
+            Expression::Or(vec![
+                *variable("a"),
+                Expression::And(vec![*variable("b"), Expression::Not(variable("c"))]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_comparison_and_parentheses() {
+        let expression = parse("(order.total > 100) && count != -1").unwrap();
+
+        assert_eq!(
+            expression,
+            Expression::And(vec![
+                Expression::Compare(variable("order.total"), CompareOperator::Greater, number(100)),
+                Expression::Compare(variable("count"), CompareOperator::NotEqual, Box::new(Expression::Negate(number(1)))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors_report_position() {
+        let error = parse("intent == ").unwrap_err();
+        assert_eq!(error.position, 10);
+        assert_eq!(error.message, "unexpected end of expression");
+
+        let error = parse("(a && b").unwrap_err();
+        assert_eq!(error.position, 7);
+
+        let error = parse("a == b c").unwrap_err();
+        assert_eq!(error.position, 7);
+        assert_eq!(error.message, "unexpected variable `c`");
+
+        let error = parse("a == == b").unwrap_err();
+        assert_eq!(error.position, 5);
+    }
+
+    #[test]
+    fn test_rejects_deep_nesting() {
+        let source = format!("{}a{}", "(".repeat(100), ")".repeat(100));
+
+        assert!(parse(&source).is_err());
+    }
+
+    #[test]
+    fn test_long_chains_stay_flat() {
+        let source = vec!["a"; 50_000].join(" && ");
+
+        match parse(&source).unwrap() {
+            Expression::And(operands) => assert_eq!(operands.len(), 50_000),
+            expression => panic!("expected a flat chain, got {:?}", expression),
+        }
+    }
+}
@@ -1,5 +1,8 @@
 pub mod condition;
+pub mod composite_condition;
+pub mod expression;
 pub mod tests {
     pub mod condition_implementation;
 }
-pub mod condition_registry;
\ No newline at end of file
+pub mod condition_registry;
+pub mod variable_condition;
\ No newline at end of file
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::graph::{condition::condition::Condition, node::node_context::NodeContext};
+use crate::graph::{condition::condition::Condition, config_error::ConfigError, node::node_context::NodeContext};
 
 pub struct PositiveCondition;
 
@@ -14,8 +14,8 @@ impl Condition<NodeContext> for PositiveCondition {
 }
 
 impl PositiveCondition {
-    pub fn create_positive_condition(_: &serde_json::Value, _input_vars: &serde_json::Value) -> Box<dyn Condition<NodeContext>> {
-        Box::new(PositiveCondition)
+    pub fn create_positive_condition(_: &serde_json::Value, _input_vars: &serde_json::Value) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(PositiveCondition))
     }
 }
 
@@ -32,8 +32,8 @@ impl Condition<NodeContext> for NegativeCondition {
 }
 
 impl NegativeCondition {
-    pub fn create_negative_condition(_: &serde_json::Value, _input_vars: &serde_json::Value) -> Box<dyn Condition<NodeContext>> {
-        Box::new(NegativeCondition)
+    pub fn create_negative_condition(_: &serde_json::Value, _input_vars: &serde_json::Value) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(NegativeCondition))
     }
 }
 
@@ -0,0 +1,383 @@
+use std::cmp::Ordering;
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::{json, Value as JsonValue};
+
+use crate::{
+    graph::{
+        action::utils::vars_parser::parse_input_vars,
+        condition::condition::Condition,
+        config_error::{config_string, ConfigError},
+        config_schema::{ComponentSchema, ConfigSchema, FieldType},
+        node::node_context::{NodeContext, Value},
+    },
+};
+
+pub const EQUALS_CONDITION_TYPE: &str = "equals";
+pub const NOT_EQUALS_CONDITION_TYPE: &str = "not_equals";
+pub const IN_RANGE_CONDITION_TYPE: &str = "in_range";
+pub const CONTAINS_CONDITION_TYPE: &str = "contains";
+pub const STARTS_WITH_CONDITION_TYPE: &str = "starts_with";
+pub const MATCHES_REGEX_CONDITION_TYPE: &str = "matches_regex";
+pub const EXISTS_CONDITION_TYPE: &str = "exists";
+pub const IS_NULL_CONDITION_TYPE: &str = "is_null";
+pub const LIST_CONTAINS_CONDITION_TYPE: &str = "list_contains";
+pub const LAST_MESSAGE_CONDITION_TYPE: &str = "last_message";
+
+// Input var holding the variable a condition looks at
+const VALUE_INPUT_VAR: &str = "value";
+
+#[derive(Debug, Clone)]
+enum Comparison {
+    Equals(Value),
+    NotEquals(Value),
+    // Inclusive numeric or datetime bounds, a missing bound is unbounded
+    InRange { min: Option<Value>, max: Option<Value> },
+    Contains(String),
+    StartsWith(String),
+    MatchesRegex(Regex),
+    Exists,
+    IsNull,
+    ListContains(Value),
+}
+
+impl Comparison {
+    fn holds(&self, variable: Option<&Value>) -> bool {
+        match (self, variable) {
+            (Comparison::Exists, variable) => variable.is_some(),
+            (Comparison::IsNull, variable) => matches!(variable, None | Some(Value::Null)),
+            // A missing variable compares as null
+            (Comparison::Equals(expected), variable) => variable.unwrap_or(&Value::Null).equals(expected),
+            (Comparison::NotEquals(expected), variable) => !variable.unwrap_or(&Value::Null).equals(expected),
+            (Comparison::InRange { min, max }, Some(value)) if value.is_numeric() || matches!(value, Value::DateTime(_)) => {
+                min.as_ref().is_none_or(|min| matches!(value.compare(min), Some(Ordering::Greater | Ordering::Equal)))
+                    && max.as_ref().is_none_or(|max| matches!(value.compare(max), Some(Ordering::Less | Ordering::Equal)))
+            }
+            (Comparison::Contains(expected), Some(Value::String(text))) => text.contains(expected.as_str()),
+            (Comparison::StartsWith(expected), Some(Value::String(text))) => text.starts_with(expected.as_str()),
+            (Comparison::MatchesRegex(regex), Some(Value::String(text))) => regex.is_match(text),
+            (Comparison::ListContains(expected), Some(Value::List(items))) => items.iter().any(|item| item.equals(expected)),
+            _ => false,
+        }
+    }
+}
+
+/// Compares a `NodeContext` variable, given by the "value" input var, against its config
+#[derive(Debug, Clone)]
+pub struct VariableCondition {
+    input_vars: JsonValue,
+    comparison: Comparison,
+}
+
+impl VariableCondition {
+    fn new(input_vars: &JsonValue, comparison: Comparison) -> Self {
+        let input_vars = if input_vars.is_object() { input_vars.clone() } else { json!({}) };
+        VariableCondition { input_vars, comparison }
+    }
+
+    /// Schema of a variable condition, only the config differs between comparisons
+    pub fn schema(config: ConfigSchema) -> ComponentSchema {
+        ComponentSchema::new()
+            .with_config(config)
+            .with_input_var(VALUE_INPUT_VAR, "Variable the condition looks at")
+    }
+
+    // config: { "value": <any json> }
+    pub fn create_equals_condition(config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(Self::new(input_vars, Comparison::Equals(config_value(config)?))))
+    }
+
+    // config: { "value": <any json> }
+    pub fn create_not_equals_condition(config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(Self::new(input_vars, Comparison::NotEquals(config_value(config)?))))
+    }
+
+    // config: { "min": <number | RFC 3339 datetime>, "max": <number | RFC 3339 datetime> }, both optional
+    pub fn create_in_range_condition(config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        let min = config_bound(config, "min")?;
+        let max = config_bound(config, "max")?;
+        Ok(Box::new(Self::new(input_vars, Comparison::InRange { min, max })))
+    }
+
+    // config: { "value": <string> }
+    pub fn create_contains_condition(config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(Self::new(input_vars, Comparison::Contains(config_string(config, "value")?))))
+    }
+
+    // config: { "value": <string> }
+    pub fn create_starts_with_condition(config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(Self::new(input_vars, Comparison::StartsWith(config_string(config, "value")?))))
+    }
+
+    // config: { "pattern": <regex> }
+    pub fn create_matches_regex_condition(config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        let regex = config_regex(&config_string(config, "pattern")?, "pattern")?;
+        Ok(Box::new(Self::new(input_vars, Comparison::MatchesRegex(regex))))
+    }
+
+    pub fn create_exists_condition(_config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(Self::new(input_vars, Comparison::Exists)))
+    }
+
+    pub fn create_is_null_condition(_config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(Self::new(input_vars, Comparison::IsNull)))
+    }
+
+    // config: { "value": <any json> }
+    pub fn create_list_contains_condition(config: &JsonValue, input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        Ok(Box::new(Self::new(input_vars, Comparison::ListContains(config_value(config)?))))
+    }
+}
+
+#[async_trait]
+impl Condition<NodeContext> for VariableCondition {
+    async fn evaluate(&self, context: &NodeContext) -> bool {
+        let variables = parse_input_vars(&self.input_vars, context).unwrap_or_default();
+        self.comparison.holds(variables.get(VALUE_INPUT_VAR))
+    }
+
+    fn clone_box(&self) -> Box<dyn Condition<NodeContext>> {
+        Box::new(self.clone())
+    }
+}
+
+/// Compares the text (or media caption) of the latest user message, the `trigger_message` of the current trigger
+#[derive(Debug, Clone)]
+pub struct LastMessageCondition {
+    comparison: Comparison,
+    case_sensitive: bool,
+}
+
+impl LastMessageCondition {
+    pub fn schema() -> ComponentSchema {
+        ComponentSchema::new().with_config(
+            ConfigSchema::new()
+                .with_optional_field("operator", FieldType::String, "How the message is compared, equals by default")
+                .with_allowed_values(vec![json!("equals"), json!("contains"), json!("starts_with"), json!("matches_regex")])
+                .with_field("value", FieldType::String, "Text or pattern the message is compared with")
+                .with_optional_field("case_sensitive", FieldType::Boolean, "Defaults to true"),
+        )
+    }
+
+    // config: { "operator": "equals" | "contains" | "starts_with" | "matches_regex", "value": <string>, "case_sensitive": <bool> }
+    pub fn create_last_message_condition(config: &JsonValue, _input_vars: &JsonValue) -> Result<Box<dyn Condition<NodeContext>>, ConfigError> {
+        let case_sensitive = config.get("case_sensitive").and_then(JsonValue::as_bool).unwrap_or(true);
+        let expected = config_string(config, "value")?;
+        let expected = if case_sensitive { expected } else { expected.to_lowercase() };
+
+        let comparison = match config.get("operator").and_then(JsonValue::as_str).unwrap_or("equals") {
+            "equals" => Comparison::Equals(Value::String(expected)),
+            "contains" => Comparison::Contains(expected),
+            "starts_with" => Comparison::StartsWith(expected),
+            "matches_regex" => {
+                let pattern = if case_sensitive { expected } else { format!("(?i){}", expected) };
+                Comparison::MatchesRegex(config_regex(&pattern, "value")?)
+            }
+            operator => return Err(ConfigError::invalid_field("operator", format!("unknown operator {}", operator))),
+        };
+
+        Ok(Box::new(LastMessageCondition { comparison, case_sensitive }))
+    }
+}
+
+#[async_trait]
+impl Condition<NodeContext> for LastMessageCondition {
+    async fn evaluate(&self, context: &NodeContext) -> bool {
+        let last_text = context
+            .transient
+            .get("trigger_message")
+            .and_then(Value::as_messages)
+            .and_then(|messages| messages.last())
+            .and_then(|message| message.content.text())
+            .map(str::to_string);
+
+        let last_text = match last_text {
+            // Regexes handle case insensitivity themselves
+            Some(text) if !self.case_sensitive && !matches!(self.comparison, Comparison::MatchesRegex(_)) => {
+                Value::String(text.to_lowercase())
+            }
+            Some(text) => Value::String(text),
+            None => return false,
+        };
+
+        self.comparison.holds(Some(&last_text))
+    }
+
+    fn clone_box(&self) -> Box<dyn Condition<NodeContext>> {
+        Box::new(self.clone())
+    }
+}
+
+fn config_value(config: &JsonValue) -> Result<Value, ConfigError> {
+    match config.get("value") {
+        Some(value) => Value::try_from(value.clone()).map_err(|error| ConfigError::invalid_field("value", error)),
+        None => Ok(Value::Null),
+    }
+}
+
+fn config_bound(config: &JsonValue, key: &str) -> Result<Option<Value>, ConfigError> {
+    let Some(bound) = config.get(key).filter(|bound| !bound.is_null()) else {
+        return Ok(None);
+    };
+    let bound = Value::try_from(bound.clone()).map_err(|error| ConfigError::invalid_field(key, error))?;
+    match bound.as_datetime() {
+        Some(datetime) => Ok(Some(Value::DateTime(datetime))),
+        None if bound.is_numeric() => Ok(Some(bound)),
+        None => Err(ConfigError::invalid_field(key, "expected a number or an RFC 3339 datetime")),
+    }
+}
+
+fn config_regex(pattern: &str, key: &str) -> Result<Regex, ConfigError> {
+    Regex::new(pattern).map_err(|error| ConfigError::invalid_field(key, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::flow::conversation::Message;
+
+    use super::*;
+
+    fn context_with(name: &str, value: Value) -> NodeContext {
+        let mut context = NodeContext::new();
+        context.variables.insert(name.to_string(), value);
+        context
+    }
+
+    fn input_vars() -> JsonValue {
+        json!({"value": "ai_action.intent"})
+    }
+
+    async fn holds(condition: Box<dyn Condition<NodeContext>>, value: Value) -> bool {
+        condition.evaluate(&context_with("ai_action.intent", value)).await
+    }
+
+    #[tokio::test]
+    async fn test_equals() {
+        let condition = || VariableCondition::create_equals_condition(&json!({"value": "refund"}), &input_vars()).unwrap();
+
+        assert!(holds(condition(), Value::String("refund".to_string())).await);
+        assert!(!holds(condition(), Value::String("billing".to_string())).await);
+        assert!(!condition().evaluate(&NodeContext::new()).await);
+    }
+
+    #[tokio::test]
+    async fn test_not_equals() {
+        let condition = || VariableCondition::create_not_equals_condition(&json!({"value": 3}), &input_vars()).unwrap();
+
+        assert!(holds(condition(), Value::Number(4.0)).await);
+        assert!(!holds(condition(), Value::Number(3.0)).await);
+        assert!(!holds(condition(), Value::Integer(3)).await);
+        assert!(condition().evaluate(&NodeContext::new()).await);
+    }
+
+    #[tokio::test]
+    async fn test_in_range() {
+        let condition = || VariableCondition::create_in_range_condition(&json!({"min": 10, "max": 20}), &input_vars()).unwrap();
+        let open_condition = VariableCondition::create_in_range_condition(&json!({"min": 10}), &input_vars()).unwrap();
+
+        assert!(holds(condition(), Value::Number(10.0)).await);
+        assert!(holds(condition(), Value::Number(20.0)).await);
+        assert!(!holds(condition(), Value::Number(20.5)).await);
+        assert!(!holds(condition(), Value::String("15".to_string())).await);
+        assert!(holds(open_condition, Value::Number(1000.0)).await);
+        assert!(holds(condition(), Value::Integer(15)).await);
+        assert!(holds(condition(), Value::Decimal("19.99".parse().unwrap())).await);
+    }
+
+    #[tokio::test]
+    async fn test_in_range_of_datetimes() {
+        let condition = VariableCondition::create_in_range_condition(
+            &json!({"min": "2025-03-01T09:00:00Z", "max": "2025-03-01T17:00:00Z"}),
+            &input_vars(),
+        ).unwrap();
+        let datetime = |text: &str| Value::DateTime(chrono::DateTime::parse_from_rfc3339(text).unwrap().to_utc());
+
+        assert!(holds(condition.clone_box(), datetime("2025-03-01T12:00:00+01:00")).await);
+        assert!(!holds(condition, datetime("2025-03-01T18:00:00Z")).await);
+        assert!(VariableCondition::create_in_range_condition(&json!({"min": "soon"}), &input_vars()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contains_and_starts_with() {
+        let contains = VariableCondition::create_contains_condition(&json!({"value": "fund"}), &input_vars()).unwrap();
+        let starts_with = VariableCondition::create_starts_with_condition(&json!({"value": "re"}), &input_vars()).unwrap();
+
+        assert!(holds(contains, Value::String("refund".to_string())).await);
+        assert!(holds(starts_with.clone_box(), Value::String("refund".to_string())).await);
+        assert!(!holds(starts_with, Value::String("billing".to_string())).await);
+    }
+
+    #[tokio::test]
+    async fn test_matches_regex() {
+        let condition = || VariableCondition::create_matches_regex_condition(&json!({"pattern": "^ord-[0-9]+$"}), &input_vars()).unwrap();
+
+        assert!(holds(condition(), Value::String("ord-42".to_string())).await);
+        assert!(!holds(condition(), Value::String("ord-x".to_string())).await);
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        assert!(VariableCondition::create_matches_regex_condition(&json!({"pattern": "("}), &input_vars()).is_err());
+        assert!(matches!(
+            VariableCondition::create_contains_condition(&json!({}), &input_vars()),
+            Err(ConfigError::MissingField(field)) if field == "value"
+        ));
+        assert!(LastMessageCondition::create_last_message_condition(&json!({"operator": "like", "value": "a"}), &JsonValue::Null).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exists_and_is_null() {
+        let exists = || VariableCondition::create_exists_condition(&JsonValue::Null, &input_vars()).unwrap();
+        let is_null = || VariableCondition::create_is_null_condition(&JsonValue::Null, &input_vars()).unwrap();
+
+        assert!(holds(exists(), Value::Null).await);
+        assert!(!exists().evaluate(&NodeContext::new()).await);
+        assert!(holds(is_null(), Value::Null).await);
+        assert!(is_null().evaluate(&NodeContext::new()).await);
+        assert!(!holds(is_null(), Value::Boolean(false)).await);
+    }
+
+    #[tokio::test]
+    async fn test_list_contains() {
+        let condition = || VariableCondition::create_list_contains_condition(&json!({"value": "vip"}), &input_vars()).unwrap();
+
+        assert!(holds(condition(), Value::List(vec![Value::String("vip".to_string())])).await);
+        assert!(!holds(condition(), Value::List(vec![])).await);
+        assert!(!holds(condition(), Value::Map(HashMap::new())).await);
+    }
+
+    #[tokio::test]
+    async fn test_last_message() {
+        let mut context = NodeContext::new();
+        context.transient.insert(
+            "trigger_message".to_string(),
+            Value::Messages(vec![Message::new("user".to_string(), "I want a REFUND".to_string(), "ai".to_string())]),
+        );
+
+        let contains = LastMessageCondition::create_last_message_condition(
+            &json!({"operator": "contains", "value": "refund", "case_sensitive": false}),
+            &JsonValue::Null,
+        ).unwrap();
+        let case_sensitive = LastMessageCondition::create_last_message_condition(
+            &json!({"operator": "contains", "value": "refund"}),
+            &JsonValue::Null,
+        ).unwrap();
+        let regex = LastMessageCondition::create_last_message_condition(
+            &json!({"operator": "matches_regex", "value": "want an? refund", "case_sensitive": false}),
+            &JsonValue::Null,
+        ).unwrap();
+        let equals = LastMessageCondition::create_last_message_condition(
+            &json!({"value": "I want a REFUND"}),
+            &JsonValue::Null,
+        ).unwrap();
+
+        assert!(contains.evaluate(&context).await);
+        assert!(!case_sensitive.evaluate(&context).await);
+        assert!(regex.evaluate(&context).await);
+        assert!(equals.evaluate(&context).await);
+        assert!(!equals.evaluate(&NodeContext::new()).await);
+    }
+}
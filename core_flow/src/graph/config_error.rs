@@ -0,0 +1,69 @@
+use std::{error::Error, fmt};
+
+use serde_json::Value as JsonValue;
+
+use crate::graph::flow_load_error::{FlowLoadError, FlowLoadErrorKind};
+
+/// Error returned by action and condition constructors when their config can't be used.
+/// Fields are named relative to the `config` object of the action or condition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    MissingField(String),
+    InvalidField { field: String, reason: String },
+    // The config is valid but a resource the constructor needs could not be set up
+    ConstructionFailed(String),
+}
+
+impl ConfigError {
+    pub fn invalid_field(field: &str, reason: impl fmt::Display) -> Self {
+        ConfigError::InvalidField { field: field.to_string(), reason: reason.to_string() }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::MissingField(field) => write!(f, "Config field {} is required", field),
+            ConfigError::InvalidField { field, reason } => write!(f, "Config field {} is invalid: {}", field, reason),
+            ConfigError::ConstructionFailed(reason) => write!(f, "Construction failed: {}", reason),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Required string field of a config
+pub fn config_string(config: &JsonValue, key: &str) -> Result<String, ConfigError> {
+    match config.get(key) {
+        Some(JsonValue::String(text)) => Ok(text.clone()),
+        Some(value) => Err(ConfigError::invalid_field(key, format!("expected a string, found {}", value))),
+        None => Err(ConfigError::MissingField(key.to_string())),
+    }
+}
+
+impl From<ConfigError> for FlowLoadError {
+    fn from(error: ConfigError) -> Self {
+        let (kind, path) = match &error {
+            ConfigError::MissingField(field) => (FlowLoadErrorKind::MissingField, format!("config.{}", field)),
+            ConfigError::InvalidField { field, .. } => (FlowLoadErrorKind::InvalidFieldValue, format!("config.{}", field)),
+            ConfigError::ConstructionFailed(_) => (FlowLoadErrorKind::ConstructionFailed, "config".to_string()),
+        };
+        FlowLoadError::new(kind, error.to_string()).with_path(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_flow_load_error() {
+        let error = FlowLoadError::from(ConfigError::MissingField("model".to_string()));
+        assert_eq!(error.kind(), FlowLoadErrorKind::MissingField);
+        assert_eq!(error.path(), "config.model");
+
+        let error = FlowLoadError::from(ConfigError::invalid_field("pattern", "unclosed group"));
+        assert_eq!(error.kind(), FlowLoadErrorKind::InvalidFieldValue);
+        assert_eq!(error.reason(), "Config field pattern is invalid: unclosed group");
+    }
+}
@@ -0,0 +1,363 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::graph::flow_load_error::{FlowLoadError, FlowLoadErrorKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Object,
+    Array,
+    // Any json value, the field is only checked for presence
+    Any,
+}
+
+impl FieldType {
+    fn matches(&self, value: &JsonValue) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Number => value.is_number(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Object => value.is_object(),
+            FieldType::Array => value.is_array(),
+            FieldType::Any => true,
+        }
+    }
+
+    fn json_schema_type(&self) -> Option<&'static str> {
+        match self {
+            FieldType::String => Some("string"),
+            FieldType::Number => Some("number"),
+            FieldType::Integer => Some("integer"),
+            FieldType::Boolean => Some("boolean"),
+            FieldType::Object => Some("object"),
+            FieldType::Array => Some("array"),
+            FieldType::Any => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldSchema {
+    pub name: String,
+    pub field_type: FieldType,
+    pub required: bool,
+    pub description: String,
+    // Closed set of accepted values, empty accepts any value of the type
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_values: Vec<JsonValue>,
+}
+
+/// Fields of a json object, used for the `config` of actions and conditions.
+/// Keys that are not declared are accepted, so `id` and `name` never need declaring.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConfigSchema {
+    fields: Vec<FieldSchema>,
+}
+
+impl ConfigSchema {
+    pub fn new() -> Self {
+        ConfigSchema { fields: Vec::new() }
+    }
+
+    pub fn with_field(self, name: &str, field_type: FieldType, description: &str) -> Self {
+        self.with(name, field_type, true, description)
+    }
+
+    pub fn with_optional_field(self, name: &str, field_type: FieldType, description: &str) -> Self {
+        self.with(name, field_type, false, description)
+    }
+
+    /// Restricts the last declared field to the given values
+    pub fn with_allowed_values(mut self, allowed_values: Vec<JsonValue>) -> Self {
+        if let Some(field) = self.fields.last_mut() {
+            field.allowed_values = allowed_values;
+        }
+        self
+    }
+
+    fn with(mut self, name: &str, field_type: FieldType, required: bool, description: &str) -> Self {
+        self.fields.push(FieldSchema {
+            name: name.to_string(),
+            field_type,
+            required,
+            description: description.to_string(),
+            allowed_values: Vec::new(),
+        });
+        self
+    }
+
+    pub fn get_fields(&self) -> &[FieldSchema] {
+        &self.fields
+    }
+
+    /// Checks the object against the declared fields, paths in the error are relative to it
+    pub fn validate(&self, value: &JsonValue) -> Result<(), FlowLoadError> {
+        let empty = Map::new();
+        let object = match value {
+            JsonValue::Object(object) => object,
+            // Configs may be left out when nothing in them is required
+            JsonValue::Null => &empty,
+            value => {
+                return Err(FlowLoadError::new(
+                    FlowLoadErrorKind::InvalidFieldType,
+                    format!("Expected an object, found {}", value),
+                ));
+            }
+        };
+
+        for field in &self.fields {
+            match object.get(&field.name) {
+                None | Some(JsonValue::Null) if field.required => {
+                    return Err(FlowLoadError::new(
+                        FlowLoadErrorKind::MissingField,
+                        format!("Field {} is required", field.name),
+                    )
+                    .with_path(&field.name));
+                }
+                None | Some(JsonValue::Null) => {}
+                Some(value) if !field.field_type.matches(value) => {
+                    return Err(FlowLoadError::new(
+                        FlowLoadErrorKind::InvalidFieldType,
+                        format!("Field {} must be of type {:?}, found {}", field.name, field.field_type, value),
+                    )
+                    .with_path(&field.name));
+                }
+                Some(value) if !field.allowed_values.is_empty() && !field.allowed_values.contains(value) => {
+                    return Err(FlowLoadError::new(
+                        FlowLoadErrorKind::InvalidFieldValue,
+                        format!("Field {} must be one of {}", field.name, JsonValue::from(field.allowed_values.clone())),
+                    )
+                    .with_path(&field.name));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// JSON Schema (draft 2020-12) of the object, for editors rendering config forms
+    pub fn to_json_schema(&self) -> JsonValue {
+        let mut properties = Map::new();
+        for field in &self.fields {
+            let mut property = Map::new();
+            if let Some(json_type) = field.field_type.json_schema_type() {
+                property.insert("type".to_string(), json!(json_type));
+            }
+            property.insert("description".to_string(), json!(field.description));
+            if !field.allowed_values.is_empty() {
+                property.insert("enum".to_string(), json!(field.allowed_values));
+            }
+            properties.insert(field.name.clone(), JsonValue::Object(property));
+        }
+
+        let required: Vec<&str> = self
+            .fields
+            .iter()
+            .filter(|field| field.required)
+            .map(|field| field.name.as_str())
+            .collect();
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
+/// A variable an action or condition reads from, or writes to, the node context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VarSchema {
+    pub name: String,
+    pub required: bool,
+    pub description: String,
+}
+
+/// Contract of a registered action or condition: its config and the variables it reads and writes.
+/// Conditions never write variables, their `output_vars` stay empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ComponentSchema {
+    pub config: ConfigSchema,
+    pub input_vars: Vec<VarSchema>,
+    pub output_vars: Vec<VarSchema>,
+}
+
+impl ComponentSchema {
+    pub fn new() -> Self {
+        ComponentSchema::default()
+    }
+
+    pub fn with_config(mut self, config: ConfigSchema) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_input_var(mut self, name: &str, description: &str) -> Self {
+        self.input_vars.push(var_schema(name, true, description));
+        self
+    }
+
+    pub fn with_optional_input_var(mut self, name: &str, description: &str) -> Self {
+        self.input_vars.push(var_schema(name, false, description));
+        self
+    }
+
+    pub fn with_output_var(mut self, name: &str, description: &str) -> Self {
+        self.output_vars.push(var_schema(name, false, description));
+        self
+    }
+
+    /// Validates the `config`, `input_vars` and `output_vars` of an action or condition json.
+    /// Output vars are only checked when the schema declares some.
+    pub fn validate(
+        &self,
+        config: &JsonValue,
+        input_vars: &JsonValue,
+        output_vars: &JsonValue,
+    ) -> Result<(), FlowLoadError> {
+        self.config
+            .validate(config)
+            .map_err(|error| error.with_path_prefix("config"))?;
+
+        for input_var in self.input_vars.iter().filter(|input_var| input_var.required) {
+            if input_vars.get(&input_var.name).is_none() {
+                return Err(FlowLoadError::new(
+                    FlowLoadErrorKind::MissingField,
+                    format!("Input var {} is required", input_var.name),
+                )
+                .with_path(&format!("input_vars.{}", input_var.name)));
+            }
+        }
+
+        if self.output_vars.is_empty() {
+            return Ok(());
+        }
+        for (index, output_var) in output_vars.as_array().into_iter().flatten().enumerate() {
+            let declared = output_var
+                .as_str()
+                .map(|name| name.strip_suffix('?').unwrap_or(name))
+                .is_some_and(|name| self.output_vars.iter().any(|var| var.name == name));
+            if !declared {
+                return Err(FlowLoadError::new(
+                    FlowLoadErrorKind::InvalidFieldValue,
+                    format!("Output var {} is not produced by this type", output_var),
+                )
+                .with_path(&format!("output_vars[{}]", index)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// JSON Schema of the `config`, `input_vars` and `output_vars` of the component
+    pub fn to_json_schema(&self) -> JsonValue {
+        let input_vars: Map<String, JsonValue> = self
+            .input_vars
+            .iter()
+            .map(|var| {
+                // A path into the node context, or an object with a path and a default or a literal
+                let mapping = json!({ "type": ["string", "object"], "description": var.description });
+                (var.name.clone(), mapping)
+            })
+            .collect();
+        let required_input_vars: Vec<&str> = self
+            .input_vars
+            .iter()
+            .filter(|var| var.required)
+            .map(|var| var.name.as_str())
+            .collect();
+        let output_var_names: Vec<&str> = self.output_vars.iter().map(|var| var.name.as_str()).collect();
+
+        let mut output_vars = json!({ "type": "array", "items": { "type": "string" } });
+        if !output_var_names.is_empty() {
+            output_vars["items"]["enum"] = json!(output_var_names);
+        }
+
+        json!({
+            "type": "object",
+            "properties": {
+                "config": self.config.to_json_schema(),
+                "input_vars": {
+                    "type": "object",
+                    "properties": input_vars,
+                    "required": required_input_vars,
+                },
+                "output_vars": output_vars,
+            },
+        })
+    }
+}
+
+fn var_schema(name: &str, required: bool, description: &str) -> VarSchema {
+    VarSchema { name: name.to_string(), required, description: description.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ai_schema() -> ComponentSchema {
+        ComponentSchema::new()
+            .with_config(
+                ConfigSchema::new()
+                    .with_field("model", FieldType::String, "Model name")
+                    .with_optional_field("temperature", FieldType::Number, "Sampling temperature")
+                    .with_optional_field("mode", FieldType::String, "Answer mode")
+                    .with_allowed_values(vec![json!("chat"), json!("json")]),
+            )
+            .with_input_var("messages", "Conversation so far")
+            .with_output_var("messages", "Messages with the reply appended")
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_json() {
+        let result = ai_schema().validate(
+            &json!({"id": "ai", "name": "ai", "model": "gemini", "mode": "chat"}),
+            &json!({"messages": "messages"}),
+            &json!(["messages"]),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_reports_the_offending_field() {
+        let schema = ai_schema();
+        let input_vars = json!({"messages": "messages"});
+
+        let error = schema.validate(&json!({}), &input_vars, &json!([])).unwrap_err();
+        assert_eq!(error.kind(), FlowLoadErrorKind::MissingField);
+        assert_eq!(error.path(), "config.model");
+
+        let error = schema.validate(&json!({"model": "gemini", "temperature": "hot"}), &input_vars, &json!([])).unwrap_err();
+        assert_eq!(error.kind(), FlowLoadErrorKind::InvalidFieldType);
+        assert_eq!(error.path(), "config.temperature");
+
+        let error = schema.validate(&json!({"model": "gemini", "mode": "poem"}), &input_vars, &json!([])).unwrap_err();
+        assert_eq!(error.kind(), FlowLoadErrorKind::InvalidFieldValue);
+
+        let error = schema.validate(&json!({"model": "gemini"}), &json!({}), &json!([])).unwrap_err();
+        assert_eq!(error.path(), "input_vars.messages");
+
+        let error = schema.validate(&json!({"model": "gemini"}), &input_vars, &json!(["messages", "summary"])).unwrap_err();
+        assert_eq!(error.path(), "output_vars[1]");
+    }
+
+    #[test]
+    fn test_to_json_schema() {
+        let schema = ai_schema().to_json_schema();
+
+        assert_eq!(schema["properties"]["config"]["required"], json!(["model"]));
+        assert_eq!(schema["properties"]["config"]["properties"]["model"]["type"], json!("string"));
+        assert_eq!(schema["properties"]["config"]["properties"]["mode"]["enum"], json!(["chat", "json"]));
+        assert_eq!(schema["properties"]["input_vars"]["required"], json!(["messages"]));
+        assert_eq!(schema["properties"]["output_vars"]["items"]["enum"], json!(["messages"]));
+    }
+}
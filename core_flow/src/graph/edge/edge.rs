@@ -2,14 +2,31 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::graph::{condition::{condition::{deserialize_conditions_with_config, Condition}, condition_registry::ConditionRegistry}, edge::edge_builder::EdgeBuilder, node::node_context::NodeContext};
+use crate::graph::{condition::{condition::{deserialize_conditions_with_config, Condition}, condition_registry::ConditionRegistry}, edge::edge_builder::EdgeBuilder, flow_load_error::FlowLoadError, node::node_context::NodeContext};
 
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    // Taken when its conditions hold
+    #[default]
+    Conditional,
+    // Only taken when no conditional edge of the source node holds
+    Default,
+    // Only taken when an action of the source node failed with the route policy
+    OnError,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Edge {
     pub id: String,
     pub source_node_id: String,
     pub target_node_id: String,
+    // Higher priorities are preferred when several edges of a node are satisfied
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub kind: EdgeKind,
     #[serde(skip)]
     conditions: Vec<Box<dyn Condition<NodeContext>>>,
 }
@@ -24,6 +41,8 @@ impl Edge {
             id,
             source_node_id,
             target_node_id,
+            priority: 0,
+            kind: EdgeKind::Conditional,
             conditions: Vec::new(),
         }
     }
@@ -31,15 +50,17 @@ impl Edge {
     pub fn from_json(
         json: &str,
         condition_registry: &ConditionRegistry,
-    ) -> Result<Self, serde_json::Error> {
-        let mut edge: Edge = serde_json::from_str(json)?;
-
+    ) -> Result<Self, FlowLoadError> {
         let json_map: HashMap<String, serde_json::Value> = serde_json::from_str(json)?;
+        let edge_id = json_map.get("id").and_then(|id| id.as_str()).map(str::to_string);
+
+        let mut edge: Edge = serde_json::from_str(json)
+            .map_err(|error| FlowLoadError::from(error).with_edge(edge_id.clone()))?;
 
+        // Conditions that fail to load fail the edge, dropping them would make it always hold
         if let Some(conditions_value) = json_map.get("conditions") {
-            if let Ok(conditions) = deserialize_conditions_with_config(conditions_value.to_string().as_str(), condition_registry) {
-                edge.conditions = conditions;
-            }
+            edge.conditions = deserialize_conditions_with_config(conditions_value.to_string().as_str(), condition_registry)
+                .map_err(|error| error.with_path_prefix("conditions").with_edge(edge_id))?;
         }
         Ok(edge)
     }
@@ -48,6 +69,18 @@ impl Edge {
         EdgeBuilder::new(id, source_node_id, target_node_id)
     }
 
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    pub fn set_kind(&mut self, kind: EdgeKind) {
+        self.kind = kind;
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.kind == EdgeKind::Default
+    }
+
     pub fn add_condition(&mut self, condition: Box<dyn Condition<NodeContext>>) {
         self.conditions.push(condition.clone_box());
     }
@@ -142,7 +175,84 @@ mod tests {
             assert_eq!(edge.id, "welcome_to_help");
             assert_eq!(edge.source_node_id, "welcome");
             assert_eq!(edge.target_node_id, "help");
+            assert_eq!(edge.priority, 0);
             assert_eq!(edge.conditions.len(), 2);
         }
+
+        #[test]
+        fn test_from_json_with_priority() {
+            let json = r#"{
+                "id": "welcome_to_help",
+                "source_node_id": "welcome",
+                "target_node_id": "help",
+                "priority": 10
+            }"#;
+
+            let edge = Edge::from_json(json, &ConditionRegistry::new()).unwrap();
+
+            assert_eq!(edge.priority, 10);
+            assert_eq!(edge.kind, EdgeKind::Conditional);
+        }
+
+        #[test]
+        fn test_from_json_with_default_kind() {
+            let json = r#"{
+                "id": "welcome_to_fallback",
+                "source_node_id": "welcome",
+                "target_node_id": "fallback",
+                "kind": "default"
+            }"#;
+
+            let edge = Edge::from_json(json, &ConditionRegistry::new()).unwrap();
+
+            assert!(edge.is_default());
+        }
+
+        #[test]
+        fn test_from_json_fails_with_invalid_expression() {
+            let json = r#"{
+                "id": "welcome_to_refund",
+                "source_node_id": "welcome",
+                "target_node_id": "refund",
+                "conditions": [
+                    {
+                        "condition_type": "expression",
+                        "config": { "expression": "intent == == 'refund'" }
+                    }
+                ]
+            }"#;
+
+            let error = Edge::from_json(json, &ConditionRegistry::new()).unwrap_err();
+
+            assert!(error.to_string().contains("position 10"));
+        }
+
+        #[test]
+        fn test_from_json_fails_with_unknown_condition() {
+            let json = r#"{
+                "id": "welcome_to_help",
+                "source_node_id": "welcome",
+                "target_node_id": "help",
+                "conditions": [
+                    { "condition_type": "unknown_condition" }
+                ]
+            }"#;
+
+            let error = Edge::from_json(json, &ConditionRegistry::new()).unwrap_err();
+
+            assert_eq!(error.edge_id(), Some("welcome_to_help"));
+            assert_eq!(error.condition_index(), Some(0));
+            assert_eq!(error.reason(), "Unknown condition type: unknown_condition");
+        }
+
+        #[test]
+        fn test_from_json_fails_with_missing_field() {
+            let json = r#"{ "id": "welcome_to_help", "source_node_id": "welcome" }"#;
+
+            let error = Edge::from_json(json, &ConditionRegistry::new()).unwrap_err();
+
+            assert_eq!(error.edge_id(), Some("welcome_to_help"));
+            assert!(error.reason().contains("target_node_id"));
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,18 @@
-use super::{edge::Edge};
-use crate::graph::{condition::condition::Condition, node::node_context::NodeContext};
+use super::edge::{Edge, EdgeKind};
+use crate::graph::{
+    condition::{
+        composite_condition::{AnyOf, Not},
+        condition::Condition,
+    },
+    node::node_context::NodeContext,
+};
 
 pub struct EdgeBuilder {
     id: String,
     source_node_id: String,
     target_node_id: String,
+    priority: i32,
+    kind: EdgeKind,
     conditions: Vec<Box<dyn Condition<NodeContext>>>,
 }
 
@@ -14,21 +22,47 @@ impl EdgeBuilder {
             id,
             source_node_id,
             target_node_id,
+            priority: 0,
+            kind: EdgeKind::Conditional,
             conditions: Vec::new(),
         }
     }
 
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: EdgeKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
     pub fn with_condition(mut self, condition: impl Condition<NodeContext>) -> Self {
         self.conditions.push(condition.clone_box());
         self
     }
 
+    /// Adds a condition holding when at least one of the given conditions holds
+    pub fn with_any_of(mut self, conditions: Vec<Box<dyn Condition<NodeContext>>>) -> Self {
+        self.conditions.push(Box::new(AnyOf::new(conditions)));
+        self
+    }
+
+    /// Adds a condition holding when the given condition does not
+    pub fn with_not(mut self, condition: impl Condition<NodeContext>) -> Self {
+        self.conditions.push(Box::new(Not::new(condition.clone_box())));
+        self
+    }
+
     pub fn build(self) -> Edge {
         let mut edge = Edge::new(
             self.id,
             self.source_node_id,
             self.target_node_id,
         );
+        edge.set_priority(self.priority);
+        edge.set_kind(self.kind);
 
         for condition in self.conditions {
             edge.add_condition(condition);
@@ -77,6 +111,33 @@ mod tests {
         assert_eq!(edge.id, "test_id");
         assert_eq!(edge.source_node_id, "source_id");
         assert_eq!(edge.target_node_id, "target_id");
+        assert_eq!(edge.priority, 0);
+    }
+
+    #[test]
+    fn test_builder_sets_priority() {
+        let edge = EdgeBuilder::new(
+            "test_id".to_string(),
+            "source_id".to_string(),
+            "target_id".to_string()
+        )
+        .with_priority(5)
+        .build();
+
+        assert_eq!(edge.priority, 5);
+    }
+
+    #[test]
+    fn test_builder_sets_kind() {
+        let edge = EdgeBuilder::new(
+            "test_id".to_string(),
+            "source_id".to_string(),
+            "target_id".to_string()
+        )
+        .with_kind(EdgeKind::Default)
+        .build();
+
+        assert!(edge.is_default());
     }
 
     #[tokio::test]
@@ -116,4 +177,34 @@ mod tests {
 
         assert!(!edge_with_false.evaluate(&NodeContext::new()).await);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_builder_adds_any_of_condition() {
+        let edge = EdgeBuilder::new(
+            "test_id".to_string(),
+            "source_id".to_string(),
+            "target_id".to_string()
+        )
+        .with_any_of(vec![
+            TestCondition::new(false).clone_box(),
+            TestCondition::new(true).clone_box(),
+        ])
+        .build();
+
+        assert!(edge.evaluate(&NodeContext::new()).await);
+    }
+
+    #[tokio::test]
+    async fn test_builder_adds_not_condition() {
+        let edge = EdgeBuilder::new(
+            "test_id".to_string(),
+            "source_id".to_string(),
+            "target_id".to_string()
+        )
+        .with_condition(TestCondition::new(true))
+        .with_not(TestCondition::new(true))
+        .build();
+
+        assert!(!edge.evaluate(&NodeContext::new()).await);
+    }
+}
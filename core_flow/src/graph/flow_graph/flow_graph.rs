@@ -6,9 +6,11 @@ use serde_json::Value;
 use crate::graph::action::action_registry::ActionRegistry;
 use crate::graph::condition::condition_registry::ConditionRegistry;
 use crate::graph::flow_graph::flow_graph_builder::FlowGraphBuilder;
+use crate::graph::flow_graph::flow_graph_validator::validate_flow_graph;
+use crate::graph::flow_load_error::{FlowLoadError, FlowLoadErrorKind};
 use crate::graph::{
-    edge::edge::Edge,
-    node::{node::Node, node_context::NodeContext},
+    edge::edge::{Edge, EdgeKind},
+    node::{node::{Node, ACTION_ERROR_VARIABLE}, node_context::{NodeContext, Value as NodeValue}},
 };
 
 #[derive(Debug)]
@@ -17,6 +19,12 @@ pub struct  FlowGraph {
     edges: HashMap<String, Edge>,
     // Adjacency list for quick traversal
     adjacency_list: HashMap<String, Vec<String>>, // node_id -> vec of edge_ids
+    // Node every conversation starts at
+    start_node_id: Option<String>,
+    // Node reached when no edge of the current node can be taken
+    fallback_node_id: Option<String>,
+    // Seeded into the global scope of the context on every trigger
+    global_variables: HashMap<String, NodeValue>,
 }
 
 impl FlowGraph {
@@ -25,16 +33,23 @@ impl FlowGraph {
             nodes: HashMap::new(),
             edges: HashMap::new(),
             adjacency_list: HashMap::new(),
+            start_node_id: None,
+            fallback_node_id: None,
+            global_variables: HashMap::new(),
         }
     }
     // Json Structure
     // {
+    //     "start_node_id": "node_id",
+    //     "fallback_node_id": "node_id",
+    //     "global_variables": {},
     //     "nodes": [
     //         {
     //             "id": "node_id",
     //             "node_type": "conversational",
     //             "name": "Node Name",
     //             "description": "Node Description",
+    //             "terminal": false,
     //             "node_context": {
     //                 "variables": {}
     //             },
@@ -50,6 +65,8 @@ impl FlowGraph {
     //             "id": "edge_id",
     //             "source_node_id": "node_id",
     //             "target_node_id": "node_id",
+    //             "priority": 0,
+    //             "kind": "conditional" | "default" | "on_error",
     //             "conditions": [
     //                 {
     //                     "condition_type": "positive_condition"
@@ -63,30 +80,48 @@ impl FlowGraph {
         json: &str,
         action_registry: &ActionRegistry,
         condition_registry: &ConditionRegistry,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    ) -> Result<Self, FlowLoadError> {
         let json_map: HashMap<String, serde_json::Value> = serde_json::from_str(json)?;
 
         let mut graph = FlowGraph::new();
 
-        if let Some(Value::Array(nodes)) = json_map.get("nodes") {
-            let nodes = nodes
-                .iter()
-                .map(|node| Node::from_json(node.to_string().as_str(), action_registry))
-                .collect::<Result<Vec<Node>, serde_json::Error>>()?;
+        for (index, node) in json_array(&json_map, "nodes")?.iter().enumerate() {
+            let path = format!("nodes[{}]", index);
+            let node = Node::from_json(node.to_string().as_str(), action_registry, condition_registry)
+                .map_err(|error| error.with_path_prefix(&path))?;
+            let node_id = node.id.clone();
+
+            graph.add_node(node).map_err(|error| {
+                FlowLoadError::new(FlowLoadErrorKind::DuplicateNode, error.to_string())
+                    .with_path(&format!("{}.id", path))
+                    .with_node(Some(node_id))
+            })?;
+        }
 
-            for node in nodes {
-                graph.add_node(node)?;
-            }
+        for (index, edge) in json_array(&json_map, "edges")?.iter().enumerate() {
+            let path = format!("edges[{}]", index);
+            let edge = Edge::from_json(edge.to_string().as_str(), condition_registry)
+                .map_err(|error| error.with_path_prefix(&path))?;
+            let edge_id = edge.id.clone();
+
+            // Unknown nodes are left to `validate`, which reports them along with every other problem
+            graph.add_unchecked_edge(edge).map_err(|error| {
+                FlowLoadError::new(FlowLoadErrorKind::DuplicateEdge, error.to_string())
+                    .with_path(&format!("{}.id", path))
+                    .with_edge(Some(edge_id))
+            })?;
         }
 
-        if let Some(Value::Array(edges)) = json_map.get("edges") {
-            let edges = edges
-                .iter()
-                .map(|edge| Edge::from_json(edge.to_string().as_str(), condition_registry))
-                .collect::<Result<Vec<Edge>, serde_json::Error>>()?;
+        graph.start_node_id = json_string(&json_map, "start_node_id")?.map(str::to_string);
+        graph.fallback_node_id = json_string(&json_map, "fallback_node_id")?.map(str::to_string);
 
-            for edge in edges {
-                graph.add_edge(edge)?;
+        if let Some(global_variables) = json_object(&json_map, "global_variables")? {
+            for (name, value) in global_variables {
+                let value = NodeValue::try_from(value.clone()).map_err(|error| {
+                    FlowLoadError::new(FlowLoadErrorKind::InvalidFieldValue, error.to_string())
+                        .with_path(&format!("global_variables.{}", name))
+                })?;
+                graph.set_global_variable(name.clone(), value);
             }
         }
 
@@ -97,12 +132,81 @@ impl FlowGraph {
         FlowGraphBuilder::new()
     }
 
-    pub fn get_node_mut(&mut self, node_id: &str) -> Result<&mut Node, FlowError> {
-        if !self.nodes.contains_key(node_id) {
-            return Err(FlowError::NodeNotFound(node_id.to_string()));
+    pub fn get_node(&self, node_id: &str) -> Result<&Node, FlowError> {
+        self.nodes
+            .get(node_id)
+            .ok_or_else(|| FlowError::NodeNotFound(node_id.to_string()))
+    }
+
+    pub fn get_nodes(&self) -> &HashMap<String, Node> {
+        &self.nodes
+    }
+
+    pub fn get_edges(&self) -> &HashMap<String, Edge> {
+        &self.edges
+    }
+
+    /// Outgoing edges of a node in the order they were added
+    pub fn get_outgoing_edges(&self, node_id: &str) -> Vec<&Edge> {
+        self.adjacency_list
+            .get(node_id)
+            .map(|edge_ids| edge_ids.iter().filter_map(|edge_id| self.edges.get(edge_id)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set_start_node(&mut self, node_id: String) -> Result<(), FlowError> {
+        if !self.nodes.contains_key(&node_id) {
+            return Err(FlowError::NodeNotFound(node_id));
         }
 
-        Ok(self.nodes.get_mut(node_id).unwrap())
+        self.start_node_id = Some(node_id);
+        Ok(())
+    }
+
+    pub fn get_start_node_id(&self) -> Option<&str> {
+        self.start_node_id.as_deref()
+    }
+
+    /// Ids of the nodes that end a conversation, sorted
+    pub fn get_terminal_node_ids(&self) -> Vec<&str> {
+        let mut terminal_node_ids: Vec<&str> = self
+            .nodes
+            .values()
+            .filter(|node| node.is_terminal())
+            .map(|node| node.id.as_str())
+            .collect();
+        terminal_node_ids.sort();
+        terminal_node_ids
+    }
+
+    pub fn set_fallback_node(&mut self, node_id: String) -> Result<(), FlowError> {
+        if !self.nodes.contains_key(&node_id) {
+            return Err(FlowError::NodeNotFound(node_id));
+        }
+
+        self.fallback_node_id = Some(node_id);
+        Ok(())
+    }
+
+    // Start and fallback nodes that may not exist, for builders that leave the check to `validate`
+    pub(super) fn set_unchecked_start_node(&mut self, node_id: String) {
+        self.start_node_id = Some(node_id);
+    }
+
+    pub(super) fn set_unchecked_fallback_node(&mut self, node_id: String) {
+        self.fallback_node_id = Some(node_id);
+    }
+
+    pub fn get_fallback_node_id(&self) -> Option<&str> {
+        self.fallback_node_id.as_deref()
+    }
+
+    pub fn set_global_variable(&mut self, name: String, value: NodeValue) {
+        self.global_variables.insert(name, value);
+    }
+
+    pub fn get_global_variables(&self) -> &HashMap<String, NodeValue> {
+        &self.global_variables
     }
 
     pub fn add_node(&mut self, node: Node) -> Result<(), FlowError> {
@@ -113,7 +217,7 @@ impl FlowGraph {
         }
 
         self.nodes.insert(node_id.clone(), node);
-        self.adjacency_list.insert(node_id, Vec::new());
+        self.adjacency_list.entry(node_id).or_default();
 
         Ok(())
     }
@@ -128,17 +232,20 @@ impl FlowGraph {
             return Err(FlowError::NodeNotFound(edge.target_node_id.clone()));
         }
 
+        self.add_unchecked_edge(edge)
+    }
+
+    /// Adds an edge whose nodes may not exist yet, `validate` reports it as dangling if they never do
+    pub(super) fn add_unchecked_edge(&mut self, edge: Edge) -> Result<(), FlowError> {
         let edge_id: String = edge.id.clone();
         if self.edges.contains_key(&edge_id) {
             return Err(FlowError::DuplicateEdge(edge_id));
         }
 
-        let source_node_id = edge.source_node_id.as_str();
-
         // Add edge to adjacency list
         self.adjacency_list
-            .get_mut(source_node_id)
-            .unwrap()
+            .entry(edge.source_node_id.clone())
+            .or_default()
             .push(edge_id.clone());
 
         self.edges.insert(edge_id, edge);
@@ -146,40 +253,140 @@ impl FlowGraph {
         Ok(())
     }
 
-    /// Find next valid node based on current context
+    /// Checks the graph as a whole and reports every problem found, see `FlowError` for the kinds
+    pub fn validate(&self) -> Result<(), Vec<FlowError>> {
+        let errors = validate_flow_graph(self);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Find next valid node based on current context, see `find_satisfied_edges` for the ordering.
+    /// When no edge can be taken the graph fallback node is returned, if any.
     pub async fn find_next_node(
         &self,
         current_node_id: &str,
         context: &NodeContext,
     ) -> Option<String> {
-        let edge_ids = self.adjacency_list.get(current_node_id)?;
+        self.find_satisfied_edges(current_node_id, context)
+            .await
+            .first()
+            .map(|edge| edge.target_node_id.clone())
+            .or_else(|| self.fallback_node_id.clone())
+    }
+
+    /// All outgoing edges of a node whose conditions hold, ranked by priority (highest first).
+    /// Edges with the same priority keep the order in which they were added to the graph.
+    /// Default edges are only returned when no conditional edge holds. After an action failed
+    /// with the route policy only on_error edges are considered.
+    pub async fn find_satisfied_edges(
+        &self,
+        current_node_id: &str,
+        context: &NodeContext,
+    ) -> Vec<&Edge> {
+        if context.transient.contains_key(ACTION_ERROR_VARIABLE) {
+            return self
+                .satisfied_edges_of_kind(current_node_id, context, EdgeKind::OnError)
+                .await;
+        }
+
+        let conditional_edges = self
+            .satisfied_edges_of_kind(current_node_id, context, EdgeKind::Conditional)
+            .await;
+
+        if !conditional_edges.is_empty() {
+            return conditional_edges;
+        }
+
+        self.satisfied_edges_of_kind(current_node_id, context, EdgeKind::Default)
+            .await
+    }
+
+    async fn satisfied_edges_of_kind(
+        &self,
+        current_node_id: &str,
+        context: &NodeContext,
+        kind: EdgeKind,
+    ) -> Vec<&Edge> {
+        let Some(edge_ids) = self.adjacency_list.get(current_node_id) else {
+            return Vec::new();
+        };
 
-        // Get all valid edges and evaluate them asynchronously
         let mut valid_edges = Vec::new();
         for edge_id in edge_ids {
             if let Some(edge) = self.edges.get(edge_id) {
-                if edge.evaluate(context).await {
+                if edge.kind == kind && edge.evaluate(context).await {
                     valid_edges.push(edge);
                 }
             }
         }
 
-        // Sort by priority (highest first)
-        // Assuming Edge has a priority field, you might want to add:
-        // valid_edges.sort_by(|a, b| b.priority.cmp(&a.priority));
+        // Stable sort, so declaration order breaks ties
+        valid_edges.sort_by_key(|edge| std::cmp::Reverse(edge.priority));
 
-        valid_edges.get(0).map(|edge| edge.target_node_id.clone())
+        valid_edges
     }
 }
 
-#[derive(Debug)]
+// Optional array field of the flow json, an absent field loads as empty
+fn json_array<'a>(json_map: &'a HashMap<String, Value>, field: &str) -> Result<&'a [Value], FlowLoadError> {
+    match json_map.get(field) {
+        None => Ok(&[]),
+        Some(Value::Array(values)) => Ok(values),
+        Some(value) => Err(FlowLoadError::new(
+            FlowLoadErrorKind::InvalidFieldType,
+            format!("{} must be an array, found {}", field, value),
+        )
+        .with_path(field)),
+    }
+}
+
+// Optional object field of the flow json
+fn json_object<'a>(
+    json_map: &'a HashMap<String, Value>,
+    field: &str,
+) -> Result<Option<&'a serde_json::Map<String, Value>>, FlowLoadError> {
+    match json_map.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Object(values)) => Ok(Some(values)),
+        Some(value) => Err(FlowLoadError::new(
+            FlowLoadErrorKind::InvalidFieldType,
+            format!("{} must be an object, found {}", field, value),
+        )
+        .with_path(field)),
+    }
+}
+
+// Optional string field of the flow json
+fn json_string<'a>(json_map: &'a HashMap<String, Value>, field: &str) -> Result<Option<&'a str>, FlowLoadError> {
+    match json_map.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(value) => Err(FlowLoadError::new(
+            FlowLoadErrorKind::InvalidFieldType,
+            format!("{} must be a string, found {}", field, value),
+        )
+        .with_path(field)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum FlowError {
     NodeNotFound(String),
     DuplicateNode(String),
     DuplicateEdge(String),
+    // Nodes looping into each other without any node waiting for user input
     CycleDetected(Vec<String>),
     NoStartNodes,
     UnreachableNodes(Vec<String>),
+    // Non terminal nodes the conversation can never leave
+    DeadEndNodes(Vec<String>),
+    DanglingEdge { edge_id: String, node_id: String },
+    UndeclaredInputVar { node_id: String, action_id: String, variable: String },
+    InvalidGraph(Vec<FlowError>),
 }
 
 impl fmt::Display for FlowError {
@@ -193,6 +400,21 @@ impl fmt::Display for FlowError {
             FlowError::UnreachableNodes(unreachable_nodes) => {
                 write!(f, "Unreachable nodes: {:?}", unreachable_nodes)
             }
+            FlowError::DeadEndNodes(dead_end_nodes) => {
+                write!(f, "Dead end nodes: {:?}", dead_end_nodes)
+            }
+            FlowError::DanglingEdge { edge_id, node_id } => {
+                write!(f, "Edge {} references unknown node {}", edge_id, node_id)
+            }
+            FlowError::UndeclaredInputVar { node_id, action_id, variable } => write!(
+                f,
+                "Action {} of node {} reads {} which no upstream action declares",
+                action_id, node_id, variable
+            ),
+            FlowError::InvalidGraph(errors) => {
+                let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(f, "Invalid graph: {}", errors.join("; "))
+            }
         }
     }
 }
@@ -284,6 +506,158 @@ mod tests {
         );
     }
 
+    mod given_priorities {
+        use crate::graph::condition::tests::condition_implementation::NegativeCondition;
+
+        use super::*;
+
+        fn create_graph_with_edges(edges: Vec<Edge>) -> FlowGraph {
+            let mut graph = FlowGraph::new();
+            for node_id in ["node1", "node2", "node3", "node4"] {
+                graph
+                    .add_node(Node::new(
+                        node_id.to_string(),
+                        "message".to_string(),
+                        node_id.to_string(),
+                        "description".to_string(),
+                    ))
+                    .unwrap();
+            }
+            for edge in edges {
+                graph.add_edge(edge).unwrap();
+            }
+            graph
+        }
+
+        #[tokio::test]
+        async fn should_prefer_the_highest_priority_edge() {
+            let graph = create_graph_with_edges(vec![
+                Edge::builder("edge1".to_string(), "node1".to_string(), "node2".to_string())
+                    .with_priority(1)
+                    .build(),
+                Edge::builder("edge2".to_string(), "node1".to_string(), "node3".to_string())
+                    .with_priority(5)
+                    .build(),
+            ]);
+
+            assert_eq!(
+                graph.find_next_node("node1", &NodeContext::new()).await,
+                Some("node3".to_string())
+            );
+        }
+
+        #[tokio::test]
+        async fn should_rank_satisfied_edges() {
+            let graph = create_graph_with_edges(vec![
+                Edge::builder("low".to_string(), "node1".to_string(), "node2".to_string())
+                    .with_priority(-1)
+                    .build(),
+                Edge::builder("tie_first".to_string(), "node1".to_string(), "node3".to_string())
+                    .with_priority(3)
+                    .build(),
+                Edge::builder("unsatisfied".to_string(), "node1".to_string(), "node4".to_string())
+                    .with_priority(10)
+                    .with_condition(NegativeCondition)
+                    .build(),
+                Edge::builder("tie_second".to_string(), "node1".to_string(), "node4".to_string())
+                    .with_priority(3)
+                    .build(),
+            ]);
+
+            let ranked: Vec<&str> = graph
+                .find_satisfied_edges("node1", &NodeContext::new())
+                .await
+                .iter()
+                .map(|edge| edge.id.as_str())
+                .collect();
+
+            assert_eq!(ranked, vec!["tie_first", "tie_second", "low"]);
+        }
+
+        #[tokio::test]
+        async fn should_only_take_default_edges_when_nothing_else_holds() {
+            let graph = create_graph_with_edges(vec![
+                Edge::builder("else".to_string(), "node1".to_string(), "node4".to_string())
+                    .with_kind(EdgeKind::Default)
+                    .with_priority(100)
+                    .build(),
+                Edge::builder("unsatisfied".to_string(), "node1".to_string(), "node3".to_string())
+                    .with_condition(NegativeCondition)
+                    .build(),
+                Edge::builder("satisfied".to_string(), "node2".to_string(), "node3".to_string())
+                    .build(),
+                Edge::builder("unused_else".to_string(), "node2".to_string(), "node4".to_string())
+                    .with_kind(EdgeKind::Default)
+                    .build(),
+            ]);
+
+            assert_eq!(
+                graph.find_next_node("node1", &NodeContext::new()).await,
+                Some("node4".to_string())
+            );
+            assert_eq!(
+                graph.find_next_node("node2", &NodeContext::new()).await,
+                Some("node3".to_string())
+            );
+        }
+
+        #[tokio::test]
+        async fn should_only_take_on_error_edges_after_a_routed_failure() {
+            let graph = create_graph_with_edges(vec![
+                Edge::builder("satisfied".to_string(), "node1".to_string(), "node2".to_string())
+                    .build(),
+                Edge::builder("on_error".to_string(), "node1".to_string(), "node3".to_string())
+                    .with_kind(EdgeKind::OnError)
+                    .build(),
+            ]);
+            let mut failed_context = NodeContext::new();
+            failed_context
+                .transient
+                .insert(ACTION_ERROR_VARIABLE.to_string(), crate::graph::node::node_context::Value::Null);
+
+            assert_eq!(
+                graph.find_next_node("node1", &NodeContext::new()).await,
+                Some("node2".to_string())
+            );
+            assert_eq!(
+                graph.find_next_node("node1", &failed_context).await,
+                Some("node3".to_string())
+            );
+        }
+
+        #[tokio::test]
+        async fn should_route_to_the_fallback_node() {
+            let mut graph = create_graph_with_edges(vec![
+                Edge::builder("unsatisfied".to_string(), "node1".to_string(), "node2".to_string())
+                    .with_condition(NegativeCondition)
+                    .build(),
+            ]);
+            graph.set_fallback_node("node4".to_string()).unwrap();
+
+            assert_eq!(
+                graph.find_next_node("node1", &NodeContext::new()).await,
+                Some("node4".to_string())
+            );
+        }
+
+        #[test]
+        fn should_reject_unknown_fallback_node() {
+            let mut graph = create_graph_with_edges(vec![]);
+
+            assert!(matches!(
+                graph.set_fallback_node("unknown".to_string()),
+                Err(FlowError::NodeNotFound(_))
+            ));
+        }
+
+        #[tokio::test]
+        async fn should_return_no_edges_for_unknown_node() {
+            let graph = create_graph_with_edges(vec![]);
+
+            assert!(graph.find_satisfied_edges("unknown", &NodeContext::new()).await.is_empty());
+        }
+    }
+
     mod given_some_conditions {
 
         use crate::graph::condition::{condition::Condition, tests::condition_implementation::{NegativeCondition, PositiveCondition}};
@@ -407,7 +781,7 @@ mod tests {
                                     "id": "test_action"
                                 },
                                 "input_vars": {},
-                                "output_vars": {},
+                                "output_vars": [],
                                 "action_type": "test_action"
                             }
                         ]
@@ -465,5 +839,183 @@ mod tests {
             assert_eq!(graph.nodes.len(), 2);
             assert_eq!(graph.edges.len(), 1);
         }
+
+        #[test]
+        fn test_from_json_with_fallback_node() {
+            let json = r#"{
+                "fallback_node_id": "fallback",
+                "nodes": [
+                    {
+                        "id": "fallback",
+                        "node_type": "conversational",
+                        "name": "Fallback",
+                        "description": "Fallback description",
+                        "node_context": {
+                            "variables": {}
+                        },
+                        "actions": []
+                    }
+                ],
+                "edges": []
+            }"#;
+
+            let graph = FlowGraph::from_json(json, &ActionRegistry::new(), &ConditionRegistry::new()).unwrap();
+
+            assert_eq!(graph.get_fallback_node_id(), Some("fallback"));
+        }
+
+        #[test]
+        fn test_from_json_with_start_and_terminal_nodes() {
+            let json = r#"{
+                "start_node_id": "welcome",
+                "nodes": [
+                    {
+                        "id": "welcome",
+                        "node_type": "conversational",
+                        "name": "Welcome",
+                        "description": "Welcome description",
+                        "node_context": {
+                            "variables": {}
+                        },
+                        "actions": []
+                    },
+                    {
+                        "id": "goodbye",
+                        "node_type": "conversational",
+                        "name": "Goodbye",
+                        "description": "Goodbye description",
+                        "terminal": true,
+                        "node_context": {
+                            "variables": {}
+                        },
+                        "actions": []
+                    }
+                ],
+                "edges": []
+            }"#;
+
+            let graph = FlowGraph::from_json(json, &ActionRegistry::new(), &ConditionRegistry::new()).unwrap();
+
+            assert_eq!(graph.get_start_node_id(), Some("welcome"));
+            assert_eq!(graph.get_terminal_node_ids(), vec!["goodbye"]);
+        }
+
+        #[test]
+        fn test_from_json_with_global_variables() {
+            let json = r#"{
+                "global_variables": {"company": {"name": "Acme"}},
+                "nodes": [],
+                "edges": []
+            }"#;
+
+            let graph = FlowGraph::from_json(json, &ActionRegistry::new(), &ConditionRegistry::new()).unwrap();
+            assert_eq!(
+                graph.get_global_variables().get("company"),
+                Some(&NodeValue::try_from(serde_json::json!({"name": "Acme"})).unwrap())
+            );
+
+            let error = FlowGraph::from_json(r#"{"global_variables": []}"#, &ActionRegistry::new(), &ConditionRegistry::new())
+                .unwrap_err();
+            assert_eq!(error.kind(), FlowLoadErrorKind::InvalidFieldType);
+            assert_eq!(error.path(), "global_variables");
+        }
+
+        #[test]
+        fn test_from_json_leaves_unknown_start_node_to_validation() {
+            let json = r#"{
+                "start_node_id": "missing",
+                "nodes": [],
+                "edges": []
+            }"#;
+
+            let graph = FlowGraph::from_json(json, &ActionRegistry::new(), &ConditionRegistry::new()).unwrap();
+
+            assert_eq!(
+                graph.validate().unwrap_err(),
+                vec![FlowError::NodeNotFound("missing".to_string())]
+            );
+        }
+
+        #[test]
+        fn test_from_json_reports_the_path_of_a_missing_action_field() {
+            let json = r#"{
+                "nodes": [
+                    {
+                        "id": "welcome",
+                        "node_type": "conversational",
+                        "name": "Welcome",
+                        "description": "Welcome description",
+                        "node_context": {
+                            "variables": {}
+                        },
+                        "actions": [
+                            {
+                                "config": {
+                                    "name": "test_action"
+                                },
+                                "input_vars": {},
+                                "output_vars": [],
+                                "action_type": "test_action"
+                            }
+                        ]
+                    }
+                ]
+            }"#;
+
+            let mut action_registry = ActionRegistry::new();
+            action_registry.register_action("test_action", create_test_action);
+
+            let error = FlowGraph::from_json(json, &action_registry, &ConditionRegistry::new()).unwrap_err();
+
+            assert_eq!(error.kind(), FlowLoadErrorKind::MissingField);
+            assert_eq!(error.path(), "nodes[0].actions[0].config.id");
+            assert_eq!(error.node_id(), Some("welcome"));
+            assert_eq!(error.action_index(), Some(0));
+        }
+
+        #[test]
+        fn test_from_json_reports_edges_to_unknown_nodes() {
+            let json = r#"{
+                "nodes": [
+                    {
+                        "id": "welcome",
+                        "node_type": "conversational",
+                        "name": "Welcome",
+                        "description": "Welcome description",
+                        "node_context": {
+                            "variables": {}
+                        }
+                    }
+                ],
+                "edges": [
+                    {
+                        "id": "welcome_to_help",
+                        "source_node_id": "welcome",
+                        "target_node_id": "help"
+                    }
+                ]
+            }"#;
+
+            let graph = FlowGraph::from_json(json, &ActionRegistry::new(), &ConditionRegistry::new()).unwrap();
+
+            assert_eq!(
+                graph.validate().unwrap_err(),
+                vec![
+                    FlowError::NoStartNodes,
+                    FlowError::DanglingEdge {
+                        edge_id: "welcome_to_help".to_string(),
+                        node_id: "help".to_string(),
+                    },
+                    FlowError::DeadEndNodes(vec!["welcome".to_string()]),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_from_json_reports_invalid_json() {
+            let error = FlowGraph::from_json("{ \"nodes\": [", &ActionRegistry::new(), &ConditionRegistry::new()).unwrap_err();
+
+            assert_eq!(error.kind(), FlowLoadErrorKind::InvalidJson);
+        }
     }
 }
@@ -1,12 +1,17 @@
+use std::collections::HashMap;
+
 use super::flow_graph::{FlowGraph, FlowError};
 use crate::graph::{
-    node::node::Node,
+    node::{node::Node, node_context::Value},
     edge::edge::Edge,
 };
 
 pub struct FlowGraphBuilder {
     nodes: Vec<Node>,
     edges: Vec<Edge>,
+    start_node_id: Option<String>,
+    fallback_node_id: Option<String>,
+    global_variables: HashMap<String, Value>,
 }
 
 impl FlowGraphBuilder {
@@ -14,6 +19,9 @@ impl FlowGraphBuilder {
         FlowGraphBuilder {
             nodes: Vec::new(),
             edges: Vec::new(),
+            start_node_id: None,
+            fallback_node_id: None,
+            global_variables: HashMap::new(),
         }
     }
 
@@ -27,6 +35,22 @@ impl FlowGraphBuilder {
         self
     }
 
+    pub fn with_start_node(mut self, node_id: String) -> Self {
+        self.start_node_id = Some(node_id);
+        self
+    }
+
+    pub fn with_fallback_node(mut self, node_id: String) -> Self {
+        self.fallback_node_id = Some(node_id);
+        self
+    }
+
+    pub fn with_global_variable(mut self, name: String, value: Value) -> Self {
+        self.global_variables.insert(name, value);
+        self
+    }
+
+    /// Builds the graph and validates it, every validation problem is reported in `FlowError::InvalidGraph`
     pub fn build(self) -> Result<FlowGraph, FlowError> {
         let mut flow_graph = FlowGraph::new();
 
@@ -35,11 +59,25 @@ impl FlowGraphBuilder {
             flow_graph.add_node(node)?;
         }
 
-        // Then add all edges
+        // Then add all edges, references to unknown nodes are reported by the validation
         for edge in self.edges {
-            flow_graph.add_edge(edge)?;
+            flow_graph.add_unchecked_edge(edge)?;
+        }
+
+        if let Some(start_node_id) = self.start_node_id {
+            flow_graph.set_unchecked_start_node(start_node_id);
         }
 
+        if let Some(fallback_node_id) = self.fallback_node_id {
+            flow_graph.set_unchecked_fallback_node(fallback_node_id);
+        }
+
+        for (name, value) in self.global_variables {
+            flow_graph.set_global_variable(name, value);
+        }
+
+        flow_graph.validate().map_err(FlowError::InvalidGraph)?;
+
         Ok(flow_graph)
     }
 }
@@ -75,14 +113,34 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_build_empty_graph() {
-        let graph = FlowGraphBuilder::new().build().unwrap();
-        assert!(graph.find_next_node("non_existent", &NodeContext::new()).await.is_none());
+    #[test]
+    fn test_build_empty_graph() {
+        let result = FlowGraphBuilder::new().build();
+        assert!(matches!(result, Err(FlowError::InvalidGraph(errors)) if errors == vec![FlowError::NoStartNodes]));
     }
 
     #[tokio::test]
     async fn test_build_graph_with_nodes() {
+        let mut node1 = Node::new(
+            "node1".to_string(),
+            "message".to_string(),
+            "Node 1".to_string(),
+            "Node 1 description".to_string(),
+        );
+        node1.set_terminal(true);
+
+        let graph = FlowGraphBuilder::new()
+            .with_node(node1)
+            .with_start_node("node1".to_string())
+            .build()
+            .unwrap();
+
+        assert_eq!(graph.get_start_node_id(), Some("node1"));
+        assert!(graph.find_next_node("node1", &NodeContext::new()).await.is_none());
+    }
+
+    #[test]
+    fn test_build_fails_with_every_validation_error() {
         let node1 = Node::new(
             "node1".to_string(),
             "message".to_string(),
@@ -97,13 +155,22 @@ mod tests {
             "Node 2 description".to_string(),
         );
 
-        let graph = FlowGraphBuilder::new()
+        let result = FlowGraphBuilder::new()
             .with_node(node1)
             .with_node(node2)
-            .build()
-            .unwrap();
+            .with_start_node("node1".to_string())
+            .build();
 
-        assert!(graph.find_next_node("node1", &NodeContext::new()).await.is_none());
+        match result {
+            Err(FlowError::InvalidGraph(errors)) => assert_eq!(
+                errors,
+                vec![
+                    FlowError::UnreachableNodes(vec!["node2".to_string()]),
+                    FlowError::DeadEndNodes(vec!["node1".to_string(), "node2".to_string()]),
+                ]
+            ),
+            _ => panic!("Expected an invalid graph"),
+        }
     }
 
     #[tokio::test]
@@ -115,12 +182,13 @@ mod tests {
             "Node 1 description".to_string(),
         );
 
-        let node2 = Node::new(
+        let mut node2 = Node::new(
             "node2".to_string(),
             "message".to_string(),
             "Node 2".to_string(),
             "Node 2 description".to_string(),
         );
+        node2.set_terminal(true);
 
         let mut edge = Edge::new(
             "edge1".to_string(),
@@ -133,6 +201,7 @@ mod tests {
             .with_node(node1)
             .with_node(node2)
             .with_edge(edge)
+            .with_start_node("node1".to_string())
             .build()
             .unwrap();
 
@@ -142,6 +211,37 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_build_graph_with_fallback_node() {
+        let node1 = Node::new(
+            "node1".to_string(),
+            "conversational".to_string(),
+            "Node 1".to_string(),
+            "Node 1 description".to_string(),
+        );
+
+        let fallback = Node::new(
+            "fallback".to_string(),
+            "conversational".to_string(),
+            "Fallback".to_string(),
+            "Fallback description".to_string(),
+        );
+
+        let graph = FlowGraphBuilder::new()
+            .with_node(node1)
+            .with_node(fallback)
+            .with_start_node("node1".to_string())
+            .with_fallback_node("fallback".to_string())
+            .build()
+            .unwrap();
+
+        assert_eq!(graph.get_fallback_node_id(), Some("fallback"));
+        assert_eq!(
+            graph.find_next_node("node1", &NodeContext::new()).await,
+            Some("fallback".to_string())
+        );
+    }
+
     #[test]
     fn test_build_fails_with_missing_node_for_edge() {
         let node1 = Node::new(
@@ -160,9 +260,68 @@ mod tests {
         let result = FlowGraphBuilder::new()
             .with_node(node1)
             .with_edge(edge)
+            .with_start_node("node1".to_string())
             .build();
 
-        assert!(matches!(result, Err(FlowError::NodeNotFound(_))));
+        match result {
+            Err(FlowError::InvalidGraph(errors)) => assert_eq!(
+                errors,
+                vec![
+                    FlowError::DanglingEdge {
+                        edge_id: "edge1".to_string(),
+                        node_id: "non_existent".to_string(),
+                    },
+                    FlowError::DeadEndNodes(vec!["node1".to_string()]),
+                ]
+            ),
+            _ => panic!("Expected an invalid graph"),
+        }
+    }
+
+    #[test]
+    fn test_build_reports_dangling_edges_with_unreachable_nodes() {
+        let node1 = Node::new(
+            "node1".to_string(),
+            "message".to_string(),
+            "Node 1".to_string(),
+            "Node 1 description".to_string(),
+        );
+        let node2 = Node::new(
+            "node2".to_string(),
+            "message".to_string(),
+            "Node 2".to_string(),
+            "Node 2 description".to_string(),
+        );
+
+        let edge = Edge::new(
+            "edge1".to_string(),
+            "node1".to_string(),
+            "non_existent".to_string(),
+        );
+
+        let result = FlowGraphBuilder::new()
+            .with_node(node1)
+            .with_node(node2)
+            .with_edge(edge)
+            .with_start_node("node1".to_string())
+            .with_fallback_node("missing".to_string())
+            .build();
+
+        match result {
+            Err(FlowError::InvalidGraph(errors)) => assert_eq!(
+                errors,
+                vec![
+                    FlowError::NodeNotFound("missing".to_string()),
+                    FlowError::DanglingEdge {
+                        edge_id: "edge1".to_string(),
+                        node_id: "non_existent".to_string(),
+                    },
+                    FlowError::UnreachableNodes(vec!["node2".to_string()]),
+                    FlowError::DeadEndNodes(vec!["node1".to_string(), "node2".to_string()]),
+                ]
+            ),
+            _ => panic!("Expected an invalid graph"),
+        }
     }
 
     #[test]
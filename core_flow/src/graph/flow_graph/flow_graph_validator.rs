@@ -0,0 +1,557 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::flow_graph::{FlowError, FlowGraph};
+use crate::graph::node::{node::Node, node_context::VariableScope};
+
+// Variables the flow manager provides to every node it executes
+const PROVIDED_VARIABLES: [&str; 2] = ["messages", "trigger_message"];
+
+pub(super) fn validate_flow_graph(graph: &FlowGraph) -> Vec<FlowError> {
+    let mut errors = Vec::new();
+
+    errors.extend(check_references(graph));
+    errors.extend(check_reachability(graph));
+    errors.extend(check_dead_ends(graph));
+    errors.extend(check_cycles(graph));
+    errors.extend(check_input_vars(graph));
+
+    errors
+}
+
+fn sorted_nodes(graph: &FlowGraph) -> Vec<&Node> {
+    let mut nodes: Vec<&Node> = graph.get_nodes().values().collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    nodes
+}
+
+// Nodes a conversation can move to once the node ran. Terminal nodes end the conversation,
+// every other node can be routed to the fallback node when none of its edges holds.
+fn successors<'a>(graph: &'a FlowGraph, node: &'a Node) -> Vec<&'a str> {
+    if node.is_terminal() {
+        return Vec::new();
+    }
+
+    let mut successors: Vec<&str> = graph
+        .get_outgoing_edges(&node.id)
+        .into_iter()
+        .map(|edge| edge.target_node_id.as_str())
+        .filter(|target| graph.get_nodes().contains_key(*target))
+        .collect();
+
+    if let Some(fallback_node_id) = graph.get_fallback_node_id()
+        && graph.get_nodes().contains_key(fallback_node_id)
+    {
+        successors.push(fallback_node_id);
+    }
+
+    successors
+}
+
+fn reachable_from<'a>(graph: &'a FlowGraph, start_nodes: Vec<&'a str>) -> HashSet<&'a str> {
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<&str> = start_nodes.into_iter().collect();
+
+    while let Some(node_id) = queue.pop_front() {
+        if !visited.insert(node_id) {
+            continue;
+        }
+        if let Some(node) = graph.get_nodes().get(node_id) {
+            queue.extend(successors(graph, node));
+        }
+    }
+
+    visited
+}
+
+fn check_references(graph: &FlowGraph) -> Vec<FlowError> {
+    let mut errors = Vec::new();
+
+    match graph.get_start_node_id() {
+        None => errors.push(FlowError::NoStartNodes),
+        Some(start_node_id) if !graph.get_nodes().contains_key(start_node_id) => {
+            errors.push(FlowError::NodeNotFound(start_node_id.to_string()))
+        }
+        Some(_) => {}
+    }
+
+    if let Some(fallback_node_id) = graph
+        .get_fallback_node_id()
+        .filter(|fallback_node_id| !graph.get_nodes().contains_key(*fallback_node_id))
+    {
+        errors.push(FlowError::NodeNotFound(fallback_node_id.to_string()));
+    }
+
+    let mut edges: Vec<_> = graph.get_edges().values().collect();
+    edges.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for edge in edges {
+        for node_id in [&edge.source_node_id, &edge.target_node_id] {
+            if !graph.get_nodes().contains_key(node_id) {
+                errors.push(FlowError::DanglingEdge {
+                    edge_id: edge.id.clone(),
+                    node_id: node_id.clone(),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+fn check_reachability(graph: &FlowGraph) -> Vec<FlowError> {
+    let Some(start_node_id) = graph.get_start_node_id() else {
+        return Vec::new();
+    };
+
+    let reachable = reachable_from(graph, vec![start_node_id]);
+    let unreachable: Vec<String> = sorted_nodes(graph)
+        .into_iter()
+        .filter(|node| !reachable.contains(node.id.as_str()))
+        .map(|node| node.id.clone())
+        .collect();
+
+    if unreachable.is_empty() {
+        Vec::new()
+    } else {
+        vec![FlowError::UnreachableNodes(unreachable)]
+    }
+}
+
+fn check_dead_ends(graph: &FlowGraph) -> Vec<FlowError> {
+    let dead_ends: Vec<String> = sorted_nodes(graph)
+        .into_iter()
+        .filter(|node| !node.is_terminal() && successors(graph, node).is_empty())
+        .map(|node| node.id.clone())
+        .collect();
+
+    if dead_ends.is_empty() {
+        Vec::new()
+    } else {
+        vec![FlowError::DeadEndNodes(dead_ends)]
+    }
+}
+
+// Only nodes that do not wait for user input can loop forever, so cycles are searched
+// among them (strongly connected components, Tarjan's algorithm).
+fn check_cycles(graph: &FlowGraph) -> Vec<FlowError> {
+    struct Tarjan<'a> {
+        graph: &'a FlowGraph,
+        index: usize,
+        indexes: HashMap<&'a str, usize>,
+        low_links: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        components: Vec<Vec<&'a str>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn neighbours(&self, node_id: &'a str) -> Vec<&'a str> {
+            let node = &self.graph.get_nodes()[node_id];
+            successors(self.graph, node)
+                .into_iter()
+                .filter(|target| !self.graph.get_nodes()[*target].awaits_input())
+                .collect()
+        }
+
+        fn visit(&mut self, node_id: &'a str) {
+            self.indexes.insert(node_id, self.index);
+            self.low_links.insert(node_id, self.index);
+            self.index += 1;
+            self.stack.push(node_id);
+            self.on_stack.insert(node_id);
+
+            for target in self.neighbours(node_id) {
+                if !self.indexes.contains_key(target) {
+                    self.visit(target);
+                    let low_link = self.low_links[node_id].min(self.low_links[target]);
+                    self.low_links.insert(node_id, low_link);
+                } else if self.on_stack.contains(target) {
+                    let low_link = self.low_links[node_id].min(self.indexes[target]);
+                    self.low_links.insert(node_id, low_link);
+                }
+            }
+
+            if self.low_links[node_id] == self.indexes[node_id] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member);
+                    if member == node_id {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph,
+        index: 0,
+        indexes: HashMap::new(),
+        low_links: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+
+    for node in sorted_nodes(graph) {
+        if !node.awaits_input() && !tarjan.indexes.contains_key(node.id.as_str()) {
+            tarjan.visit(node.id.as_str());
+        }
+    }
+
+    let mut cycles: Vec<Vec<String>> = tarjan
+        .components
+        .iter()
+        .filter(|component| {
+            component.len() > 1 || tarjan.neighbours(component[0]).contains(&component[0])
+        })
+        .map(|component| {
+            let mut cycle: Vec<String> = component.iter().map(|id| id.to_string()).collect();
+            cycle.sort();
+            cycle
+        })
+        .collect();
+    cycles.sort();
+
+    cycles.into_iter().map(FlowError::CycleDetected).collect()
+}
+
+fn check_input_vars(graph: &FlowGraph) -> Vec<FlowError> {
+    let nodes = sorted_nodes(graph);
+
+    // For every node, the nodes that can run before it in the same conversation
+    let mut upstream_nodes: HashMap<&str, Vec<&Node>> = HashMap::new();
+    for node in nodes.iter() {
+        for reached in reachable_from(graph, successors(graph, node)) {
+            upstream_nodes.entry(reached).or_default().push(node);
+        }
+    }
+
+    let mut errors = Vec::new();
+    for node in nodes.iter() {
+        let mut available: HashSet<String> = PROVIDED_VARIABLES.iter().map(|v| v.to_string()).collect();
+        available.extend(graph.get_global_variables().keys().cloned());
+        available.extend(node.get_node_context().variables.keys().cloned());
+        available.extend(node.get_node_context().local.keys().cloned());
+
+        for upstream_node in upstream_nodes.get(node.id.as_str()).into_iter().flatten() {
+            for action in upstream_node.actions.iter() {
+                available.extend(action.declared_output_vars());
+            }
+        }
+
+        // Actions of a parallel group can't read what the others of the group write
+        for step in node.action_steps() {
+            for action in step {
+                let mut referenced = action.referenced_input_vars();
+                referenced.sort();
+
+                for variable in referenced {
+                    if !is_available(&available, &variable) {
+                        errors.push(FlowError::UndeclaredInputVar {
+                            node_id: node.id.clone(),
+                            action_id: action.id.clone(),
+                            variable,
+                        });
+                    }
+                }
+            }
+            available.extend(step.iter().flat_map(|action| action.declared_output_vars()));
+        }
+    }
+
+    errors
+}
+
+// Paths such as `crm.customer.orders[0].id` are available when a variable they start with is
+// Scope prefixes are ignored, a variable is available whatever scope it's written to
+fn is_available(available: &HashSet<String>, path: &str) -> bool {
+    let (_, path) = VariableScope::split_path(path);
+    available.contains(path)
+        || path
+            .char_indices()
+            .filter(|(_, c)| *c == '.' || *c == '[')
+            .any(|(index, _)| available.contains(&path[..index]))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value as JsonValue};
+
+    use crate::graph::{
+        action::{action_definition::ActionDefinition, parallel_group::OnConflict, tests::action_implementation::TestAction},
+        edge::edge::Edge,
+        node::node::Node,
+    };
+
+    use super::*;
+
+    fn node(id: &str, node_type: &str) -> Node {
+        Node::new(id.to_string(), node_type.to_string(), id.to_string(), "description".to_string())
+    }
+
+    fn terminal(id: &str) -> Node {
+        let mut node = node(id, "conversational");
+        node.set_terminal(true);
+        node
+    }
+
+    fn edge(id: &str, source: &str, target: &str) -> Edge {
+        Edge::new(id.to_string(), source.to_string(), target.to_string())
+    }
+
+    fn action(name: &str, input_vars: JsonValue, output_vars: JsonValue) -> ActionDefinition {
+        ActionDefinition::new(
+            name.to_string(),
+            name.to_string(),
+            "test_action".to_string(),
+            input_vars,
+            output_vars,
+            Box::new(TestAction::new(&JsonValue::Null)),
+        )
+    }
+
+    fn graph(nodes: Vec<Node>, edges: Vec<Edge>, start: Option<&str>) -> FlowGraph {
+        let mut graph = FlowGraph::new();
+        for node in nodes {
+            graph.add_node(node).unwrap();
+        }
+        for edge in edges {
+            graph.add_edge(edge).unwrap();
+        }
+        if let Some(start) = start {
+            graph.set_start_node(start.to_string()).unwrap();
+        }
+        graph
+    }
+
+    #[test]
+    fn test_valid_graph() {
+        let graph = graph(
+            vec![node("welcome", "conversational"), terminal("goodbye")],
+            vec![edge("welcome_to_goodbye", "welcome", "goodbye")],
+            Some("welcome"),
+        );
+
+        assert_eq!(graph.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_start_node() {
+        let graph = graph(vec![terminal("welcome")], vec![], None);
+
+        assert_eq!(graph.validate(), Err(vec![FlowError::NoStartNodes]));
+    }
+
+    #[test]
+    fn test_unreachable_nodes() {
+        let graph = graph(
+            vec![terminal("welcome"), terminal("orphan_b"), terminal("orphan_a")],
+            vec![],
+            Some("welcome"),
+        );
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![FlowError::UnreachableNodes(vec![
+                "orphan_a".to_string(),
+                "orphan_b".to_string()
+            ])])
+        );
+    }
+
+    #[test]
+    fn test_terminal_node_edges_are_never_taken() {
+        let graph = graph(
+            vec![terminal("welcome"), terminal("after_end")],
+            vec![edge("welcome_to_after_end", "welcome", "after_end")],
+            Some("welcome"),
+        );
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![FlowError::UnreachableNodes(vec!["after_end".to_string()])])
+        );
+    }
+
+    #[test]
+    fn test_dead_end_nodes() {
+        let graph = graph(
+            vec![node("welcome", "conversational"), node("stuck", "conversational")],
+            vec![edge("welcome_to_stuck", "welcome", "stuck")],
+            Some("welcome"),
+        );
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![FlowError::DeadEndNodes(vec!["stuck".to_string()])])
+        );
+    }
+
+    #[test]
+    fn test_fallback_node_prevents_dead_ends() {
+        let mut graph = graph(
+            vec![node("welcome", "conversational"), node("fallback", "conversational")],
+            vec![],
+            Some("welcome"),
+        );
+        graph.set_fallback_node("fallback".to_string()).unwrap();
+
+        assert_eq!(graph.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_cycle_without_user_input() {
+        let graph = graph(
+            vec![
+                node("welcome", "conversational"),
+                node("lookup", "integration"),
+                node("decide", "decision"),
+                terminal("goodbye"),
+            ],
+            vec![
+                edge("welcome_to_lookup", "welcome", "lookup"),
+                edge("lookup_to_decide", "lookup", "decide"),
+                edge("decide_to_lookup", "decide", "lookup"),
+                edge("decide_to_goodbye", "decide", "goodbye"),
+            ],
+            Some("welcome"),
+        );
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![FlowError::CycleDetected(vec![
+                "decide".to_string(),
+                "lookup".to_string()
+            ])])
+        );
+    }
+
+    #[test]
+    fn test_cycle_through_conversational_node_is_allowed() {
+        let graph = graph(
+            vec![node("welcome", "conversational"), node("lookup", "integration")],
+            vec![
+                edge("welcome_to_lookup", "welcome", "lookup"),
+                edge("lookup_to_welcome", "lookup", "welcome"),
+            ],
+            Some("welcome"),
+        );
+
+        assert_eq!(graph.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_undeclared_input_vars() {
+        let mut welcome = node("welcome", "conversational");
+        welcome.add_action_definition(action("ai_action", json!({}), json!(["messages"])));
+        welcome.add_action_definition(action(
+            "send_message",
+            json!({"messages": "ai_action.messages", "customer": "crm.customer"}),
+            json!([]),
+        ));
+
+        let mut goodbye = terminal("goodbye");
+        goodbye.add_action_definition(action(
+            "send_goodbye",
+            json!({"messages": "ai_action.messages", "history": "messages", "reply": "goodbye_ai.messages"}),
+            json!([]),
+        ));
+        goodbye.add_action_definition(action("goodbye_ai", json!({}), json!(["messages"])));
+
+        let graph = graph(
+            vec![welcome, goodbye],
+            vec![edge("welcome_to_goodbye", "welcome", "goodbye")],
+            Some("welcome"),
+        );
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![
+                FlowError::UndeclaredInputVar {
+                    node_id: "goodbye".to_string(),
+                    action_id: "send_goodbye".to_string(),
+                    variable: "goodbye_ai.messages".to_string(),
+                },
+                FlowError::UndeclaredInputVar {
+                    node_id: "welcome".to_string(),
+                    action_id: "send_message".to_string(),
+                    variable: "crm.customer".to_string(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parallel_actions_cannot_read_each_other() {
+        let mut welcome = terminal("welcome");
+        welcome.add_parallel_actions(
+            vec![
+                action("crm", json!({}), json!(["customer"])),
+                action("kb", json!({"customer": "crm.customer"}), json!(["answer"])),
+            ],
+            OnConflict::Fail,
+        );
+        welcome.add_action_definition(action("reply", json!({"answer": "kb.answer"}), json!([])));
+
+        let graph = graph(vec![welcome], vec![], Some("welcome"));
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![FlowError::UndeclaredInputVar {
+                node_id: "welcome".to_string(),
+                action_id: "kb".to_string(),
+                variable: "crm.customer".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_input_var_paths_into_declared_variables() {
+        let mut welcome = terminal("welcome");
+        welcome.add_action_definition(action("crm", json!({}), json!(["customer"])));
+        welcome.add_action_definition(action(
+            "reply",
+            json!({
+                "order_id": "crm.customer.orders[0].id",
+                "notes": "crm.notes?",
+                "language": { "path": "user.language", "default": "en" },
+                "channel": { "literal": "whatsapp" },
+                "summary": "crm.summary",
+            }),
+            json!([]),
+        ));
+
+        let graph = graph(vec![welcome], vec![], Some("welcome"));
+
+        assert_eq!(
+            graph.validate(),
+            Err(vec![FlowError::UndeclaredInputVar {
+                node_id: "welcome".to_string(),
+                action_id: "reply".to_string(),
+                variable: "crm.summary".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_reports_every_problem_at_once() {
+        let graph = graph(
+            vec![node("welcome", "conversational"), node("orphan", "conversational")],
+            vec![],
+            None,
+        );
+
+        let errors = graph.validate().unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                FlowError::NoStartNodes,
+                FlowError::DeadEndNodes(vec!["orphan".to_string(), "welcome".to_string()]),
+            ]
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub mod flow_graph;
 mod flow_graph_builder;
+mod flow_graph_validator;
@@ -0,0 +1,264 @@
+use std::{error::Error, fmt};
+
+use serde::Serialize;
+use serde_json::error::Category;
+
+/// Machine readable reason a flow could not be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowLoadErrorKind {
+    InvalidJson,
+    MissingField,
+    InvalidFieldType,
+    InvalidFieldValue,
+    UnknownActionType,
+    UnknownConditionType,
+    InvalidExpression,
+    DuplicateNode,
+    DuplicateEdge,
+    // Two actions of a node share a config id, and so the vars they output
+    DuplicateAction,
+    // An action or condition constructor could not set up what it needs
+    ConstructionFailed,
+}
+
+/// Error raised while loading a flow from json. It names the element that could not be loaded
+/// and the json path of the offending field, e.g. `nodes[2].actions[1].config.model`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct FlowLoadError {
+    // Boxed to keep the `Result`s of the loading functions small
+    details: Box<FlowLoadErrorDetails>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct FlowLoadErrorDetails {
+    kind: FlowLoadErrorKind,
+    path: String,
+    node_id: Option<String>,
+    edge_id: Option<String>,
+    // Position of the action in its node and its config id, when it has one
+    action_index: Option<usize>,
+    action_id: Option<String>,
+    // Position of the condition in its edge
+    condition_index: Option<usize>,
+    reason: String,
+}
+
+impl FlowLoadError {
+    pub fn new(kind: FlowLoadErrorKind, reason: String) -> Self {
+        FlowLoadError {
+            details: Box::new(FlowLoadErrorDetails {
+                kind,
+                path: String::new(),
+                node_id: None,
+                edge_id: None,
+                action_index: None,
+                action_id: None,
+                condition_index: None,
+                reason,
+            }),
+        }
+    }
+
+    /// Sets the path of the offending field, relative to the element being loaded
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.details.path = path.to_string();
+        self
+    }
+
+    /// Places the path under a parent, `actions` + `[1].config` gives `actions[1].config`
+    pub fn with_path_prefix(mut self, prefix: &str) -> Self {
+        let path = &self.details.path;
+        self.details.path = if path.is_empty() {
+            prefix.to_string()
+        } else if path.starts_with('[') {
+            format!("{}{}", prefix, path)
+        } else {
+            format!("{}.{}", prefix, path)
+        };
+        self
+    }
+
+    pub fn with_node(mut self, node_id: Option<String>) -> Self {
+        self.details.node_id = node_id;
+        self
+    }
+
+    pub fn with_edge(mut self, edge_id: Option<String>) -> Self {
+        self.details.edge_id = edge_id;
+        self
+    }
+
+    pub fn with_action(mut self, action_index: usize, action_id: Option<String>) -> Self {
+        self.details.action_index = Some(action_index);
+        self.details.action_id = action_id;
+        self
+    }
+
+    pub fn with_condition(mut self, condition_index: usize) -> Self {
+        self.details.condition_index = Some(condition_index);
+        self
+    }
+
+    pub fn kind(&self) -> FlowLoadErrorKind {
+        self.details.kind
+    }
+
+    pub fn path(&self) -> &str {
+        &self.details.path
+    }
+
+    pub fn node_id(&self) -> Option<&str> {
+        self.details.node_id.as_deref()
+    }
+
+    pub fn edge_id(&self) -> Option<&str> {
+        self.details.edge_id.as_deref()
+    }
+
+    pub fn action_index(&self) -> Option<usize> {
+        self.details.action_index
+    }
+
+    pub fn action_id(&self) -> Option<&str> {
+        self.details.action_id.as_deref()
+    }
+
+    pub fn condition_index(&self) -> Option<usize> {
+        self.details.condition_index
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.details.reason
+    }
+}
+
+impl fmt::Display for FlowLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut location = Vec::new();
+
+        if let Some(node_id) = self.node_id() {
+            location.push(format!("node {}", node_id));
+        }
+        if let Some(edge_id) = self.edge_id() {
+            location.push(format!("edge {}", edge_id));
+        }
+        match (self.action_index(), self.action_id()) {
+            (Some(index), Some(action_id)) => location.push(format!("action {} ({})", index, action_id)),
+            (Some(index), None) => location.push(format!("action {}", index)),
+            _ => {}
+        }
+        if let Some(index) = self.condition_index() {
+            location.push(format!("condition {}", index));
+        }
+
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(", "))?;
+        }
+        write!(f, "{}", self.reason())?;
+        if !self.path().is_empty() {
+            write!(f, " (at {})", self.path())?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for FlowLoadError {}
+
+impl From<serde_json::Error> for FlowLoadError {
+    fn from(error: serde_json::Error) -> Self {
+        let reason = error.to_string();
+
+        // serde reports missing fields as "missing field `name` at line 1 column 2"
+        let missing_field = reason
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.split('`').next())
+            .map(str::to_string);
+
+        match (error.classify(), missing_field) {
+            (Category::Data, Some(field)) => {
+                FlowLoadError::new(FlowLoadErrorKind::MissingField, reason).with_path(&field)
+            }
+            (Category::Data, None) => FlowLoadError::new(FlowLoadErrorKind::InvalidFieldType, reason),
+            _ => FlowLoadError::new(FlowLoadErrorKind::InvalidJson, reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_display_names_the_element() {
+        let error = FlowLoadError::new(FlowLoadErrorKind::UnknownConditionType, "Unknown condition type: foo".to_string())
+            .with_path("[1].condition_type")
+            .with_path_prefix("conditions")
+            .with_edge(Some("welcome_to_help".to_string()))
+            .with_condition(1);
+
+        assert_eq!(
+            error.to_string(),
+            "edge welcome_to_help, condition 1: Unknown condition type: foo (at conditions[1].condition_type)"
+        );
+
+        let error = FlowLoadError::new(FlowLoadErrorKind::MissingField, "Action config id is required".to_string())
+            .with_node(Some("welcome".to_string()))
+            .with_action(0, None);
+
+        assert_eq!(error.to_string(), "node welcome, action 0: Action config id is required");
+    }
+
+    #[test]
+    fn test_path_prefixes() {
+        let error = FlowLoadError::new(FlowLoadErrorKind::MissingField, "missing".to_string())
+            .with_path("config.model")
+            .with_path_prefix("[1]")
+            .with_path_prefix("actions")
+            .with_path_prefix("nodes[2]");
+
+        assert_eq!(error.path(), "nodes[2].actions[1].config.model");
+    }
+
+    #[test]
+    fn test_from_serde_error() {
+        let missing_field: Result<HashMapWithId, _> = serde_json::from_str("{}");
+        let error = FlowLoadError::from(missing_field.unwrap_err());
+        assert_eq!(error.kind(), FlowLoadErrorKind::MissingField);
+        assert_eq!(error.path(), "id");
+
+        let invalid_json: Result<HashMapWithId, _> = serde_json::from_str("{");
+        assert_eq!(FlowLoadError::from(invalid_json.unwrap_err()).kind(), FlowLoadErrorKind::InvalidJson);
+    }
+
+    #[test]
+    fn test_serializes_for_flow_editors() {
+        let error = FlowLoadError::new(FlowLoadErrorKind::UnknownActionType, "Unknown action type: foo".to_string())
+            .with_path("nodes[0].actions[0].action_type")
+            .with_node(Some("welcome".to_string()))
+            .with_action(0, Some("foo".to_string()));
+
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "kind": "unknown_action_type",
+                "path": "nodes[0].actions[0].action_type",
+                "node_id": "welcome",
+                "edge_id": null,
+                "action_index": 0,
+                "action_id": "foo",
+                "condition_index": null,
+                "reason": "Unknown action type: foo"
+            })
+        );
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct HashMapWithId {
+        id: String,
+    }
+}
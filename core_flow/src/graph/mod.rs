@@ -2,4 +2,9 @@ pub mod flow_graph;
 pub mod node;
 pub mod edge;
 pub mod action;
-pub mod condition;
\ No newline at end of file
+pub mod condition;
+pub mod template;
+pub mod catalog;
+pub mod config_error;
+pub mod config_schema;
+pub mod flow_load_error;
@@ -0,0 +1,154 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+// Enough for any amount of money, and still exact once rescaled for comparisons
+const MAX_SCALE: u32 = 18;
+
+/// Exact decimal number such as an amount of money, `mantissa * 10^-scale`.
+/// Trailing zeros are dropped, so `12.50` and `12.5` are the same decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseDecimalError(pub String);
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid decimal {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+impl Decimal {
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        let (mut mantissa, mut scale) = (mantissa, scale);
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        Decimal { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Decimal { mantissa: self.mantissa.checked_neg()?, scale: self.scale })
+    }
+
+    // Mantissa at a larger scale, none on overflow
+    fn rescaled(&self, scale: u32) -> Option<i128> {
+        self.mantissa.checked_mul(10i128.checked_pow(scale - self.scale)?)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(integer: i64) -> Self {
+        Decimal::new(integer as i128, 0)
+    }
+}
+
+impl From<u64> for Decimal {
+    fn from(integer: u64) -> Self {
+        Decimal::new(integer as i128, 0)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseDecimalError(text.to_string());
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        if integer.is_empty() || !integer.bytes().chain(fraction.bytes()).all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > MAX_SCALE as usize {
+            return Err(invalid());
+        }
+
+        let mantissa: i128 = format!("{}{}", integer, fraction).parse().map_err(|_| invalid())?;
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Ok(Decimal::new(mantissa, fraction.len() as u32))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.mantissa);
+        }
+
+        let digits = format!("{:0>width$}", self.mantissa.unsigned_abs(), width = self.scale as usize + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - self.scale as usize);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        write!(f, "{}{}.{}", sign, integer, fraction)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        match (self.rescaled(scale), other.rescaled(scale)) {
+            (Some(left), Some(right)) => left.cmp(&right),
+            // Only huge numbers overflow, at that size the float comparison is close enough
+            _ => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(text: &str) -> Decimal {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(decimal("12.50"), Decimal::new(125, 1));
+        assert_eq!(decimal("-0.05").to_string(), "-0.05");
+        assert_eq!(decimal("+7").to_string(), "7");
+        assert_eq!(decimal("100.00").to_string(), "100");
+        assert!("1e3".parse::<Decimal>().is_err());
+        assert!(".5".parse::<Decimal>().is_err());
+        assert!("1.0000000000000000001".parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn test_exact_comparisons() {
+        assert!(decimal("0.3") > decimal("0.29999999999999999"));
+        assert_eq!(decimal("19.99").cmp(&Decimal::from(20i64)), Ordering::Less);
+        assert_eq!(decimal("9007199254740993"), Decimal::from(9_007_199_254_740_993i64));
+        assert_eq!(decimal("2.5").to_f64(), 2.5);
+    }
+}
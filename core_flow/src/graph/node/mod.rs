@@ -1,3 +1,5 @@
+pub mod attachment;
+pub mod decimal;
 pub mod node_context;
 pub mod node;
-mod node_builder;
\ No newline at end of file
+mod node_builder;
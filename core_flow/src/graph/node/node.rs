@@ -1,14 +1,25 @@
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
 
 use crate::graph::action::action::{Action};
+use crate::graph::action::action_definition::ActionDefinition;
+use crate::graph::action::action_policy::OnError;
+use crate::graph::action::parallel_group::{ActionOutput, OnConflict, ParallelGroup};
 use crate::graph::action::action_registry::ActionRegistry;
 use crate::graph::action::utils::action_deserializer::deserialize_actions;
+use crate::graph::condition::condition_registry::ConditionRegistry;
+use crate::graph::flow_load_error::FlowLoadError;
 
 use super::node_builder::NodeBuilder;
 use super::node_context::{NodeContext, Value};
 
+pub const CONVERSATIONAL_NODE_TYPE: &str = "conversational";
+// Set in the transient scope when an action failed with the route policy, holds the action id and the failure reason.
+// The node then leaves through its on_error edges.
+pub const ACTION_ERROR_VARIABLE: &str = "action_error";
+
 /// Represents a node in the conversation flow
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Node {
@@ -17,8 +28,11 @@ pub struct Node {
     pub name: String,
     pub description: String,
     node_context: NodeContext,
+    // Terminal nodes end the conversation, they are allowed to have no outgoing edges
+    #[serde(default)]
+    pub terminal: bool,
     #[serde(skip)]
-    pub actions: Vec<Box<dyn Action>>, // Actions to perform
+    pub actions: Vec<ActionDefinition>, // Actions to perform
 }
 
 impl Node {
@@ -29,6 +43,7 @@ impl Node {
             name,
             description,
             node_context: NodeContext::new(),
+            terminal: false,
             actions: Vec::new(),
         }
     }
@@ -36,13 +51,17 @@ impl Node {
     pub fn from_json(
         json: &str,
         action_registry: &ActionRegistry,
-    ) -> Result<Self, serde_json::Error> {
-        let mut node: Node = serde_json::from_str(json)?;
-
+        condition_registry: &ConditionRegistry,
+    ) -> Result<Self, FlowLoadError> {
         let json_map: HashMap<String, serde_json::Value> = serde_json::from_str(json)?;
+        let node_id = json_map.get("id").and_then(|id| id.as_str()).map(str::to_string);
+
+        let mut node: Node = serde_json::from_str(json)
+            .map_err(|error| FlowLoadError::from(error).with_node(node_id.clone()))?;
 
         if let Some(actions_value) = json_map.get("actions") {
-            let actions = deserialize_actions(actions_value.to_string().as_str(), action_registry)?;
+            let actions = deserialize_actions(actions_value.to_string().as_str(), action_registry, condition_registry)
+                .map_err(|error| error.with_path_prefix("actions").with_node(node_id))?;
             node.actions = actions;
         }
         Ok(node)
@@ -61,8 +80,41 @@ impl Node {
         self.node_context = node_context;
     }
 
+    pub fn set_terminal(&mut self, terminal: bool) {
+        self.terminal = terminal;
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.terminal
+    }
+
+    /// Conversational nodes wait for the next user message once their actions ran
+    pub fn awaits_input(&self) -> bool {
+        self.node_type == CONVERSATIONAL_NODE_TYPE
+    }
+
     pub fn add_action(&mut self, action: Box<dyn Action>) {
-        self.actions.push(action);
+        let action_id = format!("action_{}", self.actions.len());
+        self.actions.push(ActionDefinition::from_action(action_id, action));
+    }
+
+    pub fn add_action_definition(&mut self, action_definition: ActionDefinition) {
+        self.actions.push(action_definition);
+    }
+
+    /// Adds actions that run concurrently, their outputs are merged following the conflict rule
+    pub fn add_parallel_actions(&mut self, action_definitions: Vec<ActionDefinition>, on_conflict: OnConflict) {
+        let parallel_group = ParallelGroup::new(self.actions.len(), on_conflict);
+        for action_definition in action_definitions {
+            self.actions.push(action_definition.with_parallel_group(parallel_group));
+        }
+    }
+
+    /// Actions in execution order, a step holds one action or a whole parallel group
+    pub fn action_steps(&self) -> impl Iterator<Item = &[ActionDefinition]> {
+        self.actions.chunk_by(|previous, next| {
+            previous.parallel_group.is_some() && previous.parallel_group == next.parallel_group
+        })
     }
 
     pub fn set_var_context(&mut self, key: String, value: Value) {
@@ -77,16 +129,143 @@ impl Node {
         &self.node_context
     }
 
-    pub async fn execute_actions(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut new_context = self.node_context.clone();
-        for action in self.actions.iter() {
-            new_context = action.execute(&mut new_context).await?;
+    /// Runs the node actions against a conversation context and returns the resulting context.
+    /// The node itself is never modified, its own variables only act as defaults for the
+    /// ones the conversation does not define yet. The local scope starts from the node's own
+    /// local variables, whatever a previous node left there is dropped.
+    pub async fn execute_actions(
+        &self,
+        context: NodeContext,
+    ) -> Result<NodeContext, Box<dyn std::error::Error>> {
+        let mut new_context = context;
+        new_context.local = self.node_context.local.clone();
+        for (key, value) in self.node_context.variables.iter() {
+            new_context
+                .variables
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
         }
-        self.node_context = new_context;
-        Ok(())
+
+        for step in self.action_steps() {
+            let routed = match step {
+                [action] => execute_action(action, &mut new_context).await?,
+                group => execute_parallel_group(group, &mut new_context).await?,
+            };
+            if routed {
+                break;
+            }
+        }
+        Ok(new_context)
     }
 }
 
+// Runs a single action, returns whether the node must leave through its on_error edges
+async fn execute_action(
+    action: &ActionDefinition,
+    context: &mut NodeContext,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    // Errors of a previous run must not be mistaken for this one
+    context.variables.remove(&action.error_variable());
+    if !action.should_run(context).await {
+        return Ok(false);
+    }
+
+    match action.execute(context).await {
+        Ok(new_context) => {
+            *context = new_context;
+            Ok(false)
+        }
+        Err(error) => handle_action_error(action, error, context),
+    }
+}
+
+// Runs the actions of a group concurrently, each on its own copy of the context, then merges
+// what they wrote in declaration order
+async fn execute_parallel_group(
+    group: &[ActionDefinition],
+    context: &mut NodeContext,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    for action in group {
+        context.variables.remove(&action.error_variable());
+    }
+
+    let base = context.clone();
+    // Errors are turned into their message, boxed errors aren't Send and the results are
+    // held until every action of the group finished
+    let base_context = &base;
+    let results = join_all(group.iter().map(|action| async move {
+        // A skipped action leaves the context untouched, so it writes nothing
+        if !action.should_run(base_context).await {
+            return Ok(base_context.clone());
+        }
+        let mut action_context = base_context.clone();
+        action.execute(&mut action_context).await.map_err(|error| error.to_string())
+    }))
+    .await;
+
+    let mut outputs = Vec::new();
+    let mut routed_action = None;
+    for (action, result) in group.iter().zip(results) {
+        let mut action_context = base.clone();
+        match result {
+            Ok(new_context) => action_context = new_context,
+            Err(error) => {
+                let routed = handle_action_error(action, error.into(), &mut action_context)?;
+                // The route marker is set once for the whole group, by its first routed action
+                if routed && routed_action.is_none() {
+                    routed_action = action_context.transient.remove(ACTION_ERROR_VARIABLE);
+                }
+            }
+        }
+        outputs.push(ActionOutput::new(action.id.clone(), &base, action_context));
+    }
+
+    let parallel_group = group[0].parallel_group.unwrap_or_default();
+    let mut merged = parallel_group.merge(base, outputs)?;
+    let routed = routed_action.is_some();
+    if let Some(action_error) = routed_action {
+        merged.transient.insert(ACTION_ERROR_VARIABLE.to_string(), action_error);
+    }
+
+    *context = merged;
+    Ok(routed)
+}
+
+// Applies the error policy of a failed action, returns whether the node must leave through
+// its on_error edges
+fn handle_action_error(
+    action: &ActionDefinition,
+    error: Box<dyn std::error::Error>,
+    context: &mut NodeContext,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    match action.policy.on_error {
+        OnError::Fail => Err(error),
+        OnError::Continue => {
+            context
+                .variables
+                .insert(action.error_variable(), Value::String(error.to_string()));
+            Ok(false)
+        }
+        OnError::Route => {
+            context
+                .variables
+                .insert(action.error_variable(), Value::String(error.to_string()));
+            context.transient.insert(
+                ACTION_ERROR_VARIABLE.to_string(),
+                action_error(action, Value::String(error.to_string())),
+            );
+            Ok(true)
+        }
+    }
+}
+
+fn action_error(action: &ActionDefinition, message: Value) -> Value {
+    Value::Map(HashMap::from([
+        ("action_id".to_string(), Value::String(action.id.clone())),
+        ("message".to_string(), message),
+    ]))
+}
+
 // enum NodeContext {
 //     MessageNode,
 //     InputNode,
@@ -103,7 +282,9 @@ mod tests {
 
     // Tests the actions behaviour of the node
     mod given_certain_actions {
-        use crate::graph::action::tests::action_implementation::TestAction;
+        use crate::flow::conversation::Message;
+        use crate::graph::action::{action_policy::ActionPolicy, tests::action_implementation::TestAction};
+        use crate::graph::condition::tests::condition_implementation::{NegativeCondition, PositiveCondition};
 
         use super::*;
 
@@ -139,7 +320,7 @@ mod tests {
 
             node.add_action(TestAction::new(&JsonValue::Null).clone_box());
 
-            node.execute_actions().await.unwrap();
+            node.execute_actions(NodeContext::new()).await.unwrap();
         }
 
         #[tokio::test]
@@ -154,7 +335,7 @@ mod tests {
             node.add_action(TestAction::new(&JsonValue::Null).clone_box());
             node.add_action(FailTestAction::new().clone_box());
 
-            match node.execute_actions().await {
+            match node.execute_actions(NodeContext::new()).await {
                 Ok(_) => {
                     panic!("Expected an error");
                 }
@@ -164,6 +345,236 @@ mod tests {
             }
         }
 
+        fn create_node_with_failing_action(on_error: OnError) -> Node {
+            let mut node = Node::new(
+                "welcome".to_string(),
+                "message".to_string(),
+                "Welcome".to_string(),
+                "Welcome message".to_string(),
+            );
+            node.actions.push(
+                ActionDefinition::from_action("lookup".to_string(), FailTestAction::new().clone_box())
+                    .with_policy(ActionPolicy::default().with_on_error(on_error)),
+            );
+            node.add_action(TestAction::new(&JsonValue::Null).clone_box());
+            node
+        }
+
+        #[tokio::test]
+        async fn test_continues_after_an_error_when_the_policy_allows_it() {
+            let node = create_node_with_failing_action(OnError::Continue);
+
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
+
+            assert_eq!(context.variables.get("lookup.error"), Some(&Value::String("Action failed".to_string())));
+            assert!(context.variables.contains_key("test_var"));
+            assert!(!context.transient.contains_key(ACTION_ERROR_VARIABLE));
+        }
+
+        #[tokio::test]
+        async fn test_stops_and_marks_the_error_when_routing() {
+            let node = create_node_with_failing_action(OnError::Route);
+
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
+
+            assert!(!context.variables.contains_key("test_var"));
+            let Some(Value::Map(action_error)) = context.transient.get(ACTION_ERROR_VARIABLE) else {
+                panic!("Expected the action error to be recorded");
+            };
+            assert_eq!(action_error.get("action_id"), Some(&Value::String("lookup".to_string())));
+        }
+
+        #[derive(Clone)]
+        struct WriteAction {
+            variable: String,
+            value: String,
+            delay_ms: u64,
+        }
+
+        #[async_trait]
+        impl Action for WriteAction {
+            async fn execute(
+                &self,
+                context: &mut NodeContext,
+            ) -> Result<NodeContext, Box<dyn std::error::Error>> {
+                tokio::time::sleep(std::time::Duration::from_millis(self.delay_ms)).await;
+                context.variables.insert(self.variable.clone(), Value::String(self.value.clone()));
+                Ok(context.clone())
+            }
+            fn clone_box(&self) -> Box<dyn Action> {
+                Box::new(self.clone())
+            }
+        }
+
+        fn write_action(id: &str, variable: &str, delay_ms: u64) -> ActionDefinition {
+            let action = WriteAction { variable: variable.to_string(), value: id.to_string(), delay_ms };
+            ActionDefinition::from_action(id.to_string(), action.clone_box())
+        }
+
+        fn create_node_with_parallel_actions(on_conflict: OnConflict, variables: [&str; 2]) -> Node {
+            let mut node = Node::new(
+                "welcome".to_string(),
+                "message".to_string(),
+                "Welcome".to_string(),
+                "Welcome message".to_string(),
+            );
+            // The slower action is declared first, the merge must not depend on completion order
+            node.add_parallel_actions(
+                vec![write_action("crm", variables[0], 30), write_action("kb", variables[1], 0)],
+                on_conflict,
+            );
+            node.add_action(TestAction::new(&JsonValue::Null).clone_box());
+            node
+        }
+
+        #[tokio::test]
+        async fn test_runs_parallel_actions_concurrently() {
+            let node = create_node_with_parallel_actions(OnConflict::Fail, ["crm.customer", "kb.answer"]);
+
+            assert_eq!(node.action_steps().map(|step| step.len()).collect::<Vec<_>>(), vec![2, 1]);
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
+
+            assert_eq!(context.variables.get("crm.customer"), Some(&Value::String("crm".to_string())));
+            assert_eq!(context.variables.get("kb.answer"), Some(&Value::String("kb".to_string())));
+            assert!(context.variables.contains_key("test_var"));
+        }
+
+        #[tokio::test]
+        async fn test_applies_the_conflict_rule_of_parallel_actions() {
+            let node = create_node_with_parallel_actions(OnConflict::Fail, ["summary", "summary"]);
+            let error = node.execute_actions(NodeContext::new()).await.unwrap_err();
+            assert_eq!(error.to_string(), "Parallel actions crm and kb both write summary");
+
+            let node = create_node_with_parallel_actions(OnConflict::FirstWins, ["summary", "summary"]);
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
+            assert_eq!(context.variables.get("summary"), Some(&Value::String("crm".to_string())));
+
+            let node = create_node_with_parallel_actions(OnConflict::LastWins, ["summary", "summary"]);
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
+            assert_eq!(context.variables.get("summary"), Some(&Value::String("kb".to_string())));
+        }
+
+        #[tokio::test]
+        async fn test_keeps_the_messages_of_every_parallel_action() {
+            let json = r#"{
+                "id": "welcome",
+                "node_type": "conversational",
+                "name": "Welcome",
+                "description": "Welcome message",
+                "node_context": {
+                    "variables": {}
+                },
+                "actions": [
+                    {
+                        "parallel": [
+                            {
+                                "config": { "name": "greeting", "id": "greeting", "template": "Hi!" },
+                                "input_vars": {},
+                                "output_vars": ["messages"],
+                                "action_type": "templated_message"
+                            },
+                            {
+                                "config": { "name": "status", "id": "status", "template": "Your order shipped" },
+                                "input_vars": {},
+                                "output_vars": ["messages"],
+                                "action_type": "templated_message"
+                            }
+                        ]
+                    }
+                ]
+            }"#;
+            let mut action_registry = ActionRegistry::new();
+            action_registry.register_builtin_actions();
+            let node = Node::from_json(json, &action_registry, &ConditionRegistry::new()).unwrap();
+
+            let trigger_message = Message::new("+34600000000".to_string(), "hello".to_string(), "ai".to_string());
+            let mut context = NodeContext::new();
+            context.transient.insert("messages".to_string(), Value::Messages(vec![trigger_message.clone()]));
+            context.transient.insert("trigger_message".to_string(), Value::Messages(vec![trigger_message]));
+
+            let context = node.execute_actions(context).await.unwrap();
+
+            let texts: Vec<&str> = context.transient["messages"]
+                .as_messages()
+                .unwrap()
+                .iter()
+                .filter_map(|message| message.content.text())
+                .collect();
+            assert_eq!(texts, ["hello", "Hi!", "Your order shipped"]);
+        }
+
+        #[tokio::test]
+        async fn test_routes_when_a_parallel_action_fails() {
+            let mut node = Node::new(
+                "welcome".to_string(),
+                "message".to_string(),
+                "Welcome".to_string(),
+                "Welcome message".to_string(),
+            );
+            node.add_parallel_actions(
+                vec![
+                    write_action("crm", "crm.customer", 0),
+                    ActionDefinition::from_action("lookup".to_string(), FailTestAction::new().clone_box())
+                        .with_policy(ActionPolicy::default().with_on_error(OnError::Route)),
+                ],
+                OnConflict::Fail,
+            );
+            node.add_action(TestAction::new(&JsonValue::Null).clone_box());
+
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
+
+            assert!(context.variables.contains_key("crm.customer"));
+            assert!(context.variables.contains_key("lookup.error"));
+            assert!(context.transient.contains_key(ACTION_ERROR_VARIABLE));
+            assert!(!context.variables.contains_key("test_var"));
+        }
+
+        #[tokio::test]
+        async fn test_skips_actions_whose_when_clause_does_not_hold() {
+            let mut node = Node::new(
+                "welcome".to_string(),
+                "message".to_string(),
+                "Welcome".to_string(),
+                "Welcome message".to_string(),
+            );
+            node.add_action_definition(
+                write_action("skipped", "skipped.reply", 0).with_when(vec![Box::new(NegativeCondition {})]),
+            );
+            node.add_action_definition(
+                write_action("run", "run.reply", 0).with_when(vec![Box::new(PositiveCondition {})]),
+            );
+            node.add_parallel_actions(
+                vec![
+                    write_action("crm", "summary", 0).with_when(vec![Box::new(NegativeCondition {})]),
+                    write_action("kb", "summary", 0),
+                ],
+                OnConflict::Fail,
+            );
+
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
+
+            assert!(!context.variables.contains_key("skipped.reply"));
+            assert!(context.variables.contains_key("run.reply"));
+            assert_eq!(context.variables.get("summary"), Some(&Value::String("kb".to_string())));
+        }
+
+        #[tokio::test]
+        async fn test_local_scope_starts_from_the_node_locals() {
+            let mut node = Node::new(
+                "welcome".to_string(),
+                "message".to_string(),
+                "Welcome".to_string(),
+                "Welcome message".to_string(),
+            );
+            node.node_context.local.insert("attempts".to_string(), Value::Number(0.0));
+            let mut context = NodeContext::new();
+            context.local.insert("previous_node_draft".to_string(), Value::Null);
+
+            let context = node.execute_actions(context).await.unwrap();
+
+            assert_eq!(context.local, HashMap::from([("attempts".to_string(), Value::Number(0.0))]));
+        }
+
         #[tokio::test]
         async fn test_correctly_modifies_the_node_context() {
             let mut node = Node::new(
@@ -174,15 +585,61 @@ mod tests {
             );
             node.add_action(TestAction::new(&JsonValue::Null).clone_box());
 
-            node.execute_actions().await.unwrap();
+            let context = node.execute_actions(NodeContext::new()).await.unwrap();
 
-            let test_var = node.get_var_context("test_var".to_string());
+            let test_var = context.variables.get("test_var").cloned();
             assert_eq!(test_var.unwrap(), Value::String("test_value".to_string()));
         }
+
+        #[tokio::test]
+        async fn test_does_not_modify_the_node_itself() {
+            let mut node = Node::new(
+                "welcome".to_string(),
+                "message".to_string(),
+                "Welcome".to_string(),
+                "Welcome message".to_string(),
+            );
+            node.add_action(TestAction::new(&JsonValue::Null).clone_box());
+
+            node.execute_actions(NodeContext::new()).await.unwrap();
+
+            assert_eq!(node.get_var_context("test_var".to_string()), None);
+            assert!(node.get_node_context().variables.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_node_variables_act_as_defaults() {
+            let mut node = Node::new(
+                "welcome".to_string(),
+                "message".to_string(),
+                "Welcome".to_string(),
+                "Welcome message".to_string(),
+            );
+            node.set_var_context("greeting".to_string(), Value::String("hello".to_string()));
+            node.set_var_context("language".to_string(), Value::String("en".to_string()));
+
+            let mut conversation_context = NodeContext::new();
+            conversation_context
+                .variables
+                .insert("language".to_string(), Value::String("es".to_string()));
+
+            let context = node.execute_actions(conversation_context).await.unwrap();
+
+            assert_eq!(
+                context.variables.get("greeting"),
+                Some(&Value::String("hello".to_string()))
+            );
+            assert_eq!(
+                context.variables.get("language"),
+                Some(&Value::String("es".to_string()))
+            );
+        }
     }
 
     mod given_json {
         use crate::graph::action::tests::action_implementation::create_test_action;
+        use crate::graph::config_error::ConfigError;
+        use crate::graph::flow_load_error::FlowLoadErrorKind;
 
         use super::*;
 
@@ -215,13 +672,140 @@ mod tests {
                 create_test_action
             );
 
-            let node = Node::from_json(json, &action_registry).unwrap();
+            let node = Node::from_json(json, &action_registry, &ConditionRegistry::new()).unwrap();
 
             assert_eq!(node.id, "welcome");
             assert_eq!(node.node_type, "conversational");
             assert_eq!(node.name, "Welcome");
             assert_eq!(node.description, "Welcome message");
             assert_eq!(node.actions.len(), 1);
+            assert_eq!(node.actions[0].id, "test_action");
+            assert_eq!(node.actions[0].action_type, "test_action");
+            assert!(!node.is_terminal());
+        }
+
+        #[test]
+        fn test_from_json_fails_with_unknown_action_type() {
+            let json = r#"{
+                "id": "welcome",
+                "node_type": "conversational",
+                "name": "Welcome",
+                "description": "Welcome message",
+                "node_context": {
+                    "variables": {}
+                },
+                "actions": [
+                    {
+                        "config": {
+                            "name": "unknown",
+                            "id": "unknown_action"
+                        },
+                        "input_vars": {},
+                        "output_vars": [],
+                        "action_type": "unknown_action"
+                    }
+                ]
+            }"#;
+
+            let error = Node::from_json(json, &ActionRegistry::new(), &ConditionRegistry::new()).unwrap_err();
+
+            assert_eq!(error.node_id(), Some("welcome"));
+            assert_eq!(error.action_index(), Some(0));
+            assert_eq!(error.action_id(), Some("unknown_action"));
+            assert_eq!(error.kind(), FlowLoadErrorKind::UnknownActionType);
+            assert_eq!(error.path(), "actions[0].action_type");
+            assert_eq!(error.reason(), "Unknown action type: unknown_action");
+        }
+
+        #[test]
+        fn test_from_json_fails_when_the_constructor_rejects_the_config() {
+            let json = r#"{
+                "id": "welcome",
+                "node_type": "conversational",
+                "name": "Welcome",
+                "description": "Welcome message",
+                "node_context": {
+                    "variables": {}
+                },
+                "actions": [
+                    {
+                        "config": {
+                            "name": "ai",
+                            "id": "ai"
+                        },
+                        "input_vars": {},
+                        "output_vars": [],
+                        "action_type": "ai_action"
+                    }
+                ]
+            }"#;
+
+            let mut action_registry = ActionRegistry::new();
+            action_registry.register_action("ai_action", |_, _, _| Err(ConfigError::MissingField("model".to_string())));
+
+            let error = Node::from_json(json, &action_registry, &ConditionRegistry::new()).unwrap_err();
+
+            assert_eq!(error.kind(), FlowLoadErrorKind::MissingField);
+            assert_eq!(error.path(), "actions[0].config.model");
+            assert_eq!(error.action_id(), Some("ai"));
+        }
+
+        fn node_json(actions: &str) -> String {
+            format!(r#"{{
+                "id": "welcome",
+                "node_type": "conversational",
+                "name": "Welcome",
+                "description": "Welcome message",
+                "node_context": {{
+                    "variables": {{}}
+                }},
+                "actions": {}
+            }}"#, actions)
+        }
+
+        fn test_action_json(id: &str) -> String {
+            format!(r#"{{
+                "config": {{ "name": "test_action", "id": {} }},
+                "input_vars": {{}},
+                "output_vars": [],
+                "action_type": "test_action"
+            }}"#, id)
+        }
+
+        fn load_error(actions: &str) -> FlowLoadError {
+            let mut action_registry = ActionRegistry::new();
+            action_registry.register_action("test_action", create_test_action);
+
+            Node::from_json(&node_json(actions), &action_registry, &ConditionRegistry::new()).unwrap_err()
+        }
+
+        #[test]
+        fn test_from_json_fails_with_duplicate_action_ids() {
+            let error = load_error(&format!("[{}, {}]", test_action_json(r#""greeting""#), test_action_json(r#""greeting""#)));
+
+            assert_eq!(error.kind(), FlowLoadErrorKind::DuplicateAction);
+            assert_eq!(error.path(), "actions[1].config.id");
+            assert_eq!(error.action_id(), Some("greeting"));
+
+            let error = load_error(&format!(
+                r#"[{}, {{ "parallel": [{}, {}] }}]"#,
+                test_action_json(r#""lookup""#),
+                test_action_json(r#""crm""#),
+                test_action_json(r#""lookup""#),
+            ));
+
+            assert_eq!(error.kind(), FlowLoadErrorKind::DuplicateAction);
+            assert_eq!(error.path(), "actions[1].parallel[1].config.id");
+        }
+
+        #[test]
+        fn test_from_json_fails_with_invalid_action_ids() {
+            for id in ["5", r#""""#, "null"] {
+                let error = load_error(&format!("[{}]", test_action_json(id)));
+
+                assert_eq!(error.kind(), FlowLoadErrorKind::InvalidFieldValue, "id {}", id);
+                assert_eq!(error.path(), "actions[0].config.id");
+            }
         }
     }
 }
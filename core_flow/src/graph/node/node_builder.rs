@@ -9,6 +9,7 @@ pub struct NodeBuilder {
     description: String,
     actions: Vec<Box<dyn Action>>,
     node_context: NodeContext,
+    terminal: bool,
 }
 
 impl NodeBuilder {
@@ -20,6 +21,7 @@ impl NodeBuilder {
             description,
             actions: Vec::new(),
             node_context: NodeContext::new(),
+            terminal: false,
         }
     }
 
@@ -33,10 +35,16 @@ impl NodeBuilder {
         self
     }
 
+    pub fn with_terminal(mut self, terminal: bool) -> Self {
+        self.terminal = terminal;
+        self
+    }
+
     pub fn build(self) -> Node {
         let mut new_node = Node::new(self.id, self.node_type, self.name, self.description);
 
         new_node.set_node_context(self.node_context);
+        new_node.set_terminal(self.terminal);
 
         for action in self.actions {
             new_node.add_action(action);
@@ -86,11 +94,26 @@ mod tests {
         assert_eq!(node.node_type, "test_type");
         assert_eq!(node.name, "Test Node");
         assert_eq!(node.description, "Test Description");
+        assert!(!node.is_terminal());
+    }
+
+    #[test]
+    fn test_builder_marks_node_as_terminal() {
+        let node = NodeBuilder::new(
+            "test_id".to_string(),
+            "test_type".to_string(),
+            "Test Node".to_string(),
+            "Test Description".to_string()
+        )
+        .with_terminal(true)
+        .build();
+
+        assert!(node.is_terminal());
     }
 
     #[tokio::test]
     async fn test_builder_adds_action() {
-        let mut node = NodeBuilder::new(
+        let node = NodeBuilder::new(
             "test_id".to_string(),
             "test_type".to_string(),
             "Test Node".to_string(),
@@ -100,9 +123,9 @@ mod tests {
         .build();
 
         // Execute actions to verify the action was added
-        node.execute_actions().await.unwrap();
+        let context = node.execute_actions(NodeContext::new()).await.unwrap();
         assert_eq!(
-            node.get_var_context("test_var".to_string()),
+            context.variables.get("test_var").cloned(),
             Some(Value::String("test_value".to_string()))
         );
     }
@@ -128,7 +151,7 @@ mod tests {
     #[tokio::test]
    async fn test_builder_chain_methods() {
         let test_value = Value::String("context_value".to_string());
-        let mut node= NodeBuilder::new(
+        let node = NodeBuilder::new(
             "test_id".to_string(),
             "test_type".to_string(),
             "Test Node".to_string(),
@@ -145,10 +168,14 @@ mod tests {
         );
 
         // Verify action was added
-        node.execute_actions().await.unwrap();
+        let context = node.execute_actions(NodeContext::new()).await.unwrap();
         assert_eq!(
-            node.get_var_context("test_var".to_string()),
+            context.variables.get("test_var").cloned(),
             Some(Value::String("test_value".to_string()))
         );
+        assert_eq!(
+            context.variables.get("test_key").cloned(),
+            Some(Value::String("context_value".to_string()))
+        );
     }
 }
\ No newline at end of file
@@ -1,17 +1,27 @@
-use std::collections::HashMap;
-use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashMap, error::Error, fmt};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{Value as JsonValue, Map as JsonMap};
 
 use crate::flow::conversation::Message;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+use super::{attachment::Attachment, decimal::{Decimal, ParseDecimalError}};
+
+/// Value of a variable, see below for its JSON encoding
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     String(String),
     Number(f64),
+    Integer(i64),
+    Decimal(Decimal),
+    DateTime(DateTime<Utc>),
     Boolean(bool),
     List(Vec<Value>),
     Map(HashMap<String, Value>),
     Null,
+    Bytes(Vec<u8>),
+    Attachment(Attachment),
     Messages(Vec<Message>)
 }
 
@@ -23,52 +33,594 @@ impl Value {
             None
         }
     }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Value::Number(_) | Value::Integer(_) | Value::Decimal(_))
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            Value::Integer(integer) => Some(*integer as f64),
+            Value::Decimal(decimal) => Some(decimal.to_f64()),
+            _ => None,
+        }
+    }
+
+    pub fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Value::DateTime(datetime) => Some(*datetime),
+            Value::String(text) => DateTime::parse_from_rfc3339(text).map(|datetime| datetime.to_utc()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Orders comparable values: numbers of any kind by value (exactly between integers and
+    /// decimals), datetimes by instant (an RFC 3339 string counts as a datetime) and strings
+    /// lexicographically. Other combinations are not comparable.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => Some(left.cmp(right)),
+            (Value::Integer(_) | Value::Decimal(_), Value::Integer(_) | Value::Decimal(_)) => {
+                Some(self.as_decimal()?.cmp(&other.as_decimal()?))
+            }
+            (left, right) if left.is_numeric() && right.is_numeric() => left.as_f64()?.partial_cmp(&right.as_f64()?),
+            (Value::DateTime(_), _) | (_, Value::DateTime(_)) => Some(self.as_datetime()?.cmp(&other.as_datetime()?)),
+            (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+            _ => None,
+        }
+    }
+
+    /// Equality used by conditions, `compare` decides for comparable values, other values must be identical
+    pub fn equals(&self, other: &Value) -> bool {
+        match self.compare(other) {
+            Some(ordering) => ordering == Ordering::Equal,
+            None => self == other,
+        }
+    }
+
+    fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Integer(integer) => Some(Decimal::from(*integer)),
+            Value::Decimal(decimal) => Some(*decimal),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::String(text)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::String(text.to_string())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(integer: i64) -> Self {
+        Value::Integer(integer)
+    }
+}
+
+impl From<Decimal> for Value {
+    fn from(decimal: Decimal) -> Self {
+        Value::Decimal(decimal)
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(datetime: DateTime<Utc>) -> Self {
+        Value::DateTime(datetime)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Self {
+        Value::Boolean(boolean)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(bytes: Vec<u8>) -> Self {
+        Value::Bytes(bytes)
+    }
+}
+
+impl From<Attachment> for Value {
+    fn from(attachment: Attachment) -> Self {
+        Value::Attachment(attachment)
+    }
+}
+
+/// Where a variable lives. Paths are resolved through the scopes in `RESOLUTION_ORDER`, a path
+/// prefixed with a scope name (`global:company.name`) only looks into that scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableScope {
+    // Scratch of the node being run, cleared whenever a node starts
+    Local,
+    // Built for a single trigger (trigger_message, messages, action_error), never persisted
+    Transient,
+    // Persisted with the conversation
+    Conversation,
+    // Configuration of the flow, seeded from the flow json on every trigger
+    Global,
+}
+
+impl VariableScope {
+    pub const RESOLUTION_ORDER: [VariableScope; 4] = [
+        VariableScope::Local,
+        VariableScope::Transient,
+        VariableScope::Conversation,
+        VariableScope::Global,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VariableScope::Local => "local",
+            VariableScope::Transient => "transient",
+            VariableScope::Conversation => "conversation",
+            VariableScope::Global => "global",
+        }
+    }
+
+    /// Splits an explicit scope prefix off a path, `global:company.name` gives `(Global, "company.name")`
+    pub fn split_path(path: &str) -> (Option<VariableScope>, &str) {
+        let scoped = path.split_once(':').and_then(|(prefix, rest)| {
+            let scope = Self::RESOLUTION_ORDER.into_iter().find(|scope| scope.name() == prefix)?;
+            Some((scope, rest))
+        });
+        match scoped {
+            Some((scope, rest)) => (Some(scope), rest),
+            None => (None, path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct NodeContext {
+    // Conversation scope
     pub variables: HashMap<String, Value>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub local: HashMap<String, Value>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub transient: HashMap<String, Value>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub global: HashMap<String, Value>,
 }
 
 impl NodeContext {
     pub fn new() -> Self {
+        NodeContext::default()
+    }
+
+    pub fn scope(&self, scope: VariableScope) -> &HashMap<String, Value> {
+        match scope {
+            VariableScope::Local => &self.local,
+            VariableScope::Transient => &self.transient,
+            VariableScope::Conversation => &self.variables,
+            VariableScope::Global => &self.global,
+        }
+    }
+
+    pub fn scope_mut(&mut self, scope: VariableScope) -> &mut HashMap<String, Value> {
+        match scope {
+            VariableScope::Local => &mut self.local,
+            VariableScope::Transient => &mut self.transient,
+            VariableScope::Conversation => &mut self.variables,
+            VariableScope::Global => &mut self.global,
+        }
+    }
+
+    /// Looks a variable up by its exact name, through the scopes in resolution order
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        VariableScope::RESOLUTION_ORDER
+            .into_iter()
+            .find_map(|scope| self.scope(scope).get(name))
+    }
+
+    /// The part of the context persisted with the conversation
+    pub fn conversation_scope(&self) -> NodeContext {
         NodeContext {
-            variables: HashMap::new(),
+            variables: self.variables.clone(),
+            ..NodeContext::default()
+        }
+    }
+
+    /// Resolves a variable path such as `crm.customer.orders[0].id`. Variable names may contain
+    /// dots themselves (`ai_action.intent`), so the longest prefix naming a variable wins and the
+    /// rest of the path navigates into its maps and lists. Closer scopes shadow the others.
+    pub fn resolve_path(&self, path: &str) -> Option<&Value> {
+        match VariableScope::split_path(path) {
+            (Some(scope), path) => resolve_in(self.scope(scope), path),
+            (None, path) => VariableScope::RESOLUTION_ORDER
+                .into_iter()
+                .find_map(|scope| resolve_in(self.scope(scope), path)),
         }
     }
 }
 
-impl From<JsonValue> for Value {
-    fn from(json_value: JsonValue) -> Self {
-        match json_value {
+fn resolve_in<'a>(variables: &'a HashMap<String, Value>, path: &str) -> Option<&'a Value> {
+    let split_points = path
+        .char_indices()
+        .filter(|(_, c)| *c == '.' || *c == '[')
+        .map(|(index, _)| index)
+        .chain(std::iter::once(path.len()));
+
+    let mut split_points: Vec<usize> = split_points.collect();
+    split_points.reverse();
+
+    split_points.into_iter().find_map(|split_point| {
+        let variable = variables.get(&path[..split_point])?;
+        navigate(variable, &path[split_point..])
+    })
+}
+
+// Follows `.key` and `[index]` segments from a value
+fn navigate<'a>(value: &'a Value, mut path: &str) -> Option<&'a Value> {
+    let mut value = value;
+
+    while !path.is_empty() {
+        if let Some(rest) = path.strip_prefix('[') {
+            let end = rest.find(']')?;
+            let index: usize = rest[..end].trim().parse().ok()?;
+            value = match value {
+                Value::List(items) => items.get(index)?,
+                _ => return None,
+            };
+            path = &rest[end + 1..];
+        } else if let Some(rest) = path.strip_prefix('.') {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            value = match value {
+                Value::Map(map) => map.get(&rest[..end])?,
+                _ => return None,
+            };
+            path = &rest[end..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(value)
+}
+
+// JSON encoding of values, used by serde and by the conversions from and to `serde_json::Value`:
+// - strings, booleans, null, lists and maps are plain JSON, map keys are sorted
+// - integers are JSON integers, numbers are JSON floats (`3.0`), so both kinds survive a round trip
+// - NaN and infinities are {"@number": "NaN" | "Infinity" | "-Infinity"}
+// - decimals are {"@decimal": "12.50"}, JSON integers too large for an i64 decode as decimals
+// - datetimes are {"@datetime": <RFC 3339, like message timestamps>}
+// - bytes are {"@bytes": <base64>}
+// - attachments are {"@attachment": {"url", "mime_type", "name", "size"}}
+// - messages are {"@messages": [<message>, ...]}
+// - a map whose only key is one of the markers above is wrapped as {"@map": {...}}
+const NUMBER_MARKER: &str = "@number";
+const DECIMAL_MARKER: &str = "@decimal";
+const DATETIME_MARKER: &str = "@datetime";
+const BYTES_MARKER: &str = "@bytes";
+const ATTACHMENT_MARKER: &str = "@attachment";
+const MESSAGES_MARKER: &str = "@messages";
+const MAP_MARKER: &str = "@map";
+const MARKERS: [&str; 7] = [
+    NUMBER_MARKER,
+    DECIMAL_MARKER,
+    DATETIME_MARKER,
+    BYTES_MARKER,
+    ATTACHMENT_MARKER,
+    MESSAGES_MARKER,
+    MAP_MARKER,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueDecodeError {
+    // Marker and what it was given
+    InvalidMarker(&'static str, String),
+    InvalidMap(String),
+}
+
+impl fmt::Display for ValueDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueDecodeError::InvalidMarker(marker, reason) => write!(f, "Invalid {}: {}", marker, reason),
+            ValueDecodeError::InvalidMap(map) => write!(f, "Invalid {}: {}", MAP_MARKER, map),
+        }
+    }
+}
+
+impl Error for ValueDecodeError {}
+
+impl TryFrom<JsonValue> for Value {
+    type Error = ValueDecodeError;
+
+    fn try_from(json_value: JsonValue) -> Result<Self, Self::Error> {
+        let value = match json_value {
             JsonValue::String(s) => Value::String(s),
-            JsonValue::Number(n) => Value::Number(n.as_f64().unwrap_or(0.0)),
+            JsonValue::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(integer), _) => Value::Integer(integer),
+                (None, Some(integer)) => Value::Decimal(Decimal::from(integer)),
+                _ => Value::Number(n.as_f64().unwrap_or(0.0)),
+            },
             JsonValue::Bool(b) => Value::Boolean(b),
-            JsonValue::Array(arr) => Value::List(arr.into_iter().map(Value::from).collect()),
-            JsonValue::Object(obj) => Value::Map(
-                obj.into_iter()
-                    .map(|(k, v)| (k, Value::from(v)))
-                    .collect(),
-            ),
+            JsonValue::Array(arr) => Value::List(arr.into_iter().map(Value::try_from).collect::<Result<_, _>>()?),
             JsonValue::Null => Value::Null,
-        }
+            JsonValue::Object(obj) if obj.len() == 1 && MARKERS.iter().any(|marker| obj.contains_key(*marker)) => {
+                let Some((marker, content)) = obj.into_iter().next() else {
+                    unreachable!("the object has one entry");
+                };
+                decode_marker(&marker, content)?
+            }
+            JsonValue::Object(obj) => decode_map(obj)?,
+        };
+        Ok(value)
     }
 }
 
-impl Into<JsonValue> for Value {
-    fn into(self) -> JsonValue {
-        match self {
-            Value::String(s) => JsonValue::String(s),
-            Value::Number(n) => JsonValue::Number(serde_json::Number::from_f64(n).unwrap()),
-            Value::Boolean(b) => JsonValue::Bool(b),
-            Value::List(lst) => JsonValue::Array(lst.into_iter().map(Into::into).collect()),
-            Value::Map(map) => JsonValue::Object(
-                map.into_iter()
-                    .map(|(k, v)| (k, v.into()))
-                    .collect::<JsonMap<String, JsonValue>>(),
-            ),
+fn decode_marker(marker: &str, content: JsonValue) -> Result<Value, ValueDecodeError> {
+    let text = |marker: &'static str| match &content {
+        JsonValue::String(text) => Ok(text.as_str()),
+        content => Err(ValueDecodeError::InvalidMarker(marker, content.to_string())),
+    };
+
+    match marker {
+        NUMBER_MARKER => match text(NUMBER_MARKER)? {
+            "NaN" => Ok(Value::Number(f64::NAN)),
+            "Infinity" => Ok(Value::Number(f64::INFINITY)),
+            "-Infinity" => Ok(Value::Number(f64::NEG_INFINITY)),
+            number => Err(ValueDecodeError::InvalidMarker(NUMBER_MARKER, number.to_string())),
+        },
+        DECIMAL_MARKER => text(DECIMAL_MARKER)?
+            .parse()
+            .map(Value::Decimal)
+            .map_err(|error: ParseDecimalError| ValueDecodeError::InvalidMarker(DECIMAL_MARKER, error.0)),
+        DATETIME_MARKER => DateTime::parse_from_rfc3339(text(DATETIME_MARKER)?)
+            .map(|datetime| Value::DateTime(datetime.to_utc()))
+            .map_err(|error| ValueDecodeError::InvalidMarker(DATETIME_MARKER, error.to_string())),
+        BYTES_MARKER => BASE64
+            .decode(text(BYTES_MARKER)?)
+            .map(Value::Bytes)
+            .map_err(|error| ValueDecodeError::InvalidMarker(BYTES_MARKER, error.to_string())),
+        ATTACHMENT_MARKER => serde_json::from_value(content)
+            .map(Value::Attachment)
+            .map_err(|error| ValueDecodeError::InvalidMarker(ATTACHMENT_MARKER, error.to_string())),
+        MESSAGES_MARKER => serde_json::from_value(content)
+            .map(Value::Messages)
+            .map_err(|error| ValueDecodeError::InvalidMarker(MESSAGES_MARKER, error.to_string())),
+        _ => match content {
+            JsonValue::Object(obj) => decode_map(obj),
+            content => Err(ValueDecodeError::InvalidMap(content.to_string())),
+        },
+    }
+}
+
+fn decode_map(obj: JsonMap<String, JsonValue>) -> Result<Value, ValueDecodeError> {
+    let map = obj
+        .into_iter()
+        .map(|(k, v)| Ok((k, Value::try_from(v)?)))
+        .collect::<Result<_, ValueDecodeError>>()?;
+    Ok(Value::Map(map))
+}
+
+impl From<&Value> for JsonValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::String(s) => JsonValue::String(s.clone()),
+            Value::Number(n) => encode_number(*n),
+            Value::Integer(i) => JsonValue::from(*i),
+            Value::Decimal(d) => marker(DECIMAL_MARKER, JsonValue::String(d.to_string())),
+            Value::DateTime(dt) => marker(DATETIME_MARKER, JsonValue::String(dt.to_rfc3339())),
+            Value::Boolean(b) => JsonValue::Bool(*b),
+            Value::List(lst) => JsonValue::Array(lst.iter().map(JsonValue::from).collect()),
+            Value::Map(map) => {
+                let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+                entries.sort_by_key(|(k, _)| *k);
+                let obj: JsonMap<String, JsonValue> = entries.into_iter().map(|(k, v)| (k.clone(), v.into())).collect();
+                match map.keys().next() {
+                    Some(key) if map.len() == 1 && MARKERS.contains(&key.as_str()) => marker(MAP_MARKER, JsonValue::Object(obj)),
+                    _ => JsonValue::Object(obj),
+                }
+            }
             Value::Null => JsonValue::Null,
-            Value::Messages(_) => unimplemented!("Conversion for Messages is not implemented"),
+            Value::Bytes(bytes) => marker(BYTES_MARKER, JsonValue::String(BASE64.encode(bytes))),
+            // Attachments and messages only hold strings and integers, they always serialize
+            Value::Attachment(attachment) => marker(ATTACHMENT_MARKER, serde_json::to_value(attachment).unwrap_or_default()),
+            Value::Messages(messages) => marker(MESSAGES_MARKER, serde_json::to_value(messages).unwrap_or_default()),
+        }
+    }
+}
+
+impl From<Value> for JsonValue {
+    fn from(value: Value) -> Self {
+        JsonValue::from(&value)
+    }
+}
+
+fn encode_number(number: f64) -> JsonValue {
+    match serde_json::Number::from_f64(number) {
+        Some(number) => JsonValue::Number(number),
+        None if number.is_nan() => marker(NUMBER_MARKER, JsonValue::from("NaN")),
+        None if number > 0.0 => marker(NUMBER_MARKER, JsonValue::from("Infinity")),
+        None => marker(NUMBER_MARKER, JsonValue::from("-Infinity")),
+    }
+}
+
+fn marker(marker: &str, content: JsonValue) -> JsonValue {
+    JsonValue::Object(JsonMap::from_iter([(marker.to_string(), content)]))
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JsonValue::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Value::try_from(JsonValue::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn create_context() -> NodeContext {
+        let mut context = NodeContext::new();
+        context.variables.insert("ai_action.intent".to_string(), Value::String("refund".to_string()));
+        context.variables.insert(
+            "crm".to_string(),
+            Value::try_from(json!({"customer": {"name": "Ada", "orders": [{"id": "ord-1"}, {"id": "ord-2"}]}})).unwrap(),
+        );
+        context
+    }
+
+    #[test]
+    fn test_resolve_path_with_dotted_variable_name() {
+        let context = create_context();
+
+        assert_eq!(context.resolve_path("ai_action.intent"), Some(&Value::String("refund".to_string())));
+    }
+
+    #[test]
+    fn test_resolve_path_into_maps_and_lists() {
+        let context = create_context();
+
+        assert_eq!(context.resolve_path("crm.customer.name"), Some(&Value::String("Ada".to_string())));
+        assert_eq!(context.resolve_path("crm.customer.orders[1].id"), Some(&Value::String("ord-2".to_string())));
+    }
+
+    #[test]
+    fn test_resolve_missing_path() {
+        let context = create_context();
+
+        assert_eq!(context.resolve_path("crm.customer.orders[5].id"), None);
+        assert_eq!(context.resolve_path("crm.customer.name.first"), None);
+        assert_eq!(context.resolve_path("unknown"), None);
+    }
+
+    #[test]
+    fn test_resolve_path_through_scopes() {
+        let mut context = create_context();
+        context.global.insert("company".to_string(), Value::try_from(json!({"name": "Acme"})).unwrap());
+        context.global.insert("ai_action.intent".to_string(), Value::String("global".to_string()));
+        context.local.insert("crm".to_string(), Value::try_from(json!({"customer": {"name": "Grace"}})).unwrap());
+
+        assert_eq!(context.resolve_path("company.name"), Some(&Value::String("Acme".to_string())));
+        assert_eq!(context.resolve_path("ai_action.intent"), Some(&Value::String("refund".to_string())));
+        assert_eq!(context.resolve_path("global:ai_action.intent"), Some(&Value::String("global".to_string())));
+        assert_eq!(context.resolve_path("crm.customer.name"), Some(&Value::String("Grace".to_string())));
+        assert_eq!(context.resolve_path("conversation:crm.customer.name"), Some(&Value::String("Ada".to_string())));
+        assert_eq!(context.resolve_path("local:company.name"), None);
+    }
+
+    #[test]
+    fn test_conversation_scope_drops_other_scopes() {
+        let mut context = create_context();
+        context.local.insert("draft".to_string(), Value::Null);
+        context.transient.insert("trigger_message".to_string(), Value::Messages(vec![]));
+        context.global.insert("company".to_string(), Value::Null);
+
+        let persisted = context.conversation_scope();
+
+        assert_eq!(persisted.variables, context.variables);
+        assert!(persisted.local.is_empty() && persisted.transient.is_empty() && persisted.global.is_empty());
+        assert_eq!(serde_json::to_value(&persisted).unwrap().as_object().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_json_encoding() {
+        let message = Message::new("user".to_string(), "hi".to_string(), "ai".to_string());
+        let value = Value::Map(HashMap::from([
+            ("count".to_string(), Value::Integer(3)),
+            ("total".to_string(), Value::Number(3.0)),
+            ("ratio".to_string(), Value::Number(0.5)),
+            ("limit".to_string(), Value::Number(f64::INFINITY)),
+            ("tags".to_string(), Value::List(vec![Value::String("vip".to_string()), Value::Null])),
+            ("messages".to_string(), Value::Messages(vec![message.clone()])),
+        ]));
+
+        let json = serde_json::to_value(&value).unwrap();
+
+        assert_eq!(json["count"], json!(3));
+        assert_eq!(json["total"], json!(3.0));
+        assert_eq!(json["ratio"], json!(0.5));
+        assert_eq!(json["limit"], json!({"@number": "Infinity"}));
+        assert_eq!(json["tags"], json!(["vip", null]));
+        assert_eq!(json["messages"]["@messages"][0]["id"], json!(message.get_id()));
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["count", "limit", "messages", "ratio", "tags", "total"]);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let values = vec![
+            Value::Number(-0.0),
+            Value::Number(1e300),
+            Value::Number(f64::NEG_INFINITY),
+            Value::Number(3.0),
+            Value::Integer(i64::MIN),
+            Value::Decimal("12.50".parse().unwrap()),
+            Value::DateTime(DateTime::parse_from_rfc3339("2025-03-01T10:00:00.123Z").unwrap().to_utc()),
+            Value::Bytes(vec![0, 159, 255]),
+            Value::Attachment(Attachment::new("https://cdn.example.com/invoice.pdf".to_string(), "application/pdf".to_string()).with_size(2048)),
+            Value::Messages(vec![Message::new("ai".to_string(), "hello".to_string(), "user".to_string())]),
+            Value::Map(HashMap::from([("@messages".to_string(), Value::Boolean(true))])),
+            Value::Map(HashMap::from([("@map".to_string(), Value::Map(HashMap::new()))])),
+        ];
+
+        for value in values {
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value, "{}", json);
         }
+
+        let nan: Value = serde_json::from_value(json!({"@number": "NaN"})).unwrap();
+        assert!(matches!(nan, Value::Number(number) if number.is_nan()));
+    }
+
+    #[test]
+    fn test_invalid_markers() {
+        assert_eq!(
+            Value::try_from(json!({"@number": "one"})),
+            Err(ValueDecodeError::InvalidMarker(NUMBER_MARKER, "one".to_string()))
+        );
+        assert!(matches!(Value::try_from(json!({"@messages": 3})), Err(ValueDecodeError::InvalidMarker(MESSAGES_MARKER, _))));
+        assert!(matches!(Value::try_from(json!({"@decimal": 1.5})), Err(ValueDecodeError::InvalidMarker(DECIMAL_MARKER, _))));
+        assert!(matches!(Value::try_from(json!({"@datetime": "today"})), Err(ValueDecodeError::InvalidMarker(DATETIME_MARKER, _))));
+        assert!(matches!(Value::try_from(json!({"@map": []})), Err(ValueDecodeError::InvalidMap(_))));
+        // Markers only count when they are the only key
+        assert!(matches!(Value::try_from(json!({"@number": "one", "other": 1})), Ok(Value::Map(_))));
+    }
+
+    #[test]
+    fn test_large_json_integers_stay_exact() {
+        assert_eq!(Value::try_from(json!(9_007_199_254_740_993i64)), Ok(Value::Integer(9_007_199_254_740_993)));
+        assert_eq!(Value::try_from(json!(u64::MAX)), Ok(Value::Decimal(Decimal::from(u64::MAX))));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_compare_values() {
+        let decimal = |text: &str| Value::Decimal(text.parse().unwrap());
+        let datetime = Value::DateTime(DateTime::parse_from_rfc3339("2025-03-01T10:00:00Z").unwrap().to_utc());
+
+        assert!(Value::Integer(3).equals(&Value::Number(3.0)));
+        assert!(Value::Integer(20).equals(&decimal("20.00")));
+        assert_eq!(decimal("19.99").compare(&Value::Integer(20)), Some(Ordering::Less));
+        assert_eq!(Value::Integer(9_007_199_254_740_993).compare(&Value::Integer(9_007_199_254_740_992)), Some(Ordering::Greater));
+        assert!(datetime.equals(&Value::from("2025-03-01T11:00:00+01:00")));
+        assert_eq!(datetime.compare(&Value::from("2025-03-02T00:00:00Z")), Some(Ordering::Less));
+        assert_eq!(Value::from("a").compare(&Value::Integer(1)), None);
+        assert!(!Value::from("3").equals(&Value::Integer(3)));
+        assert!(Value::Bytes(vec![1]).equals(&Value::Bytes(vec![1])));
+    }
+}
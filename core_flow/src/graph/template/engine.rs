@@ -0,0 +1,231 @@
+use std::{error::Error, fmt};
+
+use serde_json::Value as JsonValue;
+
+use crate::graph::{config_error::ConfigError, node::node_context::NodeContext};
+
+use super::filter::{to_text, Filter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl TemplateError {
+    pub fn new(position: usize, message: String) -> Self {
+        TemplateError { position, message }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for TemplateError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder { position: usize, path: String, filters: Vec<Filter> },
+}
+
+/// String with `{{ path | filter }}` placeholders rendered against a `NodeContext`, e.g.
+/// `Hello {{ crm.customer.name | default:"there" }}, your order ships {{ order.date | date:"%d/%m" }}`.
+/// Paths resolve like input vars, a missing value fails the render unless a default is given.
+/// The template is parsed once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+        let mut offset = 0;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let position = offset + start;
+            let end = rest[start..]
+                .find("}}")
+                .ok_or_else(|| TemplateError::new(position, "Unclosed placeholder".to_string()))?;
+
+            segments.push(parse_placeholder(&rest[start + 2..start + end], position)?);
+            offset += start + end + 2;
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Template { segments })
+    }
+
+    pub fn render(&self, context: &NodeContext) -> Result<String, TemplateError> {
+        let mut rendered = String::new();
+
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Placeholder { position, path, filters } => {
+                    let mut value = context.resolve_path(path).cloned();
+                    for filter in filters {
+                        value = filter
+                            .apply(value)
+                            .map_err(|message| TemplateError::new(*position, message))?;
+                    }
+                    let value = value
+                        .ok_or_else(|| TemplateError::new(*position, format!("Variable {} is missing", path)))?;
+                    rendered.push_str(&to_text(&value));
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+}
+
+/// Parses a string config field as a template, for actions that let the field use placeholders
+pub fn config_template(config: &JsonValue, field: &str) -> Result<Template, ConfigError> {
+    let source = config
+        .get(field)
+        .ok_or_else(|| ConfigError::MissingField(field.to_string()))?
+        .as_str()
+        .ok_or_else(|| ConfigError::invalid_field(field, "expected a string"))?;
+
+    Template::parse(source).map_err(|error| ConfigError::invalid_field(field, error))
+}
+
+// Content between `{{` and `}}`: a path followed by filters, `name` or `name:"argument"`
+fn parse_placeholder(content: &str, position: usize) -> Result<Segment, TemplateError> {
+    let parts = split_filters(content).map_err(|message| TemplateError::new(position, message))?;
+    let mut parts = parts.into_iter();
+
+    let path = parts.next().unwrap_or_default();
+    if path.is_empty() || path.chars().any(char::is_whitespace) {
+        return Err(TemplateError::new(position, format!("Invalid variable path {:?}", path)));
+    }
+
+    let filters = parts
+        .map(|filter| {
+            let (name, argument) = match filter.split_once(':') {
+                Some((name, argument)) => (name.trim(), Some(unquote(argument.trim())?)),
+                None => (filter.as_str(), None),
+            };
+            Filter::new(name, argument)
+        })
+        .collect::<Result<Vec<Filter>, String>>()
+        .map_err(|message| TemplateError::new(position, message))?;
+
+    Ok(Segment::Placeholder { position, path, filters })
+}
+
+// Splits on `|` outside of quoted arguments and trims every part
+fn split_filters(content: &str) -> Result<Vec<String>, String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+
+    for c in content.chars() {
+        match (quote, c) {
+            (None, '|') => parts.push(std::mem::take(&mut current).trim().to_string()),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (Some(open), c) if c == open => {
+                quote = None;
+                current.push(c);
+            }
+            _ => current.push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("Unclosed quote".to_string());
+    }
+    parts.push(current.trim().to_string());
+
+    Ok(parts)
+}
+
+// Filter arguments are quoted strings, numbers may be left bare
+fn unquote(argument: &str) -> Result<String, String> {
+    let quoted = ['"', '\''].iter().find_map(|quote| {
+        argument
+            .strip_prefix(*quote)
+            .and_then(|rest| rest.strip_suffix(*quote))
+    });
+
+    match quoted {
+        Some(text) => Ok(text.to_string()),
+        None if argument.parse::<f64>().is_ok() => Ok(argument.to_string()),
+        None => Err(format!("Filter argument {} must be quoted", argument)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::graph::node::node_context::Value;
+
+    use super::*;
+
+    fn create_context() -> NodeContext {
+        let mut context = NodeContext::new();
+        context.variables.insert(
+            "crm.customer".to_string(),
+            Value::try_from(json!({"name": "Ada", "orders": [{"id": "ord-1", "total": 42}]})).unwrap(),
+        );
+        context.variables.insert("order.date".to_string(), Value::String("2025-03-01T10:00:00Z".to_string()));
+        context
+    }
+
+    fn render(source: &str) -> Result<String, TemplateError> {
+        Template::parse(source)?.render(&create_context())
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(render("Hello {{crm.customer.name}}!"), Ok("Hello Ada!".to_string()));
+        assert_eq!(
+            render("{{ crm.customer.orders[0].id | upper }} costs {{ crm.customer.orders[0].total }}"),
+            Ok("ORD-1 costs 42".to_string())
+        );
+        assert_eq!(render("Ships {{ order.date | date:\"%d/%m/%Y\" }}"), Ok("Ships 01/03/2025".to_string()));
+        assert_eq!(render("Hi {{ user.nickname | default:'there' | upper }}"), Ok("Hi THERE".to_string()));
+        assert_eq!(render("{{ crm.customer.orders | json }}"), Ok(r#"[{"id":"ord-1","total":42}]"#.to_string()));
+        assert_eq!(render("No placeholders"), Ok("No placeholders".to_string()));
+    }
+
+    #[test]
+    fn test_missing_variables_fail_the_render() {
+        let error = render("Hello {{ user.nickname }}").unwrap_err();
+
+        assert_eq!(error, TemplateError::new(6, "Variable user.nickname is missing".to_string()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Template::parse("Hello {{ name").unwrap_err().message, "Unclosed placeholder");
+        assert!(Template::parse("{{ }}").is_err());
+        assert!(Template::parse("{{ name | shout }}").is_err());
+        assert!(Template::parse("{{ name | default:there }}").is_err());
+        assert!(Template::parse("{{ name | default:\"there }}").is_err());
+    }
+
+    #[test]
+    fn test_config_template() {
+        let config = json!({"system_prompt": "You talk to {{ crm.customer.name }}", "temperature": 1});
+
+        assert!(config_template(&config, "system_prompt").is_ok());
+        assert_eq!(config_template(&config, "prompt"), Err(ConfigError::MissingField("prompt".to_string())));
+        assert!(matches!(config_template(&config, "temperature"), Err(ConfigError::InvalidField { .. })));
+    }
+}
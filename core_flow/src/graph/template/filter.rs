@@ -0,0 +1,161 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, format::StrftimeItems};
+use serde_json::Value as JsonValue;
+
+use crate::graph::node::node_context::Value;
+
+/// Transformation applied to a placeholder value, e.g. `{{ customer.name | upper }}`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    // Used when the value is missing, null or an empty string
+    Default(String),
+    Upper,
+    Lower,
+    Trim,
+    // strftime format, the value is a datetime, an RFC 3339 string or a unix timestamp in seconds
+    Date(String),
+    Json,
+}
+
+impl Filter {
+    pub fn new(name: &str, argument: Option<String>) -> Result<Self, String> {
+        let filter = match (name, argument) {
+            ("default", Some(default)) => Filter::Default(default),
+            ("date", Some(format)) => {
+                StrftimeItems::new(&format)
+                    .parse()
+                    .map_err(|_| format!("Invalid date format {}", format))?;
+                Filter::Date(format)
+            }
+            ("default" | "date", None) => return Err(format!("Filter {} requires an argument", name)),
+            ("upper", None) => Filter::Upper,
+            ("lower", None) => Filter::Lower,
+            ("trim", None) => Filter::Trim,
+            ("json", None) => Filter::Json,
+            ("upper" | "lower" | "trim" | "json", Some(_)) => {
+                return Err(format!("Filter {} takes no argument", name));
+            }
+            _ => return Err(format!("Unknown filter {}", name)),
+        };
+        Ok(filter)
+    }
+
+    /// Applies the filter, `None` stands for a missing value
+    pub fn apply(&self, value: Option<Value>) -> Result<Option<Value>, String> {
+        let value = match (self, value) {
+            (Filter::Default(default), None | Some(Value::Null)) => return Ok(Some(Value::String(default.clone()))),
+            (Filter::Default(default), Some(Value::String(text))) if text.is_empty() => {
+                return Ok(Some(Value::String(default.clone())));
+            }
+            (_, None) => return Ok(None),
+            (_, Some(value)) => value,
+        };
+
+        let filtered = match self {
+            Filter::Default(_) => value,
+            Filter::Upper => Value::String(to_text(&value).to_uppercase()),
+            Filter::Lower => Value::String(to_text(&value).to_lowercase()),
+            Filter::Trim => Value::String(to_text(&value).trim().to_string()),
+            Filter::Json => Value::String(to_json(&value).to_string()),
+            Filter::Date(format) => {
+                let date = match &value {
+                    Value::Integer(seconds) => DateTime::from_timestamp(*seconds, 0),
+                    Value::Number(seconds) => DateTime::from_timestamp(*seconds as i64, 0),
+                    value => value.as_datetime(),
+                };
+                let date = date.ok_or_else(|| format!("{} is not a date", to_text(&value)))?;
+                Value::String(date.format(format).to_string())
+            }
+        };
+        Ok(Some(filtered))
+    }
+}
+
+/// Text a value is rendered as. Messages render as their text or caption, one per line.
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => format!("{}", *number as i64),
+        Value::Number(number) => number.to_string(),
+        Value::Integer(integer) => integer.to_string(),
+        Value::Decimal(decimal) => decimal.to_string(),
+        Value::DateTime(datetime) => datetime.to_rfc3339(),
+        Value::Boolean(boolean) => boolean.to_string(),
+        Value::Null => String::new(),
+        Value::Bytes(bytes) => BASE64.encode(bytes),
+        Value::Attachment(attachment) => attachment.url.clone(),
+        Value::Messages(messages) => messages
+            .iter()
+            .filter_map(|message| message.content.text())
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        Value::List(_) | Value::Map(_) => to_json(value).to_string(),
+    }
+}
+
+fn to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::String(text) => JsonValue::String(text.clone()),
+        Value::Number(number) => serde_json::Number::from_f64(*number).map_or(JsonValue::Null, JsonValue::Number),
+        Value::Integer(integer) => JsonValue::from(*integer),
+        // Decimals and datetimes render as text, a JSON number would round the decimal
+        Value::Decimal(_) | Value::DateTime(_) | Value::Bytes(_) => JsonValue::String(to_text(value)),
+        Value::Boolean(boolean) => JsonValue::Bool(*boolean),
+        Value::Null => JsonValue::Null,
+        Value::Attachment(attachment) => serde_json::to_value(attachment).unwrap_or(JsonValue::Null),
+        Value::List(items) => JsonValue::Array(items.iter().map(to_json).collect()),
+        // Keys are sorted so the same map always renders the same text
+        Value::Map(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            JsonValue::Object(entries.into_iter().map(|(key, value)| (key.clone(), to_json(value))).collect())
+        }
+        Value::Messages(messages) => serde_json::to_value(messages).unwrap_or(JsonValue::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(filter: Filter, value: Value) -> Value {
+        filter.apply(Some(value)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_new_filter() {
+        assert_eq!(Filter::new("default", Some("friend".to_string())), Ok(Filter::Default("friend".to_string())));
+        assert!(Filter::new("default", None).is_err());
+        assert!(Filter::new("upper", Some("x".to_string())).is_err());
+        assert!(Filter::new("date", Some("%Q".to_string())).is_err());
+        assert!(Filter::new("reverse", None).is_err());
+    }
+
+    #[test]
+    fn test_apply_filters() {
+        let text = |text: &str| Value::String(text.to_string());
+
+        assert_eq!(Filter::Default("friend".to_string()).apply(None), Ok(Some(text("friend"))));
+        assert_eq!(apply(Filter::Default("friend".to_string()), text("")), text("friend"));
+        assert_eq!(apply(Filter::Default("friend".to_string()), text("Ada")), text("Ada"));
+        assert_eq!(apply(Filter::Upper, text("Ada")), text("ADA"));
+        assert_eq!(apply(Filter::Lower, text("Ada")), text("ada"));
+        assert_eq!(apply(Filter::Trim, text(" Ada ")), text("Ada"));
+        assert_eq!(apply(Filter::Json, Value::List(vec![Value::Number(1.0)])), text("[1.0]"));
+        assert_eq!(apply(Filter::Date("%d/%m/%Y".to_string()), text("2025-03-01T10:00:00Z")), text("01/03/2025"));
+        assert_eq!(apply(Filter::Date("%Y".to_string()), Value::Number(0.0)), text("1970"));
+        assert_eq!(apply(Filter::Date("%Y".to_string()), Value::Integer(0)), text("1970"));
+        assert_eq!(apply(Filter::Json, Value::Decimal("12.50".parse().unwrap())), text("\"12.5\""));
+        assert!(Filter::Date("%Y".to_string()).apply(Some(text("tomorrow"))).is_err());
+        assert_eq!(Filter::Upper.apply(None), Ok(None));
+    }
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(to_text(&Value::Number(3.0)), "3");
+        assert_eq!(to_text(&Value::Number(2.5)), "2.5");
+        assert_eq!(to_text(&Value::Null), "");
+        assert_eq!(to_text(&Value::Boolean(true)), "true");
+        assert_eq!(to_text(&Value::Integer(42)), "42");
+    }
+}
@@ -8,7 +8,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let uri = "mongodb://localhost:27017"; // Replace with your MongoDB URI
     let database_name = "path_flow_db";
     
-    let mut mongo_repo = MongoConversationRepository::new_with_uri(uri, database_name).await?;
+    let mongo_repo = MongoConversationRepository::new_with_uri(uri, database_name).await?;
     
     // Create a new conversation
     let conversation = Conversation::new("example_conv_id".to_string(), "initial_node".to_string());
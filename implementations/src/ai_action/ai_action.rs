@@ -1,33 +1,42 @@
 use async_trait::async_trait;
 use core_flow::{
-    flow::conversation::Message,
+    flow::conversation::{Message, MessageRole, AI_SENDER},
     graph::{
         action::{action::Action, utils::vars_parser::OutputVarsBuilder},
+        config_error::{config_string, ConfigError},
+        config_schema::{ComponentSchema, ConfigSchema, FieldType},
         node::node_context::{NodeContext, Value},
+        template::engine::{config_template, Template},
     },
 };
-use rig::{client::ProviderClient, completion::Chat, providers::gemini};
+use rig::{completion::Chat, providers::gemini};
 use serde_json::Value as JsonValue;
 
-use crate::ai_action::message_adapter::rig_message_adapter;
+use crate::ai_action::message_adapter::{rig_message_adapter, rig_preamble};
+
+const GEMINI_API_KEY_VARIABLE: &str = "GEMINI_API_KEY";
 
 #[derive(Clone)]
 pub struct AIAction {
-    // Add any configuration fields needed for the AI action
+    // Shared by every ai_action of the flow
+    client: gemini::Client,
     model: String,
-    system_prompt: String,
+    // Rendered against the node context on every run
+    system_prompt: Template,
     output_vars: JsonValue,
     config: JsonValue,
 }
 
 impl AIAction {
     pub fn new(
+        client: gemini::Client,
         model: String,
-        system_prompt: String,
+        system_prompt: Template,
         output_vars: JsonValue,
         config: JsonValue,
     ) -> Self {
         AIAction {
+            client,
             model,
             system_prompt,
             output_vars,
@@ -37,35 +46,58 @@ impl AIAction {
 
     async fn process_messages(
         &self,
+        system_prompt: &str,
         messages: Vec<Message>,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        // let openai_client = openai::Client::from_env();
-        // let gpt4 = openai_client.agent(&self.model).build();
-
-        let gemini_client = gemini::Client::from_env();
-        let gpt4 = gemini_client.agent(&self.model).build();
+        // System messages go to the preamble, rig has no system role in the history
+        let mut agent = self.client.agent(&self.model);
+        if let Some(preamble) = rig_preamble(&messages) {
+            agent = agent.preamble(&preamble);
+        }
+        let gpt4 = agent.build();
 
         let messages = messages
-            .iter()
-            .map(|m| rig_message_adapter(m.clone()))
+            .into_iter()
+            .filter_map(rig_message_adapter)
             .collect::<Vec<rig::completion::Message>>();
 
-        let response = gpt4.chat(&self.system_prompt, messages).await?;
+        let response = gpt4.chat(system_prompt, messages).await?;
 
         Ok(response)
     }
 
+    /// Gemini client authenticated with the GEMINI_API_KEY environment variable
+    pub fn create_client() -> Result<gemini::Client, ConfigError> {
+        match std::env::var(GEMINI_API_KEY_VARIABLE) {
+            Ok(api_key) if !api_key.is_empty() => Ok(gemini::Client::new(&api_key)),
+            _ => Err(ConfigError::ConstructionFailed(format!("{} is not set", GEMINI_API_KEY_VARIABLE))),
+        }
+    }
+
+    pub fn schema() -> ComponentSchema {
+        ComponentSchema::new()
+            .with_config(
+                ConfigSchema::new()
+                    .with_field("model", FieldType::String, "Gemini model answering, e.g. gemini-2.0-flash")
+                    .with_field("system_prompt", FieldType::String, "Instructions given to the model, with {{ path | filter }} placeholders"),
+            )
+            .with_output_var("messages", "The reply of the model")
+    }
+
+    // config: { "model": <model name>, "system_prompt": <string> }
     pub fn create_ai_action(
+        client: &gemini::Client,
         config: &JsonValue,
         _: &JsonValue,
         output_vars: &JsonValue,
-    ) -> Box<dyn Action> {
-        Box::new(AIAction::new(
-            config["model"].as_str().unwrap().to_string(),
-            config["system_prompt"].as_str().unwrap().to_string(),
+    ) -> Result<Box<dyn Action>, ConfigError> {
+        Ok(Box::new(AIAction::new(
+            client.clone(),
+            config_string(config, "model")?,
+            config_template(config, "system_prompt")?,
             output_vars.clone(),
             config.clone(),
-        ))
+        )))
     }
 }
 
@@ -75,14 +107,15 @@ impl Action for AIAction {
         &self,
         context: &mut NodeContext,
     ) -> Result<NodeContext, Box<dyn std::error::Error>> {
-        let mut messages_vec = match context.variables.remove("messages") {
+        let mut messages_vec = match context.transient.remove("messages") {
             Some(Value::Messages(msgs)) => msgs,
             _ => Vec::new(),
         };
 
-        let ai_response = self.process_messages(messages_vec.clone()).await?;
+        let system_prompt = self.system_prompt.render(context)?;
+        let ai_response = self.process_messages(&system_prompt, messages_vec.clone()).await?;
 
-        let trigger_message: Option<&Value> = context.variables.get("trigger_message");
+        let trigger_message: Option<&Value> = context.transient.get("trigger_message");
 
         if trigger_message.is_none() {
             return Err(Box::new(std::io::Error::new(
@@ -95,22 +128,22 @@ impl Action for AIAction {
             .recipient
             .clone();
 
-        let new_ai_message = Message::new("ai".to_string(), ai_response, recipient);
+        let new_ai_message = Message::new(AI_SENDER.to_string(), ai_response, recipient).with_role(MessageRole::Assistant);
 
         messages_vec.push(new_ai_message.clone());
 
         let mut output_builder =
-            OutputVarsBuilder::new(&self.config, &self.output_vars, context.clone());
+            OutputVarsBuilder::new(&self.config, &self.output_vars, context.clone())?;
 
         output_builder.add_var(
             "messages".to_string(),
             Value::Messages(vec![new_ai_message]),
-        );
+        )?;
 
         let mut output_context = output_builder.build()?;
 
         output_context
-            .variables
+            .transient
             .insert("messages".to_string(), Value::Messages(messages_vec));
 
         Ok(output_context)
@@ -137,11 +170,11 @@ mod tests {
         )];
 
         context
-            .variables
+            .transient
             .insert("messages".to_string(), Value::Messages(messages));
 
         context
-            .variables
+            .transient
             .insert("trigger_message".to_string(), Value::Messages(vec![Message::new(
                 "user".to_string(),
                 "Hello, how are you?".to_string(),
@@ -149,8 +182,9 @@ mod tests {
             )]));
 
         let ai_action = AIAction::new(
+            AIAction::create_client().unwrap(),
             "gemini-2.0-flash".to_string(),
-            "You are a helpful assistant".to_string(),
+            Template::parse("You are a helpful assistant").unwrap(),
             json!(["messages"]),
             json!({
                 "id": "ai_action",
@@ -163,4 +197,16 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_create_ai_action_without_model() {
+        let result = AIAction::create_ai_action(
+            &gemini::Client::new("test-key"),
+            &json!({"id": "ai_action", "name": "AI Action", "system_prompt": "You are a helpful assistant"}),
+            &json!({}),
+            &json!(["messages"]),
+        );
+
+        assert!(matches!(result, Err(ConfigError::MissingField(field)) if field == "model"));
+    }
 }
@@ -1,33 +1,325 @@
-use core_flow::flow::conversation::{Message, MessageType};
-use rig::{completion::Message as RigMessage, message::{AssistantContent, Text, UserContent}, OneOrMany};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use core_flow::flow::conversation::{
+    Location, MediaContent, MediaSource, Message, MessageRole, MessageType, QuickReplies, AI_SENDER,
+};
+use rig::{
+    completion::Message as RigMessage,
+    message::{
+        AssistantContent, AudioMediaType, ContentFormat, DocumentMediaType, ImageMediaType, MimeType, Text,
+        ToolResultContent, UserContent,
+    },
+    OneOrMany,
+};
 
+const USER_SENDER: &str = "user";
+const UNKNOWN_MIME_TYPE: &str = "application/octet-stream";
+// Inline media types the Gemini provider accepts
+const GEMINI_IMAGE_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp", "image/heic", "image/heif"];
+const GEMINI_AUDIO_TYPES: &[&str] = &["audio/wav", "audio/mp3", "audio/mpeg", "audio/aiff", "audio/aac", "audio/ogg", "audio/flac"];
+const GEMINI_DOCUMENT_TYPES: &[&str] = &["application/pdf", "text/plain", "text/html", "text/markdown", "text/csv"];
 
-pub fn rig_message_adapter(message: Message) -> RigMessage {
-    if message.recipient != "ai" {
-        match message.content {
-            MessageType::Text(text) => RigMessage::User { content: OneOrMany::one(UserContent::Text(Text { text })) },
-            MessageType::Image => RigMessage::User { content: OneOrMany::one(UserContent::Text(Text { text: "asdasd".to_string() })) },
-            MessageType::Audio => RigMessage::User { content: OneOrMany::one(UserContent::Text(Text { text: "asdasd".to_string() })) },
+/// Chat history entry of a message, system messages have none as they belong in the preamble
+pub fn rig_message_adapter(message: Message) -> Option<RigMessage> {
+    let rig_message = match message.role {
+        MessageRole::User => RigMessage::User { content: rig_user_content(message.content) },
+        // The model answers for human agents too, so it sees their replies as its own
+        MessageRole::Assistant | MessageRole::HumanAgent => RigMessage::Assistant {
+            content: OneOrMany::one(AssistantContent::text(content_text(&message.content))),
+        },
+        MessageRole::Tool => RigMessage::User { content: OneOrMany::one(rig_tool_content(&message)) },
+        MessageRole::System => return None,
+    };
+    Some(rig_message)
+}
+
+// Gemini parses tool results as json, anything else is handed over as text naming the tool
+fn rig_tool_content(message: &Message) -> UserContent {
+    let text = content_text(&message.content);
+    if serde_json::from_str::<serde_json::Value>(&text).is_ok() {
+        UserContent::tool_result(message.sender.clone(), OneOrMany::one(ToolResultContent::text(text)))
+    } else {
+        UserContent::text(format!("Result of tool {}: {}", message.sender, text))
+    }
+}
+
+/// Agent preamble made of the system messages of the conversation, none without any
+pub fn rig_preamble(messages: &[Message]) -> Option<String> {
+    let parts = messages
+        .iter()
+        .filter(|message| message.role == MessageRole::System)
+        .map(|message| content_text(&message.content))
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<String>>();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+// Inline bytes of a type Gemini accepts go to the model as image, audio or document content,
+// followed by their caption. Gemini can't fetch urls and rejects other types, so those, like
+// any other content, are described as text.
+fn rig_user_content(content: MessageType) -> OneOrMany<UserContent> {
+    let media = match &content {
+        MessageType::Image(media) | MessageType::Audio(media) | MessageType::File(media) => media,
+        _ => return OneOrMany::one(UserContent::text(content_text(&content))),
+    };
+    let rig_media = match (&media.source, &content) {
+        (MediaSource::Bytes(bytes), MessageType::Image(_)) if GEMINI_IMAGE_TYPES.contains(&media.mime_type.as_str()) => {
+            ImageMediaType::from_mime_type(&media.mime_type)
+                .map(|media_type| UserContent::image(BASE64.encode(bytes), Some(ContentFormat::Base64), Some(media_type), None))
+        }
+        (MediaSource::Bytes(bytes), MessageType::Audio(_)) if GEMINI_AUDIO_TYPES.contains(&media.mime_type.as_str()) => {
+            AudioMediaType::from_mime_type(&media.mime_type)
+                .map(|media_type| UserContent::audio(BASE64.encode(bytes), Some(ContentFormat::Base64), Some(media_type)))
         }
+        (MediaSource::Bytes(bytes), MessageType::File(_)) if GEMINI_DOCUMENT_TYPES.contains(&media.mime_type.as_str()) => {
+            DocumentMediaType::from_mime_type(&media.mime_type)
+                .map(|media_type| UserContent::document(BASE64.encode(bytes), Some(ContentFormat::Base64), Some(media_type)))
+        }
+        _ => None,
+    };
+    let Some(rig_media) = rig_media else {
+        return OneOrMany::one(UserContent::text(content_text(&content)));
+    };
+
+    let mut rig_content = OneOrMany::one(rig_media);
+    if let Some(caption) = &media.caption {
+        rig_content.push(UserContent::text(caption.clone()));
+    }
+    rig_content
+}
+
+fn location_text(location: &Location) -> String {
+    let place = [location.name.as_deref(), location.address.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join(", ");
+    if place.is_empty() {
+        format!("Location: {}, {}", location.latitude, location.longitude)
     } else {
-        RigMessage::Assistant { content: OneOrMany::one(AssistantContent::Text(Text { text: "asdasd".to_string() })) }
+        format!("Location: {} ({}, {})", place, location.latitude, location.longitude)
+    }
+}
+
+fn quick_replies_text(quick_replies: &QuickReplies) -> String {
+    let replies = quick_replies
+        .replies
+        .iter()
+        .map(|reply| format!("- {}", reply.title))
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!("{}\n{}", quick_replies.text, replies)
+}
+
+// Plain text of any content, media is described by its caption or where it lives
+fn content_text(content: &MessageType) -> String {
+    let (kind, media) = match content {
+        MessageType::Text(text) => return text.clone(),
+        MessageType::Location(location) => return location_text(location),
+        MessageType::QuickReplies(quick_replies) => return quick_replies_text(quick_replies),
+        MessageType::Image(media) => ("Image", media),
+        MessageType::Audio(media) => ("Audio", media),
+        MessageType::File(media) => ("File", media),
+    };
+    if let Some(caption) = &media.caption {
+        return caption.clone();
+    }
+    match (&media.source, &media.file_name) {
+        (MediaSource::Url(url), _) => format!("{}: {}", kind, url),
+        (MediaSource::Bytes(_), Some(file_name)) => format!("{}: {}", kind, file_name),
+        (MediaSource::Bytes(_), None) => format!("{} ({})", kind, media.mime_type),
     }
 }
 
 #[allow(dead_code)]
 pub fn flow_message_adapter(message: RigMessage) -> Message {
     match message {
-        RigMessage::User { content } => {
-            match content.first() {
-                UserContent::Text(Text { text }) => Message::new("user".to_string(), text, "ai".to_string()),
-                _ => panic!("Unexpected message type"),
-            }
-        }
+        RigMessage::User { content } => flow_user_message(content),
         RigMessage::Assistant { content } => {
-            match content.first() {
-                AssistantContent::Text(Text { text }) => Message::new("ai".to_string(), text, "user".to_string()),
-                _ => panic!("Unexpected message type"),
+            let text = content
+                .iter()
+                .filter_map(|content| {
+                    if let AssistantContent::Text(Text { text }) = content {
+                        Some(text.clone())
+                    } else if let AssistantContent::ToolCall(tool_call) = content {
+                        Some(format!("{}({})", tool_call.function.name, tool_call.function.arguments))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            Message::new(AI_SENDER.to_string(), text, USER_SENDER.to_string()).with_role(MessageRole::Assistant)
+        }
+    }
+}
+
+// Tool results keep the tool as sender, media keeps its source and the text becomes its caption
+fn flow_user_message(content: OneOrMany<UserContent>) -> Message {
+    let mut media = None;
+    let mut texts = Vec::new();
+    let mut tool_id = None;
+
+    for content in content.into_iter() {
+        match content {
+            UserContent::Text(Text { text }) => texts.push(text),
+            UserContent::ToolResult(tool_result) => {
+                tool_id = Some(tool_result.id);
+                texts.extend(tool_result.content.into_iter().filter_map(|content| match content {
+                    ToolResultContent::Text(Text { text }) => Some(text),
+                    ToolResultContent::Image(_) => None,
+                }));
             }
+            UserContent::Image(image) if media.is_none() => {
+                let mime_type = image.media_type.map(|media_type| media_type.to_mime_type());
+                media = Some(MessageType::Image(flow_media(image.data, image.format, mime_type)));
+            }
+            UserContent::Audio(audio) if media.is_none() => {
+                let mime_type = audio.media_type.map(|media_type| media_type.to_mime_type());
+                media = Some(MessageType::Audio(flow_media(audio.data, audio.format, mime_type)));
+            }
+            UserContent::Document(document) if media.is_none() => {
+                let mime_type = document.media_type.map(|media_type| media_type.to_mime_type());
+                media = Some(MessageType::File(flow_media(document.data, document.format, mime_type)));
+            }
+            // A flow message carries a single media
+            UserContent::Image(_) | UserContent::Audio(_) | UserContent::Document(_) => {}
         }
     }
-}
\ No newline at end of file
+
+    let text = texts.join("\n");
+    if let Some(tool_id) = tool_id {
+        return Message::new(tool_id, text, AI_SENDER.to_string()).with_role(MessageRole::Tool);
+    }
+
+    let content = match media {
+        Some(MessageType::Image(media)) => MessageType::Image(with_caption(media, text)),
+        Some(MessageType::Audio(media)) => MessageType::Audio(with_caption(media, text)),
+        Some(MessageType::File(media)) => MessageType::File(with_caption(media, text)),
+        _ => MessageType::Text(text),
+    };
+    Message::new_with_content(USER_SENDER.to_string(), content, AI_SENDER.to_string())
+}
+
+fn flow_media(data: String, format: Option<ContentFormat>, mime_type: Option<&str>) -> MediaContent {
+    let mime_type = mime_type.unwrap_or(UNKNOWN_MIME_TYPE).to_string();
+    let source = match format {
+        Some(ContentFormat::Base64) => match BASE64.decode(&data) {
+            Ok(bytes) => MediaSource::Bytes(bytes),
+            Err(_) => MediaSource::Url(data),
+        },
+        _ => MediaSource::Url(data),
+    };
+    MediaContent::new(source, mime_type)
+}
+
+fn with_caption(media: MediaContent, caption: String) -> MediaContent {
+    if caption.is_empty() { media } else { media.with_caption(caption) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(sender: &str, text: &str, role: MessageRole) -> Message {
+        Message::new(sender.to_string(), text.to_string(), "someone".to_string()).with_role(role)
+    }
+
+    fn round_trip(message: Message) -> Message {
+        flow_message_adapter(rig_message_adapter(message).unwrap())
+    }
+
+    #[test]
+    fn test_user_and_assistant_roles() {
+        let user = rig_message_adapter(message("5511999999999", "Hi", MessageRole::User)).unwrap();
+        assert!(matches!(&user, RigMessage::User { content }
+            if matches!(content.first(), UserContent::Text(Text { text }) if text == "Hi")));
+
+        let assistant = rig_message_adapter(message(AI_SENDER, "Hello!", MessageRole::Assistant)).unwrap();
+        assert!(matches!(&assistant, RigMessage::Assistant { content }
+            if matches!(content.first(), AssistantContent::Text(Text { text }) if text == "Hello!")));
+
+        let round_trip = flow_message_adapter(assistant);
+        assert_eq!(round_trip.role, MessageRole::Assistant);
+        assert_eq!(round_trip.content, MessageType::Text("Hello!".to_string()));
+    }
+
+    #[test]
+    fn test_human_agent_speaks_as_assistant() {
+        let agent = rig_message_adapter(message("maria", "I'll check that for you", MessageRole::HumanAgent)).unwrap();
+        assert!(matches!(&agent, RigMessage::Assistant { content }
+            if matches!(content.first(), AssistantContent::Text(Text { text }) if text == "I'll check that for you")));
+    }
+
+    #[test]
+    fn test_tool_role() {
+        let tool = rig_message_adapter(message("get_order", "{\"status\":\"shipped\"}", MessageRole::Tool)).unwrap();
+        assert!(matches!(&tool, RigMessage::User { content }
+            if matches!(content.first(), UserContent::ToolResult(result) if result.id == "get_order")));
+
+        let round_trip = flow_message_adapter(tool);
+        assert_eq!(round_trip.role, MessageRole::Tool);
+        assert_eq!(round_trip.sender, "get_order");
+        assert_eq!(round_trip.content, MessageType::Text("{\"status\":\"shipped\"}".to_string()));
+
+        let tool = rig_message_adapter(message("get_order", "Order 42 shipped", MessageRole::Tool)).unwrap();
+        assert!(matches!(&tool, RigMessage::User { content }
+            if matches!(content.first(), UserContent::Text(Text { text }) if text == "Result of tool get_order: Order 42 shipped")));
+    }
+
+    #[test]
+    fn test_system_role_goes_to_preamble() {
+        let messages = vec![
+            message("flow", "Answer in Portuguese", MessageRole::System),
+            message("5511999999999", "Hi", MessageRole::User),
+        ];
+
+        assert!(rig_message_adapter(messages[0].clone()).is_none());
+        assert_eq!(rig_preamble(&messages), Some("Answer in Portuguese".to_string()));
+        assert_eq!(rig_preamble(&messages[1..]), None);
+    }
+
+    #[test]
+    fn test_media_round_trip() {
+        let image = MediaContent::new(MediaSource::Bytes(vec![137, 80, 78, 71]), "image/png".to_string())
+            .with_caption("My receipt".to_string());
+        let user = Message::new_with_content("5511999999999".to_string(), MessageType::Image(image.clone()), AI_SENDER.to_string());
+        assert_eq!(round_trip(user).content, MessageType::Image(image));
+
+        let file = MediaContent::new(MediaSource::Bytes(vec![37, 80, 68, 70]), "application/pdf".to_string());
+        let user = Message::new_with_content("5511999999999".to_string(), MessageType::File(file.clone()), AI_SENDER.to_string());
+        assert_eq!(round_trip(user).content, MessageType::File(file));
+    }
+
+    #[test]
+    fn test_url_media_as_text() {
+        let image = MediaContent::new(MediaSource::Url("https://example.com/receipt.png".to_string()), "image/png".to_string());
+        let user = Message::new_with_content("5511999999999".to_string(), MessageType::Image(image), AI_SENDER.to_string());
+
+        let user = rig_message_adapter(user).unwrap();
+        assert!(matches!(&user, RigMessage::User { content }
+            if matches!(content.first(), UserContent::Text(Text { text }) if text == "Image: https://example.com/receipt.png")));
+    }
+
+    #[test]
+    fn test_unsupported_media_as_text() {
+        let file = MediaContent::new(MediaSource::Bytes(vec![80, 75, 3, 4]), "application/zip".to_string())
+            .with_file_name("photos.zip".to_string());
+        let user = Message::new_with_content("5511999999999".to_string(), MessageType::File(file), AI_SENDER.to_string());
+
+        let user = rig_message_adapter(user).unwrap();
+        assert!(matches!(&user, RigMessage::User { content }
+            if matches!(content.first(), UserContent::Text(Text { text }) if text == "File: photos.zip")));
+
+        let image = MediaContent::new(MediaSource::Bytes(vec![71, 73, 70]), "image/gif".to_string());
+        let user = Message::new_with_content("5511999999999".to_string(), MessageType::Image(image), AI_SENDER.to_string());
+        assert_eq!(round_trip(user).content, MessageType::Text("Image (image/gif)".to_string()));
+    }
+
+    #[test]
+    fn test_assistant_media_as_text() {
+        let image = MediaContent::new(MediaSource::Url("https://example.com/map.png".to_string()), "image/png".to_string());
+        let assistant = Message::new_with_content(AI_SENDER.to_string(), MessageType::Image(image), "someone".to_string())
+            .with_role(MessageRole::Assistant);
+        assert_eq!(round_trip(assistant).content, MessageType::Text("Image: https://example.com/map.png".to_string()));
+    }
+}
@@ -1,6 +1,12 @@
 use async_trait::async_trait;
-use bson::{doc};
-use core_flow::flow::conversation::{Conversation, ConversationRepository, Message, MessageType};
+use bson::{doc, spec::BinarySubtype, Binary};
+use core_flow::{
+    flow::conversation::{
+        Conversation, ConversationRepository, ConversationStatus, Location, MediaContent, MediaSource, Message,
+        MessageRole, MessageType, QuickReplies, QuickReply,
+    },
+    graph::node::node_context::NodeContext,
+};
 use mongodb::{Client, Collection, Database};
 use serde::{Deserialize, Serialize};
 use std::result::Result;
@@ -12,6 +18,10 @@ struct ConversationDocument {
     pub history: Vec<MessageDocument>,
     pub current_node_id: String,
     pub timeout: i16,
+    #[serde(default)]
+    pub context: NodeContext,
+    #[serde(default)]
+    pub status: ConversationStatus,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,14 +31,54 @@ struct MessageDocument {
     pub sender: String,
     pub recipient: String,
     pub timestamp: String,
+    // Missing on messages saved before roles existed, guessed from the sender then
+    #[serde(default)]
+    pub role: Option<MessageRole>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 enum MessageTypeDocument {
     Text(String),
-    Image,
-    Audio,
+    Image(MediaDocument),
+    Audio(MediaDocument),
+    File(MediaDocument),
+    Location(LocationDocument),
+    QuickReplies(QuickRepliesDocument),
+}
+
+// Exactly one of url and bytes is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MediaDocument {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<Binary>,
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LocationDocument {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub name: Option<String>,
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QuickRepliesDocument {
+    pub text: String,
+    pub replies: Vec<QuickReplyDocument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QuickReplyDocument {
+    pub id: String,
+    pub title: String,
 }
 
 impl From<Conversation> for ConversationDocument {
@@ -40,16 +90,28 @@ impl From<Conversation> for ConversationDocument {
             history: conversation.get_messages().into_iter().map(|msg| msg.into()).collect(),
             current_node_id,
             timeout: 0, // Default timeout since it's not accessible from Conversation
+            context: conversation.get_context(),
+            status: conversation.get_status(),
         }
     }
 }
 
-impl From<ConversationDocument> for Conversation {
-    fn from(doc: ConversationDocument) -> Self {
-        let mut conversation = Conversation::new(doc.id, doc.current_node_id);
-        let messages: Vec<Message> = doc.history.into_iter().map(|msg| msg.into()).collect();
+// Fails on stored documents that break the invariants of the message types
+impl TryFrom<ConversationDocument> for Conversation {
+    type Error = String;
+
+    fn try_from(doc: ConversationDocument) -> Result<Self, Self::Error> {
+        let mut conversation = Conversation::new(doc.id.clone(), doc.current_node_id);
+        let messages = doc
+            .history
+            .into_iter()
+            .map(Message::try_from)
+            .collect::<Result<Vec<Message>, String>>()
+            .map_err(|error| format!("Invalid conversation {}: {}", doc.id, error))?;
         conversation.add_messages(messages);
-        conversation
+        conversation.set_context(doc.context);
+        conversation.set_status(doc.status);
+        Ok(conversation)
     }
 }
 
@@ -61,19 +123,27 @@ impl From<Message> for MessageDocument {
             sender: message.sender,
             recipient: message.recipient,
             timestamp: message.timestamp,
+            role: Some(message.role),
         }
     }
 }
 
-impl From<MessageDocument> for Message {
-    fn from(doc: MessageDocument) -> Self {
-        Message::new_with_id(
+impl TryFrom<MessageDocument> for Message {
+    type Error = String;
+
+    fn try_from(doc: MessageDocument) -> Result<Self, Self::Error> {
+        let content = MessageType::try_from(doc.content).map_err(|error| format!("message {}: {}", doc.id, error))?;
+        let message = Message::new_with_id(
             doc.id,
             doc.sender,
-            doc.content.into(),
+            content,
             doc.recipient,
             doc.timestamp,
-        )
+        );
+        Ok(match doc.role {
+            Some(role) => message.with_role(role),
+            None => message,
+        })
     }
 }
 
@@ -81,19 +151,75 @@ impl From<MessageType> for MessageTypeDocument {
     fn from(msg_type: MessageType) -> Self {
         match msg_type {
             MessageType::Text(text) => MessageTypeDocument::Text(text),
-            MessageType::Image => MessageTypeDocument::Image,
-            MessageType::Audio => MessageTypeDocument::Audio,
+            MessageType::Image(media) => MessageTypeDocument::Image(media.into()),
+            MessageType::Audio(media) => MessageTypeDocument::Audio(media.into()),
+            MessageType::File(media) => MessageTypeDocument::File(media.into()),
+            MessageType::Location(location) => MessageTypeDocument::Location(LocationDocument {
+                latitude: location.latitude,
+                longitude: location.longitude,
+                name: location.name,
+                address: location.address,
+            }),
+            MessageType::QuickReplies(quick_replies) => MessageTypeDocument::QuickReplies(QuickRepliesDocument {
+                text: quick_replies.text,
+                replies: quick_replies
+                    .replies
+                    .into_iter()
+                    .map(|reply| QuickReplyDocument { id: reply.id, title: reply.title })
+                    .collect(),
+            }),
         }
     }
 }
 
-impl From<MessageTypeDocument> for MessageType {
-    fn from(doc: MessageTypeDocument) -> Self {
-        match doc {
+impl TryFrom<MessageTypeDocument> for MessageType {
+    type Error = String;
+
+    fn try_from(doc: MessageTypeDocument) -> Result<Self, Self::Error> {
+        let content = match doc {
             MessageTypeDocument::Text(text) => MessageType::Text(text),
-            MessageTypeDocument::Image => MessageType::Image,
-            MessageTypeDocument::Audio => MessageType::Audio,
-        }
+            MessageTypeDocument::Image(media) => MessageType::Image(media.try_into()?),
+            MessageTypeDocument::Audio(media) => MessageType::Audio(media.try_into()?),
+            MessageTypeDocument::File(media) => MessageType::File(media.try_into()?),
+            MessageTypeDocument::Location(location) => MessageType::Location(Location {
+                latitude: location.latitude,
+                longitude: location.longitude,
+                name: location.name,
+                address: location.address,
+            }),
+            MessageTypeDocument::QuickReplies(quick_replies) => MessageType::QuickReplies(QuickReplies {
+                text: quick_replies.text,
+                replies: quick_replies
+                    .replies
+                    .into_iter()
+                    .map(|reply| QuickReply { id: reply.id, title: reply.title })
+                    .collect(),
+            }),
+        };
+        Ok(content)
+    }
+}
+
+impl From<MediaContent> for MediaDocument {
+    fn from(media: MediaContent) -> Self {
+        let (url, bytes) = match media.source {
+            MediaSource::Url(url) => (Some(url), None),
+            MediaSource::Bytes(bytes) => (None, Some(Binary { subtype: BinarySubtype::Generic, bytes })),
+        };
+        MediaDocument { url, bytes, mime_type: media.mime_type, caption: media.caption, file_name: media.file_name }
+    }
+}
+
+impl TryFrom<MediaDocument> for MediaContent {
+    type Error = String;
+
+    fn try_from(doc: MediaDocument) -> Result<Self, Self::Error> {
+        let source = match (doc.url, doc.bytes) {
+            (Some(url), None) => MediaSource::Url(url),
+            (None, Some(binary)) => MediaSource::Bytes(binary.bytes),
+            _ => return Err("media must have exactly one of url and bytes".to_string()),
+        };
+        Ok(MediaContent { source, mime_type: doc.mime_type, caption: doc.caption, file_name: doc.file_name })
     }
 }
 
@@ -127,7 +253,7 @@ impl ConversationRepository for MongoConversationRepository {
         let filter = doc! { "_id": &conversation_id };
         
         match self.collection.find_one(filter, None).await? {
-            Some(doc) => Ok(doc.into()),
+            Some(doc) => Ok(Conversation::try_from(doc)?),
             None => Err(format!("Conversation with id {} not found", conversation_id).into()),
         }
     }
@@ -139,7 +265,7 @@ impl ConversationRepository for MongoConversationRepository {
         let filter = doc! { "history.recipient": &recipient };
         
         match self.collection.find_one(filter, None).await? {
-            Some(doc) => Ok(doc.into()),
+            Some(doc) => Ok(Conversation::try_from(doc)?),
             None => Err(format!("Conversation with recipient {} not found", recipient).into()),
         }
     }
@@ -151,7 +277,7 @@ impl ConversationRepository for MongoConversationRepository {
         let filter = doc! { "history.sender": &sender };
         
         match self.collection.find_one(filter, None).await? {
-            Some(doc) => Ok(doc.into()),
+            Some(doc) => Ok(Conversation::try_from(doc)?),
             None => Err(format!("Conversation with sender {} not found", sender).into()),
         }
     }
@@ -166,13 +292,13 @@ impl ConversationRepository for MongoConversationRepository {
             .build();
         
         match self.collection.find_one(filter, options).await? {
-            Some(doc) => Ok(doc.into()),
+            Some(doc) => Ok(Conversation::try_from(doc)?),
             None => Err(format!("No conversation found for recipient {}", recipient).into()),
         }
     }
 
     async fn save_conversation(
-        &mut self,
+        &self,
         conversation: Conversation,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let doc: ConversationDocument = conversation.into();
@@ -181,7 +307,7 @@ impl ConversationRepository for MongoConversationRepository {
     }
 
     async fn update_conversation(
-        &mut self,
+        &self,
         conversation_id: String,
         conversation: Conversation,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -214,7 +340,7 @@ mod tests {
         let client_options = ClientOptions::parse("mongodb://localhost:27017").await.unwrap();
         let client = Client::with_options(client_options).unwrap();
         
-        let mut repo = MongoConversationRepository::new(client, "test_db").await.unwrap();
+        let repo = MongoConversationRepository::new(client, "test_db").await.unwrap();
         
         let conversation = Conversation::new("test_id".to_string(), "node_1".to_string());
         
@@ -227,4 +353,71 @@ mod tests {
         assert!(retrieved.is_ok());
         assert_eq!(retrieved.unwrap().id, "test_id");
     }
+
+    #[test]
+    fn test_conversation_document_keeps_status() {
+        let mut conversation = Conversation::new("test_id".to_string(), "node_1".to_string());
+        conversation.set_status(ConversationStatus::Completed);
+
+        let document: ConversationDocument = conversation.clone().into();
+        let bson_document = bson::to_document(&document).unwrap();
+        assert_eq!(bson_document.get_str("status").unwrap(), "completed");
+
+        let restored = Conversation::try_from(document).unwrap();
+        assert_eq!(restored, conversation);
+    }
+
+    #[test]
+    fn test_conversation_document_keeps_multimodal_messages() {
+        let mut conversation = Conversation::new("test_id".to_string(), "node_1".to_string());
+        let contents = vec![
+            MessageType::Image(
+                MediaContent::new(MediaSource::Url("https://cdn.example.com/receipt.jpg".to_string()), "image/jpeg".to_string())
+                    .with_caption("My receipt".to_string()),
+            ),
+            MessageType::Audio(MediaContent::new(MediaSource::Bytes(vec![0, 1, 2]), "audio/ogg".to_string())),
+            MessageType::File(
+                MediaContent::new(MediaSource::Bytes(vec![37, 80, 68, 70]), "application/pdf".to_string())
+                    .with_file_name("invoice.pdf".to_string()),
+            ),
+            MessageType::Location(Location { latitude: 40.4, longitude: -3.7, name: Some("Office".to_string()), address: None }),
+            MessageType::QuickReplies(QuickReplies {
+                text: "Anything else?".to_string(),
+                replies: vec![QuickReply { id: "no".to_string(), title: "No, thanks".to_string() }],
+            }),
+        ];
+        for content in contents {
+            conversation.add_message(Message::new_with_content("user".to_string(), content, "ai".to_string()));
+        }
+        conversation.add_message(
+            Message::new("lookup_order".to_string(), "shipped".to_string(), "ai".to_string()).with_role(MessageRole::Tool),
+        );
+
+        let document: ConversationDocument = conversation.clone().into();
+        let bson_document = bson::to_document(&document).unwrap();
+        let document: ConversationDocument = bson::from_document(bson_document).unwrap();
+
+        let restored = Conversation::try_from(document).unwrap();
+        assert_eq!(restored, conversation);
+    }
+
+    #[test]
+    fn test_conversation_document_rejects_media_without_source() {
+        let document: ConversationDocument = bson::from_document(doc! {
+            "_id": "test_id",
+            "history": [{
+                "id": "message_1",
+                "content": { "type": "Image", "data": { "mime_type": "image/png" } },
+                "sender": "user",
+                "recipient": "ai",
+                "timestamp": "2025-03-01T10:00:00Z",
+            }],
+            "current_node_id": "node_1",
+            "timeout": 0,
+        })
+        .unwrap();
+
+        let error = Conversation::try_from(document).unwrap_err();
+        assert!(error.contains("message_1"), "{}", error);
+    }
 }
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use core_flow::{graph::{
     action::{action::Action, utils::vars_parser::parse_input_vars},
+    config_error::ConfigError,
+    config_schema::{ComponentSchema, ConfigSchema, FieldType},
     node::node_context::{NodeContext, Value},
 }};
 use reqwest::{header, Client};
@@ -15,30 +17,46 @@ pub struct SendMessage {
 }
 
 impl SendMessage {
+    /// Http client with the timeouts used to deliver messages, meant to be shared by every
+    /// send_message action of a flow
+    pub fn create_client() -> Result<Client, reqwest::Error> {
+        Client::builder()
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+    }
+
+    pub fn schema() -> ComponentSchema {
+        ComponentSchema::new()
+            .with_config(ConfigSchema::new().with_field("post_endpoint", FieldType::String, "Url every message is posted to"))
+            .with_input_var("messages", "Messages to deliver")
+    }
+
+    // config: { "post_endpoint": <url> }
     pub fn create_send_message(
+        client: &Client,
         config: &JsonValue,
         input_vars: &JsonValue,
         _: &JsonValue,
-    ) -> Box<dyn Action> {
-        let endpoint = config["post_endpoint"].as_str().unwrap().to_string();
-        
+    ) -> Result<Box<dyn Action>, ConfigError> {
+        let endpoint = match config.get("post_endpoint") {
+            Some(JsonValue::String(endpoint)) => endpoint.clone(),
+            Some(value) => {
+                return Err(ConfigError::invalid_field("post_endpoint", format!("expected a string, found {}", value)));
+            }
+            None => return Err(ConfigError::MissingField("post_endpoint".to_string())),
+        };
+
         // Validate endpoint URL
         if endpoint.is_empty() {
-            panic!("post_endpoint cannot be empty");
+            return Err(ConfigError::invalid_field("post_endpoint", "cannot be empty"));
         }
-        
-        // Create HTTP client with timeout
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .build()
-            .expect("Failed to create HTTP client");
-            
-        Box::new(SendMessage {
+
+        Ok(Box::new(SendMessage {
             post_endpoint: endpoint,
             input_vars: input_vars.clone(),
-            client,
-        })
+            client: client.clone(),
+        }))
     }
 }
 
@@ -155,7 +173,8 @@ mod tests {
         });
 
         // Create SendMessage action
-        let action = SendMessage::create_send_message(&config, &input_vars, &json!({}));
+        let client = SendMessage::create_client().unwrap();
+        let action = SendMessage::create_send_message(&client, &config, &input_vars, &json!({})).unwrap();
 
         // Create test context
         let mut context = NodeContext::new();
@@ -189,7 +208,8 @@ mod tests {
         });
 
         // Create SendMessage action
-        let action = SendMessage::create_send_message(&config, &input_vars, &json!({}));
+        let client = SendMessage::create_client().unwrap();
+        let action = SendMessage::create_send_message(&client, &config, &input_vars, &json!({})).unwrap();
 
         // Create test context
         let mut context = NodeContext::new();
@@ -207,4 +227,13 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_create_send_message_without_endpoint() {
+        let client = SendMessage::create_client().unwrap();
+
+        let result = SendMessage::create_send_message(&client, &json!({}), &json!({}), &json!([]));
+
+        assert!(matches!(result, Err(ConfigError::MissingField(field)) if field == "post_endpoint"));
+    }
 }
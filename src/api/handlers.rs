@@ -1,57 +1,76 @@
-use axum::extract::{Json, Path, State};
-use core_flow::flow::conversation::{Conversation, ConversationRepository, Message};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use core_flow::{
+    flow::conversation::{ConversationRepository, Message},
+    graph::node::node_context::Value,
+};
 use std::{collections::HashMap, sync::Arc};
-use tokio::sync::Mutex;
 
 use crate::api::{
     models::{
-        ConversationResponse, CreateConversationRequest, CreateConversationResponse,
-        SendMessageRequest, TriggerConversationRequest,
+        CatalogResponse, ConversationResponse, CreateConversationRequest, CreateConversationResponse,
+        SchemaResponse, SendMessageRequest, TriggerConversationRequest,
     },
     state::AppState,
 };
 
 // Helper function to handle conversation triggering and response creation
 async fn execute_conversation_flow(
-    state: &mut AppState,
+    state: &AppState,
     conversation_id: String,
     message: Message,
 ) -> Json<ConversationResponse> {
     let result = state
         .flow_manager
-        .trigger_conversation(conversation_id, message)
+        .trigger_conversation(conversation_id, message.clone())
         .await;
 
     match result {
-        Ok(context) => Json(ConversationResponse {
-            success: true,
-            context: context.variables,
-            error_message: None,
-        }),
+        Ok(mut context) => {
+            let messages = match context.transient.remove("messages") {
+                Some(Value::Messages(messages)) => produced_messages(&message, messages),
+                _ => Vec::new(),
+            };
+            Json(ConversationResponse {
+                success: true,
+                context: context.variables,
+                messages,
+                error_message: None,
+            })
+        }
         Err(e) => {
             println!("Error in trigger conversation: {}", e);
             return Json(ConversationResponse {
                 success: false,
                 context: HashMap::new(),
+                messages: Vec::new(),
                 error_message: Some(format!("Failed to process conversation: {}", e)),
             });
         }
     }
 }
 
+// The run sees the whole history, only what comes after the trigger message is new
+fn produced_messages(trigger_message: &Message, mut messages: Vec<Message>) -> Vec<Message> {
+    match messages.iter().rposition(|message| message.get_id() == trigger_message.get_id()) {
+        Some(position) => messages.split_off(position + 1),
+        None => Vec::new(),
+    }
+}
+
 pub async fn create_conversation(
-    State(state): State<Arc<Mutex<AppState>>>,
+    State(state): State<Arc<AppState>>,
     Json(payload): Json<CreateConversationRequest>,
 ) -> Json<CreateConversationResponse> {
-    let mut state = state.lock().await;
-    let conversation = Conversation::new(payload.conversation_id.clone(), payload.initial_node);
-
+    // Conversations always start on the start node declared by the flow
     match state
-        .mongo_conversation_repository
-        .save_conversation(conversation.clone())
+        .flow_manager
+        .create_conversation(payload.conversation_id)
         .await
     {
-        Ok(_) => Json(CreateConversationResponse {
+        Ok(conversation) => Json(CreateConversationResponse {
             conversation_id: conversation.id,
         }),
         Err(_) => Json(CreateConversationResponse {
@@ -61,60 +80,201 @@ pub async fn create_conversation(
 }
 
 pub async fn send_message(
-    State(state): State<Arc<Mutex<AppState>>>,
+    State(state): State<Arc<AppState>>,
     Path(conversation_id): Path<String>,
     Json(payload): Json<SendMessageRequest>,
 ) -> Json<ConversationResponse> {
-    let mut state = state.lock().await;
-
     let message = Message::new(payload.sender, payload.content, payload.recipient);
 
     println!("Trigerring conversation with message: {:?}", message);
 
     // Trigger conversation through flow manager
-    execute_conversation_flow(&mut state, conversation_id, message.clone()).await
+    execute_conversation_flow(&state, conversation_id, message.clone()).await
 }
 
 pub async fn trigger_conversation(
-    State(state): State<Arc<Mutex<AppState>>>,
+    State(state): State<Arc<AppState>>,
     Json(payload): Json<TriggerConversationRequest>,
 ) -> Json<ConversationResponse> {
-    let mut state = state.lock().await;
-
     let message = Message::new(
         payload.sender.clone(),
         payload.content,
         payload.recipient.clone(),
     );
 
-    // Try to get existing conversation or create a new one
-    let conversation_id = match state
+    // Try to get existing conversation or create a new one, a completed conversation starts over.
+    // Errors are turned into strings right away, flow manager errors can't be held across an await.
+    let conversation = match state
         .mongo_conversation_repository
         .get_conversation_by_recipient(payload.sender.clone())
         .await
     {
-        Ok(conversation) => conversation.id,
+        Ok(conversation) if conversation.is_completed() => {
+            state
+                .flow_manager
+                .restart_conversation(conversation.id)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        Ok(conversation) => Ok(conversation),
         Err(_) => {
-            // Create new conversation
-            let new_conversation =
-                Conversation::new(payload.recipient.clone(), "first_node".to_string());
-
-            match state
-                .mongo_conversation_repository
-                .save_conversation(new_conversation.clone())
+            state
+                .flow_manager
+                .create_conversation(payload.recipient.clone())
                 .await
-            {
-                Ok(_) => new_conversation.id,
-                Err(e) => {
-                    return Json(ConversationResponse {
-                        success: false,
-                        context: HashMap::new(),
-                        error_message: Some(format!("Failed to create new conversation: {}", e)),
-                    });
-                }
-            }
+                .map_err(|e| e.to_string())
         }
     };
 
-    execute_conversation_flow(&mut state, conversation_id, message).await
+    let conversation_id = match conversation {
+        Ok(conversation) => conversation.id,
+        Err(e) => {
+            return Json(ConversationResponse {
+                success: false,
+                context: HashMap::new(),
+                messages: Vec::new(),
+                error_message: Some(format!("Failed to create new conversation: {}", e)),
+            });
+        }
+    };
+
+    execute_conversation_flow(&state, conversation_id, message).await
+}
+
+pub async fn get_action_schema(
+    State(state): State<Arc<AppState>>,
+    Path(action_type): Path<String>,
+) -> Result<Json<SchemaResponse>, StatusCode> {
+    let registration = state
+        .action_registry
+        .get_actions()
+        .get(&action_type)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(SchemaResponse {
+        component_type: action_type,
+        json_schema: registration.get_schema().to_json_schema(),
+    }))
+}
+
+pub async fn get_condition_schema(
+    State(state): State<Arc<AppState>>,
+    Path(condition_type): Path<String>,
+) -> Result<Json<SchemaResponse>, StatusCode> {
+    // Expressions and combinators aren't registered, the registry knows their schema anyway
+    let schema = state
+        .condition_registry
+        .schema(&condition_type)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(SchemaResponse {
+        component_type: condition_type,
+        json_schema: schema.to_json_schema(),
+    }))
+}
+
+// Action and condition types flows on this server can use
+pub async fn get_catalog(State(state): State<Arc<AppState>>) -> Json<CatalogResponse> {
+    Json(CatalogResponse {
+        actions: state.action_registry.catalog(),
+        conditions: state.condition_registry.catalog(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use core_flow::{
+        flow::flow_manager::FlowManager,
+        graph::{
+            action::action_registry::ActionRegistry, condition::condition_registry::ConditionRegistry,
+            flow_graph::flow_graph::FlowGraph,
+        },
+    };
+    use implementations::conversation_repository::MongoConversationRepository;
+    use mongodb::Client;
+
+    use crate::api::repository::MemoryConversationRepository;
+
+    use super::*;
+
+    // The mongo client connects lazily, the handlers under test never reach it
+    async fn create_state(json_graph: &str) -> Arc<AppState> {
+        let mut action_registry = ActionRegistry::new();
+        action_registry.register_builtin_actions();
+        let mut condition_registry = ConditionRegistry::new();
+        condition_registry.register_builtin_conditions();
+
+        let flow_graph = FlowGraph::from_json(json_graph, &action_registry, &condition_registry).unwrap();
+        let client = Client::with_uri_str("mongodb://localhost:27017").await.unwrap();
+
+        Arc::new(AppState {
+            flow_manager: FlowManager::new(Arc::new(MemoryConversationRepository::new()), Arc::new(flow_graph)),
+            mongo_conversation_repository: Arc::new(MongoConversationRepository::new(client, "test_db").await.unwrap()),
+            action_registry: Arc::new(action_registry),
+            condition_registry: Arc::new(condition_registry),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_send_message_returns_the_reply() {
+        let state = create_state(
+            r#"{
+                "start_node_id": "welcome",
+                "nodes": [
+                    {
+                        "id": "welcome",
+                        "node_type": "conversational",
+                        "name": "Welcome",
+                        "description": "Welcome description",
+                        "terminal": true,
+                        "node_context": {
+                            "variables": {}
+                        },
+                        "actions": [
+                            {
+                                "name": "greeting",
+                                "action_type": "templated_message",
+                                "config": {
+                                    "id": "greeting",
+                                    "name": "Greeting",
+                                    "template": "Hi {{ trigger_message }}!"
+                                },
+                                "input_vars": {},
+                                "output_vars": ["messages"]
+                            }
+                        ]
+                    }
+                ],
+                "edges": []
+            }"#,
+        )
+        .await;
+        let conversation = state.flow_manager.create_conversation("conversation".to_string()).await.unwrap();
+
+        let Json(response) = send_message(
+            State(state),
+            Path(conversation.id),
+            Json(SendMessageRequest {
+                content: "Ada".to_string(),
+                sender: "+34600000000".to_string(),
+                recipient: "ai".to_string(),
+            }),
+        )
+        .await;
+
+        assert!(response.success, "{:?}", response.error_message);
+        assert_eq!(response.messages.len(), 1);
+        assert_eq!(response.messages[0].content.text(), Some("Hi Ada!"));
+    }
+
+    #[tokio::test]
+    async fn test_condition_schema_for_every_catalog_entry() {
+        let state = create_state(r#"{"nodes": [], "edges": []}"#).await;
+
+        let Json(catalog) = get_catalog(State(state.clone())).await;
+        for entry in catalog.conditions {
+            let schema = get_condition_schema(State(state.clone()), Path(entry.component_type.clone())).await;
+            assert!(schema.is_ok(), "no schema for {}", entry.component_type);
+        }
+    }
 }
@@ -1,4 +1,7 @@
-use core_flow::graph::node::node_context::Value;
+use core_flow::{
+    flow::conversation::Message,
+    graph::{catalog::CatalogEntry, node::node_context::Value},
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -6,7 +9,6 @@ use std::collections::HashMap;
 #[derive(Deserialize)]
 pub struct CreateConversationRequest {
     pub conversation_id: String,
-    pub initial_node: String,
 }
 
 #[derive(Deserialize)]
@@ -27,7 +29,10 @@ pub struct TriggerConversationRequest {
 #[derive(Serialize, Deserialize)]
 pub struct ConversationResponse {
     pub success: bool,
+    // Conversation variables, in the JSON encoding of `Value`
     pub context: HashMap<String, Value>,
+    // Messages the flow produced for this trigger, such as the AI reply
+    pub messages: Vec<Message>,
     pub error_message: Option<String>,
 }
 
@@ -35,3 +40,16 @@ pub struct ConversationResponse {
 pub struct CreateConversationResponse {
     pub conversation_id: String,
 }
+
+#[derive(Serialize)]
+pub struct SchemaResponse {
+    // Action or condition type the schema belongs to
+    pub component_type: String,
+    pub json_schema: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct CatalogResponse {
+    pub actions: Vec<CatalogEntry>,
+    pub conditions: Vec<CatalogEntry>,
+}
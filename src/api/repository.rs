@@ -1,18 +1,18 @@
 use core_flow::flow::conversation::{Conversation, ConversationRepository};
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::RwLock};
 use chrono::{Utc, DateTime};
 use async_trait::async_trait;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug)]
 pub struct MemoryConversationRepository {
-    pub conversations: HashMap<String, Conversation>,
+    pub conversations: RwLock<HashMap<String, Conversation>>,
 }
 
 impl MemoryConversationRepository {
     #[allow(dead_code)]
     pub fn new() -> Self {
         MemoryConversationRepository {
-            conversations: HashMap::new(),
+            conversations: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -23,7 +23,7 @@ impl ConversationRepository for MemoryConversationRepository {
         &self,
         conversation_id: String,
     ) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
-        match self.conversations.get(&conversation_id) {
+        match self.conversations.read().unwrap().get(&conversation_id) {
             Some(conversation) => Ok(conversation.clone()),
             None => Err(Box::new(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -33,25 +33,28 @@ impl ConversationRepository for MemoryConversationRepository {
     }
 
     async fn update_conversation(
-        &mut self,
+        &self,
         conversation_id: String,
         conversation: Conversation,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.conversations.insert(conversation_id, conversation);
+        self.conversations.write().unwrap().insert(conversation_id, conversation);
         Ok(())
     }
 
     async fn save_conversation(
-        &mut self,
+        &self,
         conversation: Conversation,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         self.conversations
+            .write()
+            .unwrap()
             .insert(conversation.id.to_string(), conversation);
         Ok(())
     }
 
     async fn get_conversation_by_recipient(&self, recipient: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
-        let result = self.conversations.values().find(|conversation| {
+        let conversations = self.conversations.read().unwrap();
+        let result = conversations.values().find(|conversation| {
             conversation.get_messages().iter().any(|msg| msg.recipient == recipient)
         });
         
@@ -65,7 +68,8 @@ impl ConversationRepository for MemoryConversationRepository {
     }
 
     async fn get_conversation_by_sender(&self, sender: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
-        let result = self.conversations.values().find(|conversation| {
+        let conversations = self.conversations.read().unwrap();
+        let result = conversations.values().find(|conversation| {
             conversation.get_messages().iter().any(|msg| msg.sender == sender)
         });
         
@@ -79,7 +83,8 @@ impl ConversationRepository for MemoryConversationRepository {
     }
 
     async fn get_last_conversation_by_recipient(&self, recipient: String) -> Result<Conversation, Box<dyn std::error::Error + Send + Sync>> {
-        let result = self.conversations.values().max_by_key(|conversation| {
+        let conversations = self.conversations.read().unwrap();
+        let result = conversations.values().max_by_key(|conversation| {
             let conv_messages = conversation.get_messages();
             let messages = conv_messages.iter().filter(|msg| msg.recipient == recipient);
             let last_message = messages.last();
@@ -1,7 +1,15 @@
-use core_flow::flow::flow_manager::FlowManager;
+use std::sync::Arc;
+
+use core_flow::{
+    flow::flow_manager::FlowManager,
+    graph::{action::action_registry::ActionRegistry, condition::condition_registry::ConditionRegistry},
+};
 use implementations::conversation_repository::MongoConversationRepository;
 
 pub struct AppState {
     pub flow_manager: FlowManager,
-    pub mongo_conversation_repository: MongoConversationRepository,
+    pub mongo_conversation_repository: Arc<MongoConversationRepository>,
+    // Registries the flow was loaded with, exposed so editors know what they can use
+    pub action_registry: Arc<ActionRegistry>,
+    pub condition_registry: Arc<ConditionRegistry>,
 }
@@ -1,20 +1,19 @@
 mod api;
 use mongodb::{options::ClientOptions, Client};
 
-use axum::{routing::post, Router};
+use axum::{routing::{get, post}, Router};
 use core_flow::{
     flow::{
         flow_manager::FlowManager,
     },
     graph::{
-        action::action_registry::ActionRegistry, 
+        action::action_registry::{ActionRegistration, ActionRegistry},
         condition::condition_registry::ConditionRegistry, 
-        flow_graph::flow_graph::FlowGraph,
+        flow_graph::flow_graph::{FlowError, FlowGraph},
     },
 };
 use implementations::{ai_action::ai_action::AIAction, conversation_repository::MongoConversationRepository, send_message::send_message::SendMessage};
 use std::sync::Arc;
-use tokio::sync::Mutex;
 
 use api::{handlers, AppState};
 
@@ -22,18 +21,44 @@ use api::{handlers, AppState};
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let client_options = ClientOptions::parse("mongodb://localhost:27017").await.unwrap();
     let client = Client::with_options(client_options).unwrap();
-    let conversation_repository = MongoConversationRepository::new(
-        client.clone(),
+    let conversation_repository = Arc::new(MongoConversationRepository::new(
+        client,
         "path_flow_db"
-    ).await?;
+    ).await?);
 
     let mut action_registry = ActionRegistry::new();
-    let condition_registry = ConditionRegistry::new();
-    action_registry.register_action("ai_action", AIAction::create_ai_action);
-    action_registry.register_action("send_message", SendMessage::create_send_message);
+    let mut condition_registry = ConditionRegistry::new();
+    condition_registry.register_builtin_conditions();
+    action_registry.register_builtin_actions();
+    // One Gemini client is shared by every ai_action, without an api key only flows using it fail to load
+    let gemini_client = AIAction::create_client();
+    action_registry.register(
+        "ai_action",
+        ActionRegistration::new(move |config, input_vars, output_vars| {
+            let client = gemini_client.as_ref().map_err(Clone::clone)?;
+            AIAction::create_ai_action(client, config, input_vars, output_vars)
+        })
+        .with_schema(AIAction::schema())
+        .with_name("AI reply")
+        .with_description("Answers the conversation with a Gemini model")
+        .with_category("ai"),
+    );
+    // One http client, and its connection pool, is shared by every send_message action
+    let http_client = SendMessage::create_client()?;
+    action_registry.register(
+        "send_message",
+        ActionRegistration::new(move |config, input_vars, output_vars| {
+            SendMessage::create_send_message(&http_client, config, input_vars, output_vars)
+        })
+        .with_schema(SendMessage::schema())
+        .with_name("Send message")
+        .with_description("Posts messages to an http endpoint")
+        .with_category("messaging"),
+    );
 
     let json_graph = r#"
         {
+            "start_node_id": "first_node",
             "nodes": [
                 {
                     "id": "first_node",
@@ -151,8 +176,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                     "target_node_id": "second_node",
                     "conditions": [
                         {
-                            "condition_type": "positive_condition",
-                            "input_vars": {}
+                            "condition_type": "expression",
+                            "config": { "expression": "true" }
                         }
                     ]
                 },
@@ -162,8 +187,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                     "target_node_id": "third_node",
                     "conditions": [
                         {
-                            "condition_type": "negative_condition",
-                            "input_vars": {}
+                            "condition_type": "expression",
+                            "config": { "expression": "false" }
                         }
                     ]
                 },
@@ -173,23 +198,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                     "target_node_id": "first_node",
                     "conditions": [
                         {
-                            "condition_type": "positive_condition",
-                            "input_vars": {}
+                            "condition_type": "expression",
+                            "config": { "expression": "true" }
                         }
                     ]
                 }
             ]
         }"#;
 
-    let flow_graph = FlowGraph::from_json(json_graph, &action_registry, &condition_registry)
-        .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) })?;
-    let flow_manager = FlowManager::new(Box::new(conversation_repository), flow_graph);
-    let shared_state = Arc::new(Mutex::new(AppState { flow_manager, mongo_conversation_repository: MongoConversationRepository::new(client, "path_flow_db").await? }));
+    let flow_graph = FlowGraph::from_json(json_graph, &action_registry, &condition_registry)?;
+    flow_graph.validate()
+        .map_err(|errors| -> Box<dyn std::error::Error + Send + Sync> { Box::new(FlowError::InvalidGraph(errors)) })?;
+    let flow_manager = FlowManager::new(conversation_repository.clone(), Arc::new(flow_graph));
+    let shared_state = Arc::new(AppState {
+        flow_manager,
+        mongo_conversation_repository: conversation_repository,
+        action_registry: Arc::new(action_registry),
+        condition_registry: Arc::new(condition_registry),
+    });
 
     let app = Router::new()
         .route("/conversations", post(handlers::create_conversation))
         .route("/conversations/{id}/messages", post(handlers::send_message))
         .route("/conversations/trigger", post(handlers::trigger_conversation))
+        .route("/registry/catalog", get(handlers::get_catalog))
+        .route("/schemas/actions/{action_type}", get(handlers::get_action_schema))
+        .route("/schemas/conditions/{condition_type}", get(handlers::get_condition_schema))
         .with_state(shared_state);
 
     println!("Server starting on http://localhost:8000");